@@ -0,0 +1,195 @@
+// Reentrant incremental SAT solver API (IPASIR) as a C ABI, so that the library can be linked
+// into tools and benchmark harnesses written against 'ipasir.h'. Solvers are handed out as
+// opaque pointers to 'Ipasir' and must be freed with 'ipasir_release'.
+use std::{panic, process};
+use std::os::raw::{c_char, c_int, c_void};
+use sat::{self, PartialResult, Solver, IncrementalSolver};
+use sat::formula::{Var, Lit, VarMap};
+use sat::minisat::simp::SimpSolver;
+
+
+pub type TerminateCallback = extern "C" fn(data : *mut c_void) -> c_int;
+pub type LearnCallback = extern "C" fn(data : *mut c_void, clause : *mut i32);
+
+
+pub struct Ipasir {
+    solver      : SimpSolver,
+    vars        : Vec<Var>,          // 'vars[i]' stands for DIMACS variable 'i + 1'.
+    clause      : Vec<Lit>,          // Clause being added, until its terminating zero.
+    assumptions : Vec<Lit>,          // Assumptions of the next call to 'ipasir_solve'.
+    model       : Option<VarMap<bool>>,
+    failed      : Vec<Lit>           // Assumptions of the last unsatisfiable call that were used to refute them.
+}
+
+impl Ipasir {
+    fn new() -> Ipasir {
+        Ipasir { solver      : SimpSolver::new(Default::default())
+               , vars        : Vec::new()
+               , clause      : Vec::new()
+               , assumptions : Vec::new()
+               , model       : None
+               , failed      : Vec::new()
+               }
+    }
+
+    // Variables are created up to the largest one mentioned so far, as in 'dimacs::parse':
+    fn litById(&mut self, lit_id : i32) -> Lit {
+        assert!(lit_id != 0 && lit_id != i32::min_value(), "invalid literal {}", lit_id);
+        let idx = lit_id.abs() as usize;
+        while self.vars.len() < idx {
+            let v = self.solver.newVar(None, true);
+            self.vars.push(v);
+        }
+
+        self.vars[idx - 1].lit(lit_id < 0)
+    }
+
+    // The variable of a literal mentioned before, if it is one:
+    fn varById(&self, lit_id : i32) -> Option<Var> {
+        if lit_id == 0 || lit_id == i32::min_value() {
+            return None;
+        }
+        self.vars.get(lit_id.abs() as usize - 1).cloned()
+    }
+
+    fn solve(&mut self) -> c_int {
+        self.model = None;
+        self.failed.clear();
+        let assumptions = self.assumptions.split_off(0);
+        match self.solver.solveUnder(&assumptions) {
+            PartialResult::SAT(model)         => { self.model = Some(model); 10 }
+            PartialResult::UnSATUnder(failed) => { self.failed = failed; 20 }
+            PartialResult::UnSAT              => { 20 }
+            PartialResult::Interrupted(_)     => { 0 }
+        }
+    }
+
+    fn val(&self, lit_id : i32) -> i32 {
+        let model = self.model.as_ref().expect("ipasir_val is only allowed after a satisfiable call");
+        match self.varById(lit_id).and_then(|v| model.get(&v)) {
+            Some(&val) => { if val == (lit_id > 0) { lit_id } else { -lit_id } }
+            None       => { 0 }
+        }
+    }
+
+    fn isFailed(&self, lit_id : i32) -> bool {
+        match self.varById(lit_id) {
+            Some(v) => { self.failed.contains(&v.lit(lit_id < 0)) }
+            None    => { false }
+        }
+    }
+}
+
+
+// Panics must not unwind into C, so they end the process instead:
+fn guard<T, F : FnOnce() -> T>(f : F) -> T {
+    match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+        Ok(res) => { res }
+        Err(_)  => { process::abort() }
+    }
+}
+
+
+unsafe fn instance<'a>(solver : *mut c_void) -> &'a mut Ipasir {
+    assert!(!solver.is_null());
+    &mut *(solver as *mut Ipasir)
+}
+
+
+#[no_mangle]
+pub extern "C" fn ipasir_signature() -> *const c_char {
+    concat!("minisat-rust ", env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+
+#[no_mangle]
+pub extern "C" fn ipasir_init() -> *mut c_void {
+    guard(|| Box::into_raw(Box::new(Ipasir::new())) as *mut c_void)
+}
+
+
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_release(solver : *mut c_void) {
+    if !solver.is_null() {
+        guard(|| drop(Box::from_raw(solver as *mut Ipasir)));
+    }
+}
+
+
+// Literals of a clause one at a time, the clause ends with a zero:
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_add(solver : *mut c_void, lit_or_zero : i32) {
+    guard(|| {
+        let ipasir = instance(solver);
+        if lit_or_zero == 0 {
+            let clause = ipasir.clause.split_off(0);
+            ipasir.solver.addClause(&clause);
+        } else {
+            let lit = ipasir.litById(lit_or_zero);
+            ipasir.clause.push(lit);
+        }
+    })
+}
+
+
+// Assumptions hold for the next call to 'ipasir_solve' only:
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_assume(solver : *mut c_void, lit : i32) {
+    guard(|| {
+        let ipasir = instance(solver);
+        let lit = ipasir.litById(lit);
+        ipasir.assumptions.push(lit);
+    })
+}
+
+
+// 10 if satisfiable, 20 if not, 0 if interrupted:
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_solve(solver : *mut c_void) -> c_int {
+    guard(|| instance(solver).solve())
+}
+
+
+// 'lit' if it is true in the model, '-lit' if false, 0 if either value will do:
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_val(solver : *mut c_void, lit : i32) -> i32 {
+    guard(|| instance(solver).val(lit))
+}
+
+
+// Whether the assumption 'lit' was used to refute the assumptions of the last call:
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_failed(solver : *mut c_void, lit : i32) -> c_int {
+    guard(|| if instance(solver).isFailed(lit) { 1 } else { 0 })
+}
+
+
+// The callback is asked periodically during search whether to give up:
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_set_terminate(solver : *mut c_void, data : *mut c_void, terminate : Option<TerminateCallback>) {
+    guard(|| {
+        let callback = terminate.map(|terminate| -> sat::TerminateCallback { Box::new(move || terminate(data) != 0) });
+        instance(solver).solver.setTerminateCallback(callback);
+    })
+}
+
+
+// Learnt clauses of at most 'max_length' literals, zero terminated. Every solver variable comes from
+// 'litById', so variable indices follow the DIMACS numbering:
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_set_learn(solver : *mut c_void, data : *mut c_void, max_length : c_int, learn : Option<LearnCallback>) {
+    guard(|| {
+        let callback = learn.map(|learn| -> sat::LearnCallback {
+            Box::new(move |lits : &[Lit], _| {
+                let mut clause : Vec<i32> = lits.iter().map(|lit| {
+                    let id = lit.var().index() as i32 + 1;
+                    if lit.sign() { -id } else { id }
+                }).collect();
+                clause.push(0);
+                learn(data, clause.as_mut_ptr());
+            })
+        });
+        instance(solver).solver.setLearnCallback(max_length.max(0) as usize, callback);
+    })
+}
+
@@ -1,14 +1,21 @@
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
 
+extern crate bzip2;
+extern crate flate2;
 extern crate time;
 extern crate vec_map;
+extern crate xz2;
 #[macro_use] extern crate log;
 
 use std::{fs, path};
 use std::io::{self, Write};
-use sat::{minisat, dimacs, TotalResult, Solver};
+use std::sync::Arc;
+use sat::encode::PbEncoding;
+use sat::formula::{Lit, VarMap};
+use sat::{aiger, allsat, backbone, bmc, maxsat, minisat, dimacs, mus, opb, proof, PartialResult, TotalResult, Solver, IncrementalSolver};
 
+pub mod ipasir;
 pub mod sat;
 
 
@@ -23,36 +30,141 @@ pub struct MainOptions {
     pub solve       : bool,
     pub in_path     : path::PathBuf,
     pub out_path    : Option<path::PathBuf>,
-    pub dimacs_path : Option<path::PathBuf>
+    pub dimacs_path : Option<path::PathBuf>,
+    pub proof_path  : Option<path::PathBuf>,
+    pub proof_bin   : bool,
+    pub enumerate   : Option<Option<usize>>, // Enumerate models instead, at most the given number of them.
+    pub shrink      : bool,
+    pub backbone    : bool, // Compute the backbone instead of a single model.
+    pub threads     : usize // Solve with a portfolio of this many core solvers if more than one.
 }
 
 
+pub struct CheckOptions {
+    pub cnf_path   : path::PathBuf,
+    pub proof_path : path::PathBuf,
+    pub lrat       : bool,
+    pub trim_path  : Option<path::PathBuf>
+}
+
+
+pub struct MusOptions {
+    pub in_path  : path::PathBuf,
+    pub out_path : Option<path::PathBuf>
+}
+
+
+pub struct MaxSatOptions {
+    pub in_path : path::PathBuf
+}
+
+
+pub struct OpbOptions {
+    pub in_path  : path::PathBuf,
+    pub search   : opb::Search,
+    pub encoding : Option<PbEncoding>, // Chosen per constraint if not given.
+    pub native   : bool                // Propagate the constraints natively, only the objective is encoded.
+}
+
+
+pub struct CubeOptions {
+    pub in_path  : path::PathBuf,
+    pub out_path : Option<path::PathBuf>, // Write the cubes in iCNF here instead of solving them.
+    pub threads  : usize
+}
+
+
+pub struct BmcOptions {
+    pub in_path : path::PathBuf,
+    pub depth   : Option<usize> // Unroll until a counterexample is found if not given.
+}
+
+
+// The input is opened once, so that it can come from the standard input:
 pub fn solve(main_opts : MainOptions, solver_opts : SolverOptions) -> io::Result<()> {
+    let (incremental, input) = try!(dimacs::isIncremental(try!(dimacs::open(main_opts.in_path.as_path()))));
+    let input : Box<io::BufRead> = Box::new(input);
+
+    if incremental && (main_opts.threads > 1 || main_opts.enumerate.is_some() || main_opts.backbone) {
+        return Err(io::Error::new(io::ErrorKind::Other, "iCNF input can only be solved query by query"));
+    }
+
+    // Threads do without preprocessing, so that they agree on the variables for sharing clauses:
+    if main_opts.threads > 1 {
+        let settings =
+            match solver_opts {
+                SolverOptions::Core(opts)                  => { opts }
+                SolverOptions::Simp(opts) if !main_opts.pre => { opts.core }
+                SolverOptions::Simp(_)                     => {
+                    return Err(io::Error::new(io::ErrorKind::Other, "a portfolio of threads does no preprocessing"));
+                }
+            };
+
+        if main_opts.proof_path.is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "a portfolio of threads writes no proof"));
+        }
+        if main_opts.dimacs_path.is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "a portfolio of threads writes no simplified formula"));
+        }
+        if main_opts.backbone {
+            return Err(io::Error::new(io::ErrorKind::Other, "a portfolio of threads computes no backbone"));
+        }
+
+        let solver = minisat::portfolio::Portfolio::new(minisat::portfolio::diversify(&settings, main_opts.threads));
+        return if main_opts.enumerate.is_some() { enumerateWith(solver, input, main_opts) } else { solveWith(solver, input, main_opts) };
+    }
+
+    let proof =
+        match main_opts.proof_path {
+            Some(ref path) => {
+                let out = io::BufWriter::new(try!(fs::File::create(path)));
+                let format = if main_opts.proof_bin { minisat::ProofFormat::Binary } else { minisat::ProofFormat::Text };
+                Some(minisat::Proof::new(Box::new(out), format))
+            }
+
+            None           => { None }
+        };
+
     match solver_opts {
         SolverOptions::Core(opts) => {
-            let solver = minisat::CoreSolver::new(opts);
-            solveWith(solver, main_opts)
+            let mut solver = minisat::CoreSolver::new(opts);
+            if let Some(proof) = proof { solver.setProof(proof); }
+            if incremental {
+                solveIncrementalWith(solver, input, main_opts)
+            } else if main_opts.enumerate.is_some() {
+                enumerateWith(solver, input, main_opts)
+            } else if main_opts.backbone {
+                backboneWith(solver, input, main_opts)
+            } else {
+                solveWith(solver, input, main_opts)
+            }
         }
 
         SolverOptions::Simp(opts) => {
             let mut solver = minisat::simp::SimpSolver::new(opts);
+            if let Some(proof) = proof { solver.setProof(proof); }
             if !main_opts.pre { solver.eliminate(true); }
-            solveWith(solver, main_opts)
+            if incremental {
+                solveIncrementalWith(solver, input, main_opts)
+            } else if main_opts.enumerate.is_some() {
+                enumerateWith(solver, input, main_opts)
+            } else if main_opts.backbone {
+                backboneWith(solver, input, main_opts)
+            } else {
+                solveWith(solver, input, main_opts)
+            }
         }
     }
 }
 
 
-pub fn solveWith<S : Solver>(mut solver : S, options : MainOptions) -> io::Result<()> {
+pub fn solveWith<S : Solver>(mut solver : S, mut input : Box<io::BufRead>, options : MainOptions) -> io::Result<()> {
     let initial_time = time::precise_time_s();
 
     info!("============================[ Problem Statistics ]=============================");
     info!("|                                                                             |");
 
-    let backward_subst = {
-        let in_file = try!(fs::File::open(options.in_path.as_path()));
-        try!(dimacs::parse(&mut io::BufReader::new(in_file), &mut solver, options.strict))
-    };
+    let backward_subst = try!(dimacs::parse(&mut input, &mut solver, options.strict)).backward_subst;
 
     info!("|  Number of variables:  {:12}                                         |", solver.nVars());
     info!("|  Number of clauses:    {:12}                                         |", solver.nClauses());
@@ -86,14 +198,15 @@ pub fn solveWith<S : Solver>(mut solver : S, options : MainOptions) -> io::Resul
 
             if let TotalResult::Interrupted = result {
                 if let Some(path) = options.dimacs_path {
-                    let mut out = try!(fs::File::create(path));
-                    try!(dimacs::write(&mut out, &solver));
+                    let mut out = io::BufWriter::new(try!(fs::File::create(path)));
+                    try!(dimacs::write(&mut out, &solver, &backward_subst));
                 }
             }
 
             solver.printStats();
             result
         };
+    try!(solver.finishProof());
 
     println!("{}",
         match result {
@@ -114,10 +227,435 @@ pub fn solveWith<S : Solver>(mut solver : S, options : MainOptions) -> io::Resul
         }
     }
 
+    // The standard input can not be read again for the self-check:
+    if let TotalResult::SAT(ref model) = result {
+        if options.in_path != path::Path::new("-") {
+            let mut in_file = try!(dimacs::open(options.in_path.as_path()));
+            assert!(try!(dimacs::validateModel(&mut in_file, &backward_subst, &model)), "SELF-CHECK FAILED!");
+        }
+    }
+
+    Ok(())
+}
+
+
+// Every 'a' line of an iCNF stream is solved under its assumptions, with the clauses that come
+// before it, on the same solver:
+pub fn solveIncrementalWith<S : IncrementalSolver>(mut solver : S, mut input : Box<io::BufRead>, options : MainOptions) -> io::Result<()> {
+    let mut out =
+        match options.out_path {
+            Some(ref path) => { Some(try!(fs::File::create(path))) }
+            None           => { None }
+        };
+
+    let mut queries = 0;
+    try!(dimacs::parseIncremental(&mut input, &mut solver, |solver, backward_subst, assumptions| {
+        queries += 1;
+        let result = solver.solveUnder(&assumptions);
+        match result {
+            PartialResult::SAT(_)               => { println!("query {}: SATISFIABLE", queries); }
+            PartialResult::UnSAT                => { println!("query {}: UNSATISFIABLE", queries); }
+            PartialResult::Interrupted(_)       => { println!("query {}: INDETERMINATE", queries); }
+            PartialResult::UnSATUnder(ref lits) => {
+                let failed : Vec<String> = lits.iter().map(|lit| {
+                    let id = backward_subst[&lit.var()];
+                    format!("{}", if lit.sign() { -id } else { id })
+                }).collect();
+                println!("query {}: UNSATISFIABLE, failed assumptions: {}", queries, failed.join(" "));
+            }
+        }
+
+        if let Some(ref mut file) = out {
+            match result {
+                PartialResult::SAT(ref model)  => {
+                    try!(writeln!(file, "SAT"));
+                    try!(dimacs::writeModel(file, backward_subst, model));
+                }
+
+                PartialResult::Interrupted(_)  => { try!(writeln!(file, "INDET")); }
+                _                              => { try!(writeln!(file, "UNSAT")); }
+            }
+        }
+        Ok(())
+    }));
+
+    info!("Number of queries: {}", queries);
+    solver.printStats();
+    solver.finishProof()
+}
+
+
+pub fn enumerateWith<S : Solver>(solver : S, mut input : Box<io::BufRead>, options : MainOptions) -> io::Result<()> {
+    let mut enumerator = allsat::Enumerator::new(solver, options.shrink);
+    let backward_subst = {
+        let parsed = try!(dimacs::parse(&mut input, &mut enumerator, options.strict));
+        if let Some(projection) = parsed.projection {
+            enumerator.setProjection(projection);
+        }
+        parsed.backward_subst
+    };
+
+    let limit = options.enumerate.and_then(|n| n).unwrap_or(usize::max_value());
+    let mut models = Vec::new();
+    if enumerator.preprocess() {
+        let stdout = io::stdout();
+        for model in enumerator.by_ref().take(limit) {
+            let mut out = stdout.lock();
+            try!(write!(out, "v "));
+            try!(dimacs::writeModel(&mut out, &backward_subst, &model));
+            models.push(model);
+        }
+    }
+
+    info!("Number of models: {}", models.len());
+    enumerator.printStats();
+    try!(enumerator.finishProof());
+    println!("{}", if models.is_empty() { "UNSATISFIABLE" } else { "SATISFIABLE" });
+
+    if let Some(path) = options.out_path {
+        let mut file = try!(fs::File::create(path));
+        if models.is_empty() {
+            try!(writeln!(file, "UNSAT"));
+        } else {
+            try!(writeln!(file, "SAT"));
+            for model in models.iter() {
+                try!(dimacs::writeModel(&mut file, &backward_subst, model));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+pub fn backboneWith<S : IncrementalSolver>(mut solver : S, mut input : Box<io::BufRead>, options : MainOptions) -> io::Result<()> {
+    let (backward_subst, projection) = {
+        let parsed = try!(dimacs::parse(&mut input, &mut solver, options.strict));
+        (parsed.backward_subst, parsed.projection)
+    };
+
+    info!("|  Number of variables:  {:12}                                         |", solver.nVars());
+    info!("|  Number of clauses:    {:12}                                         |", solver.nClauses());
+
+    let result = backbone::compute(&mut solver, projection.as_ref().map(|vars| &vars[..]));
+
+    solver.printStats();
+    try!(solver.finishProof());
+
+    // Backbone literals are written as a partial model. One cut short by an interrupt is marked
+    // with a comment line before it:
+    let (lits, partial) =
+        match result {
+            backbone::BackboneResult::Backbone(ref lits)    => { (Some(lits), false) }
+            backbone::BackboneResult::Interrupted(ref lits) => { (Some(lits), true) }
+            _                                               => { (None, false) }
+        };
+
+    let mut model = VarMap::new();
+    for lit in lits.iter().flat_map(|lits| lits.iter()) {
+        model.insert(&lit.var(), !lit.sign());
+    }
+
+    match result {
+        backbone::BackboneResult::UnSAT         => { println!("UNSATISFIABLE"); }
+        backbone::BackboneResult::Indeterminate => { println!("INDETERMINATE"); }
+        _                                       => {
+            info!("Backbone size: {}", model.iter().count());
+            if partial { println!("c partial backbone"); }
+            print!("v ");
+            try!(dimacs::writeModel(&mut io::stdout(), &backward_subst, &model));
+            println!("SATISFIABLE");
+        }
+    }
+
+    if let Some(path) = options.out_path {
+        let mut file = try!(fs::File::create(path));
+        match result {
+            backbone::BackboneResult::UnSAT         => { try!(writeln!(file, "UNSAT")); }
+            backbone::BackboneResult::Indeterminate => { try!(writeln!(file, "INDET")); }
+            _                                       => {
+                try!(writeln!(file, "SAT"));
+                if partial { try!(writeln!(file, "c partial backbone")); }
+                try!(dimacs::writeModel(&mut file, &backward_subst, &model));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+pub fn checkProof(options : CheckOptions) -> io::Result<bool> {
+    let mut cnf = try!(dimacs::open(options.cnf_path.as_path()));
+    let mut proof = io::BufReader::new(try!(fs::File::open(options.proof_path.as_path())));
+
+    let result =
+        if options.lrat {
+            try!(proof::checkLrat(&mut cnf, &mut proof))
+        } else {
+            match options.trim_path {
+                Some(path) => {
+                    let mut out = io::BufWriter::new(try!(fs::File::create(path)));
+                    try!(proof::checkDrat(&mut cnf, &mut proof, Some(&mut out)))
+                }
+
+                None       => { try!(proof::checkDrat(&mut cnf, &mut proof, None)) }
+            }
+        };
+
+    match result {
+        proof::CheckResult::Verified         => {}
+        proof::CheckResult::NoConflict       => { info!("c proof does not derive the empty clause"); }
+        proof::CheckResult::FailedLemma(idx) => { info!("c failed to verify proof step {}", idx + 1); }
+    }
+
+    let verified = result == proof::CheckResult::Verified;
+    println!("{}", if verified { "s VERIFIED" } else { "s NOT VERIFIED" });
+    Ok(verified)
+}
+
+
+pub fn extractMus(options : MusOptions, settings : mus::Settings) -> io::Result<()> {
+    let mut clauses = Vec::new();
+    {
+        let mut in_file = try!(dimacs::open(options.in_path.as_path()));
+        try!(dimacs::parseGroups(&mut in_file, |group, raw| { clauses.push((group, raw)); }));
+    }
+
+    let mut extractor = mus::MusExtractor::new(settings);
+    let mut vars = Vec::new();
+    for &(group, ref raw) in clauses.iter() {
+        let mut lits = Vec::with_capacity(raw.len());
+        for &lit_id in raw.iter() {
+            while vars.len() < (lit_id.abs() as usize) {
+                vars.push(extractor.newVar());
+            }
+            lits.push(vars[(lit_id.abs() as usize) - 1].lit(lit_id < 0));
+        }
+        extractor.addClause(group, &lits);
+    }
+
+    let groups =
+        match extractor.extract() {
+            mus::MusResult::SAT                 => { println!("SATISFIABLE"); return Ok(()); }
+            mus::MusResult::Mus(groups)         => { println!("UNSATISFIABLE"); groups }
+            mus::MusResult::Interrupted(groups) => { println!("INDETERMINATE"); groups }
+        };
+
+    {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        try!(write!(out, "v"));
+        for g in groups.iter() {
+            try!(write!(out, " {}", g));
+        }
+        try!(writeln!(out, " 0"));
+    }
+
+    if let Some(path) = options.out_path {
+        let core : Vec<&Vec<i32>> = clauses.iter().filter(|&&(group, _)| {
+            group.map(|g| groups.binary_search(&g).is_ok()).unwrap_or(true)
+        }).map(|&(_, ref raw)| raw).collect();
+
+        let mut out = io::BufWriter::new(try!(fs::File::create(path)));
+        try!(writeln!(out, "p cnf {} {}", vars.len(), core.len()));
+        for raw in core {
+            for lit_id in raw.iter() {
+                try!(write!(out, "{} ", lit_id));
+            }
+            try!(writeln!(out, "0"));
+        }
+    }
+
+    Ok(())
+}
+
+
+pub fn solveMaxSat(options : MaxSatOptions, settings : maxsat::Settings) -> io::Result<()> {
+    let mut clauses = Vec::new();
+    {
+        let mut in_file = try!(dimacs::open(options.in_path.as_path()));
+        try!(dimacs::parseWeighted(&mut in_file, |weight, raw| { clauses.push((weight, raw)); }));
+    }
+
+    let mut solver = maxsat::MaxSatSolver::new(settings);
+    let mut vars = Vec::new();
+    for &(weight, ref raw) in clauses.iter() {
+        let mut lits = Vec::with_capacity(raw.len());
+        for &lit_id in raw.iter() {
+            while vars.len() < (lit_id.abs() as usize) {
+                vars.push(solver.newVar());
+            }
+            lits.push(vars[(lit_id.abs() as usize) - 1].lit(lit_id < 0));
+        }
+
+        match weight {
+            Some(w) => { solver.addSoft(&lits, w); }
+            None    => { solver.addHard(&lits); }
+        }
+    }
+
+    let result = solver.solve(|cost, _| { println!("o {}", cost); });
+
+    let model =
+        match result {
+            maxsat::MaxSatResult::Optimum(_, model)           => { println!("s OPTIMUM FOUND"); model }
+            maxsat::MaxSatResult::Interrupted(Some((_, model))) => { println!("s SATISFIABLE"); model }
+            maxsat::MaxSatResult::Interrupted(None)           => { println!("s UNKNOWN"); return Ok(()); }
+            maxsat::MaxSatResult::UnSAT                       => { println!("s UNSATISFIABLE"); return Ok(()); }
+        };
+
+    // Values of variables 1, 2, ... as a string of digits:
+    let values : String = vars.iter().map(|v| if model.get(v).cloned().unwrap_or(false) { '1' } else { '0' }).collect();
+    println!("v {}", values);
+    Ok(())
+}
+
+
+pub fn solveOpb(options : OpbOptions) -> io::Result<()> {
+    let mut solver = minisat::CoreSolver::new(Default::default());
+    let parsed = {
+        let mut in_file = io::BufReader::new(try!(fs::File::open(options.in_path.as_path())));
+        if options.native {
+            try!(opb::parseNative(&mut in_file, &mut solver))
+        } else {
+            try!(opb::parse(&mut in_file, &mut solver, options.encoding))
+        }
+    };
+
+    let model =
+        match parsed.objective {
+            Some(ref objective) => {
+                let result = opb::minimize(&mut solver, objective, options.search, options.encoding, |cost, _| { println!("o {}", cost); });
+                match result {
+                    opb::OptResult::Optimum(_, model)            => { println!("s OPTIMUM FOUND"); model }
+                    opb::OptResult::Interrupted(Some((_, model))) => { println!("s SATISFIABLE"); model }
+                    opb::OptResult::Interrupted(None)            => { println!("s UNKNOWN"); return Ok(()); }
+                    opb::OptResult::UnSAT                        => { println!("s UNSATISFIABLE"); return Ok(()); }
+                }
+            }
+
+            None                => {
+                match solver.solveLimited(&[]) {
+                    PartialResult::SAT(model)     => { println!("s SATISFIABLE"); model }
+                    PartialResult::Interrupted(_) => { println!("s UNKNOWN"); return Ok(()); }
+                    _                             => { println!("s UNSATISFIABLE"); return Ok(()); }
+                }
+            }
+        };
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    try!(write!(out, "v"));
+    for (v, &id) in parsed.backward_subst.iter() {
+        let val = model.get(&v).cloned().unwrap_or(false);
+        try!(write!(out, " {}x{}", if val { "" } else { "-" }, id));
+    }
+    try!(writeln!(out, ""));
+    Ok(())
+}
+
+
+// Splits the problem into cubes by lookahead, then either writes them out or solves them:
+pub fn cubeAndConquer(options : CubeOptions, settings : minisat::Settings, cube_settings : minisat::cube::CubeSettings) -> io::Result<()> {
+    let mut solver = minisat::CoreSolver::new(settings.clone());
+    let backward_subst = {
+        let mut in_file = try!(dimacs::open(options.in_path.as_path()));
+        try!(dimacs::parse(&mut in_file, &mut solver, false)).backward_subst
+    };
+
+    // The problem as parsed, loaded into every thread so that its variables agree with the cubes:
+    let num_vars = solver.nVars();
+    let formula = Arc::new(solver.formula().expect("a DIMACS problem has no linear constraints"));
+
+    let mut stats = minisat::cube::CubeStats::default();
+    let cubes = minisat::cube::cube(&mut solver, &cube_settings, &mut stats);
+    info!("|  Cubes: {}   refuted branches: {}   failed literals: {}   probes: {}", cubes.len(), stats.refuted, stats.failed, stats.probes);
+
+    if let Some(path) = options.out_path {
+        if !formula.xors.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::Other, "XOR constraints are not supported here"));
+        }
+
+        let ids = |lits : &Vec<Lit>| -> Vec<i32> {
+            lits.iter().map(|lit| {
+                let id = backward_subst[&lit.var()];
+                if lit.sign() { -id } else { id }
+            }).collect()
+        };
+        let clauses : Vec<Vec<i32>> = formula.clauses.iter().map(&ids).collect();
+        let cubes : Vec<Vec<i32>> = cubes.iter().map(&ids).collect();
+
+        let mut out = io::BufWriter::new(try!(fs::File::create(path)));
+        return dimacs::writeIcnf(&mut out, &clauses, &cubes);
+    }
+
+    let result = {
+        let formula = formula.clone();
+        minisat::cube::conquer(move || {
+            let mut solver = minisat::CoreSolver::new(settings.clone());
+            for _ in 0 .. num_vars {
+                solver.newVar(None, true);
+            }
+            for c in formula.clauses.iter() {
+                solver.addClause(c);
+            }
+            for x in formula.xors.iter() {
+                solver.addXor(x);
+            }
+            solver
+        }, cubes, options.threads)
+    };
+
+    println!("{}",
+        match result {
+            TotalResult::SAT(_)      => { "SATISFIABLE" }
+            TotalResult::UnSAT       => { "UNSATISFIABLE" }
+            TotalResult::Interrupted => { "INDETERMINATE" }
+        });
+
+    // The standard input can not be read again for the self-check:
     if let TotalResult::SAT(ref model) = result {
-        let in_file = try!(fs::File::open(options.in_path.as_path()));
-        assert!(try!(dimacs::validateModel(&mut io::BufReader::new(in_file), &backward_subst, &model)), "SELF-CHECK FAILED!");
+        print!("v ");
+        try!(dimacs::writeModel(&mut io::stdout(), &backward_subst, model));
+        if options.in_path != path::Path::new("-") {
+            let mut in_file = try!(dimacs::open(options.in_path.as_path()));
+            assert!(try!(dimacs::validateModel(&mut in_file, &backward_subst, model)), "SELF-CHECK FAILED!");
+        }
     }
 
     Ok(())
 }
+
+
+// Prints a witness of the first failing property, or the result of the competition format for
+// an undecided ('2') or a safe ('0') circuit:
+pub fn solveBmc(options : BmcOptions) -> io::Result<()> {
+    let aig = {
+        let mut in_file = io::BufReader::new(try!(fs::File::open(options.in_path.as_path())));
+        try!(aiger::parse(&mut in_file))
+    };
+
+    info!("|  Inputs: {}   latches: {}   AND gates: {}   properties: {}", aig.inputs.len(), aig.latches.len(), aig.ands.len(), aig.properties().len());
+
+    let mut solver = minisat::CoreSolver::new(Default::default());
+    let result = bmc::check(&aig, &mut solver, options.depth);
+    solver.printStats();
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let status =
+        match result {
+            bmc::BmcResult::Counterexample(trace) => { return bmc::writeWitness(&mut out, &aig, &trace); }
+            bmc::BmcResult::Bounded(depth)        => { info!("No counterexample up to depth {}", depth); 2 }
+            bmc::BmcResult::Interrupted(depth)    => { info!("No counterexample below depth {}", depth); 2 }
+            bmc::BmcResult::Unreachable           => { 0 }
+        };
+
+    for i in 0 .. aig.properties().len() {
+        try!(writeln!(out, "{}", status));
+        try!(writeln!(out, "b{}", i));
+        try!(writeln!(out, "."));
+    }
+    Ok(())
+}
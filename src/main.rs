@@ -3,8 +3,10 @@ extern crate env_logger;
 #[macro_use] extern crate clap;
 extern crate minisat_rust;
 
-use std::path;
+use std::{path, process};
+use minisat_rust::sat::encode::PbEncoding;
 use minisat_rust::sat::minisat::{self, PhaseSaving, CCMinMode};
+use minisat_rust::sat::opb::Search;
 
 
 fn main() {
@@ -13,6 +15,7 @@ fn main() {
         clap::App::new("minisat-rust")
         .version(&crate_version!()[..])
         .about("Minisat reimplementation in Rust")
+        .setting(clap::AppSettings::SubcommandsNegateReqs)
 
         .arg(clap::Arg::with_name("verb").long("verb").takes_value(true).possible_values(&ls012).help("Verbosity level (0=silent, 1=some, 2=more)"))
         .arg(clap::Arg::with_name("core").long("core").help("Use core solver"))
@@ -22,7 +25,13 @@ fn main() {
         .arg(clap::Arg::with_name("solve").long("solve").help("Completely turn on/off solving after preprocessing"))
         .arg(clap::Arg::with_name("no-solve").long("no-solve").conflicts_with("solve"))
         .arg(clap::Arg::with_name("dimacs").long("dimacs").takes_value(true).requires("no-solve").help("If given, stop after preprocessing and write the result to this file"))
-        .arg(clap::Arg::with_name("input").required(true))
+        .arg(clap::Arg::with_name("proof").long("proof").takes_value(true).help("If given, write a DRAT proof of unsatisfiability to this file"))
+        .arg(clap::Arg::with_name("binary-proof").long("binary-proof").requires("proof").help("Write the proof in binary DRAT format"))
+        .arg(clap::Arg::with_name("enumerate").long("enumerate").takes_value(true).min_values(0).require_equals(true).help("Enumerate models, all of them or at most the given number; 'c ind' lines restrict them to a projection"))
+        .arg(clap::Arg::with_name("shrink").long("shrink").requires("enumerate").help("Reduce every enumerated model to a partial one before blocking it"))
+        .arg(clap::Arg::with_name("backbone").long("backbone").conflicts_with("enumerate").help("Print the literals that are true in every model; 'c ind' lines restrict them to a projection"))
+        .arg(clap::Arg::with_name("threads").long("threads").takes_value(true).conflicts_with("proof").conflicts_with("backbone").conflicts_with("dimacs").help("Run a portfolio of differently configured core solvers on this many threads, sharing short learnt clauses"))
+        .arg(clap::Arg::with_name("input").required(true).help("DIMACS or iCNF, possibly compressed by gzip, bzip2, xz or lzma; '-' reads the standard input"))
         .arg(clap::Arg::with_name("output").required(false))
 
         .arg(clap::Arg::with_name("var-decay").long("var-decay").takes_value(true).help("The variable activity decay factor"))
@@ -41,6 +50,7 @@ fn main() {
         .arg(clap::Arg::with_name("min-learnts").long("min-learnts").takes_value(true).help("Minimum learnt clause limit"))
         .arg(clap::Arg::with_name("rcheck").long("rcheck").help("Check if a clause is already implied. (costly)"))
         .arg(clap::Arg::with_name("no-rcheck").long("no-rcheck").conflicts_with("rcheck"))
+        .arg(clap::Arg::with_name("no-xor").long("no-xor").help("Do not recover XOR constraints from clauses"))
 
         .arg(clap::Arg::with_name("asymm").long("asymm").conflicts_with("core").help("Shrink clauses by asymmetric branching"))
         .arg(clap::Arg::with_name("no-asymm").long("no-asymm").conflicts_with("asymm").conflicts_with("core"))
@@ -51,6 +61,41 @@ fn main() {
         .arg(clap::Arg::with_name("sub-lim").long("sub-lim").takes_value(true).conflicts_with("core").help("Do not check if subsumption against a clause larger than this. -1 means no limit."))
         .arg(clap::Arg::with_name("simp-gc-frac").long("simp-gc-frac").takes_value(true).conflicts_with("core").help("The fraction of wasted memory allowed before a garbage collection is triggered during simplification."))
 
+        .subcommand(clap::SubCommand::with_name("check-proof")
+            .about("Verify a proof of unsatisfiability")
+            .arg(clap::Arg::with_name("input").required(true))
+            .arg(clap::Arg::with_name("proof").required(true))
+            .arg(clap::Arg::with_name("lrat").long("lrat").help("The proof is in LRAT rather than DRAT format"))
+            .arg(clap::Arg::with_name("trim").long("trim").takes_value(true).conflicts_with("lrat").help("If given, write the verified part of a DRAT proof to this file in LRAT format")))
+        .subcommand(clap::SubCommand::with_name("mus")
+            .about("Extract a minimal unsatisfiable subset of clauses, or of clause groups for group oriented DIMACS")
+            .arg(clap::Arg::with_name("input").required(true))
+            .arg(clap::Arg::with_name("output").required(false).help("If given, write the clauses of the subset to this file")))
+
+        .subcommand(clap::SubCommand::with_name("maxsat")
+            .about("Solve a weighted partial MaxSAT problem in WCNF")
+            .arg(clap::Arg::with_name("input").required(true))
+            .arg(clap::Arg::with_name("no-stratify").long("no-stratify").help("Do not solve for heavier soft clauses first"))
+            .arg(clap::Arg::with_name("no-harden").long("no-harden").help("Do not harden soft clauses that can not be violated by an improving solution")))
+
+        .subcommand(clap::SubCommand::with_name("opb")
+            .about("Solve a pseudo-Boolean problem in OPB format, minimizing its objective if there is one")
+            .arg(clap::Arg::with_name("input").required(true))
+            .arg(clap::Arg::with_name("search").long("search").takes_value(true).possible_values(&["linear", "binary"]).help("How to search for the optimum (default: linear)"))
+            .arg(clap::Arg::with_name("pb-encoding").long("pb-encoding").takes_value(true).possible_values(&["bdd", "adder", "sorter", "native"]).help("Encode every constraint this way instead of choosing per constraint, or propagate the constraints natively")))
+
+        .subcommand(clap::SubCommand::with_name("cube")
+            .about("Split the problem into cubes by lookahead and solve them as assumptions")
+            .arg(clap::Arg::with_name("input").required(true))
+            .arg(clap::Arg::with_name("output").required(false).help("If given, write the problem with the cubes to this file in iCNF instead of solving them"))
+            .arg(clap::Arg::with_name("depth").long("depth").takes_value(true).help("Maximum number of decisions in a cube (default: 10)"))
+            .arg(clap::Arg::with_name("threads").long("threads").takes_value(true).help("Number of threads solving the cubes (default: 1)")))
+
+        .subcommand(clap::SubCommand::with_name("bmc")
+            .about("Bounded model checking of a circuit in AIGER format, printing an AIGER witness")
+            .arg(clap::Arg::with_name("input").required(true))
+            .arg(clap::Arg::with_name("depth").long("depth").takes_value(true).help("Maximum number of steps to unroll (default: until a counterexample is found)")))
+
         .get_matches();
 
     {
@@ -67,6 +112,90 @@ fn main() {
         builder.init().unwrap();
     }
 
+    if let Some(matches) = matches.subcommand_matches("check-proof") {
+        let check =
+            minisat_rust::CheckOptions {
+                cnf_path   : path::PathBuf::from(matches.value_of("input").unwrap()),
+                proof_path : path::PathBuf::from(matches.value_of("proof").unwrap()),
+                lrat       : matches.is_present("lrat"),
+                trim_path  : matches.value_of("trim").map(|x| path::PathBuf::from(x))
+            };
+
+        if !minisat_rust::checkProof(check).expect("IO Error") {
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("mus") {
+        let options =
+            minisat_rust::MusOptions {
+                in_path  : path::PathBuf::from(matches.value_of("input").unwrap()),
+                out_path : matches.value_of("output").map(|x| path::PathBuf::from(x))
+            };
+
+        minisat_rust::extractMus(options, Default::default()).expect("IO Error");
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("maxsat") {
+        let options = minisat_rust::MaxSatOptions { in_path : path::PathBuf::from(matches.value_of("input").unwrap()) };
+
+        let mut settings = minisat_rust::sat::maxsat::Settings::default();
+        settings.stratify = !matches.is_present("no-stratify");
+        settings.harden = !matches.is_present("no-harden");
+        minisat_rust::solveMaxSat(options, settings).expect("IO Error");
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("opb") {
+        let options =
+            minisat_rust::OpbOptions {
+                in_path  : path::PathBuf::from(matches.value_of("input").unwrap()),
+                search   : if matches.value_of("search") == Some("binary") { Search::Binary } else { Search::Linear },
+                encoding : matches.value_of("pb-encoding").and_then(|x| {
+                    match x {
+                        "bdd"    => { Some(PbEncoding::Bdd) }
+                        "adder"  => { Some(PbEncoding::Adder) }
+                        "sorter" => { Some(PbEncoding::SortingNetwork) }
+                        _        => { None }
+                    }
+                }),
+                native   : matches.value_of("pb-encoding") == Some("native")
+            };
+
+        minisat_rust::solveOpb(options).expect("IO Error");
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("cube") {
+        let options =
+            minisat_rust::CubeOptions {
+                in_path  : path::PathBuf::from(matches.value_of("input").unwrap()),
+                out_path : matches.value_of("output").map(|x| path::PathBuf::from(x)),
+                threads  : matches.value_of("threads").and_then(|x| x.parse().ok()).unwrap_or(1)
+            };
+
+        let mut settings = minisat::cube::CubeSettings::default();
+        for &x in matches.value_of("depth").and_then(|x| x.parse().ok()).iter() {
+            settings.depth = x;
+        }
+
+        minisat_rust::cubeAndConquer(options, Default::default(), settings).expect("IO Error");
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("bmc") {
+        let options =
+            minisat_rust::BmcOptions {
+                in_path : path::PathBuf::from(matches.value_of("input").unwrap()),
+                depth   : matches.value_of("depth").and_then(|x| x.parse().ok())
+            };
+
+        minisat_rust::solveBmc(options).expect("IO Error");
+        return;
+    }
+
     let main =
         minisat_rust::MainOptions {
             strict      : matches.is_present("strict"),
@@ -74,7 +203,13 @@ fn main() {
             solve       : !matches.is_present("no-solve"),
             in_path     : path::PathBuf::from(matches.value_of("input").unwrap()),
             out_path    : matches.value_of("output").map(|x| path::PathBuf::from(x)),
-            dimacs_path : matches.value_of("dimacs").map(|x| path::PathBuf::from(x))
+            dimacs_path : matches.value_of("dimacs").map(|x| path::PathBuf::from(x)),
+            proof_path  : matches.value_of("proof").map(|x| path::PathBuf::from(x)),
+            proof_bin   : matches.is_present("binary-proof"),
+            enumerate   : if matches.is_present("enumerate") { Some(matches.value_of("enumerate").and_then(|x| x.parse().ok())) } else { None },
+            shrink      : matches.is_present("shrink"),
+            backbone    : matches.is_present("backbone"),
+            threads     : matches.value_of("threads").and_then(|x| x.parse().ok()).unwrap_or(1)
         };
 
     let solver = {
@@ -139,11 +274,13 @@ fn main() {
 
             if matches.is_present("rcheck") { s.core.use_rcheck = true; }
             if matches.is_present("no-rcheck") { s.core.use_rcheck = false; }
+            if matches.is_present("no-xor") { s.core.recover_xors = false; }
 
             s
         };
 
-        if matches.is_present("core") {
+        // A portfolio runs core solvers only:
+        if matches.is_present("core") || main.threads > 1 {
             minisat_rust::SolverOptions::Core(core_options)
         } else {
             let simp_options = {
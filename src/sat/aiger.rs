@@ -0,0 +1,308 @@
+use std::{io, str};
+use sat::Solver;
+use sat::formula::Lit;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reset { Zero, One, Undef }
+
+
+#[derive(Clone, Copy, Debug)]
+pub struct Latch {
+    pub lit   : u32,
+    pub next  : u32,
+    pub reset : Reset
+}
+
+
+#[derive(Clone, Copy, Debug)]
+pub struct And {
+    pub lhs  : u32,
+    pub rhs0 : u32,
+    pub rhs1 : u32
+}
+
+
+// And-inverter graph in the sense of AIGER 1.9. Literals are numbered as in the file: '2 * v' for
+// variable 'v', '2 * v + 1' for its negation, and variable 0 is the constant false.
+pub struct Aig {
+    pub maxvar      : usize,
+    pub inputs      : Vec<u32>,
+    pub latches     : Vec<Latch>,
+    pub outputs     : Vec<u32>,
+    pub bad         : Vec<u32>,
+    pub constraints : Vec<u32>, // Invariant constraints, that hold in every step of a trace.
+    pub ands        : Vec<And>
+}
+
+
+// Solver literals of one copy of the circuit:
+pub struct Frame {
+    vals : Vec<Lit> // 'vals[v]' stands for variable 'v'.
+}
+
+impl Frame {
+    pub fn lit(&self, lit : u32) -> Lit {
+        let x = self.vals[(lit >> 1) as usize];
+        if lit & 1 == 1 { !x } else { x }
+    }
+}
+
+
+impl Aig {
+    // Properties to check: bad state literals, or the outputs for files from before AIGER 1.9:
+    pub fn properties(&self) -> &[u32] {
+        if self.bad.is_empty() { &self.outputs } else { &self.bad }
+    }
+
+    // Tseitin encoding of the AND gates, with the given literals for inputs and current state of
+    // latches. Every gate gets a fresh variable, so they can come in any order:
+    pub fn encode<S : Solver>(&self, solver : &mut S, inputs : &[Lit], latches : &[Lit]) -> Frame {
+        assert!(inputs.len() == self.inputs.len() && latches.len() == self.latches.len());
+
+        let falsity = solver.newVar(None, false).posLit();
+        solver.addClause(&[!falsity]);
+
+        let mut frame = Frame { vals : vec![falsity; self.maxvar + 1] };
+        for (&i, &lit) in self.inputs.iter().zip(inputs.iter()) {
+            frame.vals[(i >> 1) as usize] = lit;
+        }
+        for (l, &lit) in self.latches.iter().zip(latches.iter()) {
+            frame.vals[(l.lit >> 1) as usize] = lit;
+        }
+        for g in self.ands.iter() {
+            frame.vals[(g.lhs >> 1) as usize] = solver.newVar(None, true).posLit();
+        }
+
+        for g in self.ands.iter() {
+            let (y, a, b) = (frame.lit(g.lhs), frame.lit(g.rhs0), frame.lit(g.rhs1));
+            solver.addClause(&[!y, a]);
+            solver.addClause(&[!y, b]);
+            solver.addClause(&[y, !a, !b]);
+        }
+
+        frame
+    }
+}
+
+
+// Both the ASCII ('aag') and the binary ('aig') format. Justice and fairness properties are not
+// supported, symbols and comments are skipped:
+pub fn parse<R : io::Read>(stream : &mut R) -> io::Result<Aig> {
+    let mut buf = Vec::new();
+    try!(stream.read_to_end(&mut buf));
+    let mut p = AigParser { buf : &buf, pos : 0 };
+
+    let header = try!(p.line());
+    let words : Vec<&str> = header.split_whitespace().collect();
+    let binary =
+        match words.first() {
+            Some(&"aag") => { false }
+            Some(&"aig") => { true }
+            _            => { return Err(parseError(&format!("unexpected header '{}'", header))); }
+        };
+
+    if words.len() < 6 || words.len() > 10 {
+        return Err(parseError(&format!("unexpected header '{}'", header)));
+    }
+
+    let mut counts = [0; 9];
+    for (i, w) in words[1 ..].iter().enumerate() {
+        counts[i] = try!(parseUInt(w));
+    }
+
+    let (m, i, l, o, a, b, c, j, f) = (counts[0], counts[1], counts[2], counts[3], counts[4], counts[5], counts[6], counts[7], counts[8]);
+    if j > 0 || f > 0 {
+        return Err(parseError("justice and fairness properties are not supported"));
+    }
+
+    if binary && m != i + l + a {
+        return Err(parseError("binary format requires M = I + L + A"));
+    }
+
+    let mut aig = Aig { maxvar      : m as usize
+                      , inputs      : Vec::with_capacity(i as usize)
+                      , latches     : Vec::with_capacity(l as usize)
+                      , outputs     : Vec::with_capacity(o as usize)
+                      , bad         : Vec::with_capacity(b as usize)
+                      , constraints : Vec::with_capacity(c as usize)
+                      , ands        : Vec::with_capacity(a as usize)
+                      };
+
+    for k in 0 .. i {
+        let lit = if binary { 2 * (k + 1) } else { try!(p.lits(1))[0] };
+        aig.inputs.push(lit);
+    }
+
+    for k in 0 .. l {
+        let (lit, rest) =
+            if binary {
+                (2 * (i + k + 1), try!(p.lits(0)))
+            } else {
+                let lits = try!(p.lits(1));
+                (lits[0], lits[1 ..].to_vec())
+            };
+
+        let reset =
+            match rest.get(1) {
+                None                 => { Reset::Zero }
+                Some(&0)             => { Reset::Zero }
+                Some(&1)             => { Reset::One }
+                Some(&r) if r == lit => { Reset::Undef }
+                Some(&r)             => { return Err(parseError(&format!("invalid reset '{}' of latch {}", r, lit))); }
+            };
+
+        match rest.first() {
+            Some(&next) if rest.len() <= 2 => { aig.latches.push(Latch { lit : lit, next : next, reset : reset }); }
+            _                              => { return Err(parseError(&format!("invalid definition of latch {}", lit))); }
+        }
+    }
+
+    for _ in 0 .. o { aig.outputs.push(try!(p.lits(1))[0]); }
+    for _ in 0 .. b { aig.bad.push(try!(p.lits(1))[0]); }
+    for _ in 0 .. c { aig.constraints.push(try!(p.lits(1))[0]); }
+
+    for k in 0 .. a {
+        let g =
+            if binary {
+                let lhs = 2 * (i + l + k + 1);
+                let delta0 = try!(p.delta());
+                let delta1 = try!(p.delta());
+                if delta0 > lhs || delta1 > lhs - delta0 {
+                    return Err(parseError(&format!("invalid AND gate {}", lhs)));
+                }
+                And { lhs : lhs, rhs0 : lhs - delta0, rhs1 : lhs - delta0 - delta1 }
+            } else {
+                let lits = try!(p.lits(3));
+                And { lhs : lits[0], rhs0 : lits[1], rhs1 : lits[2] }
+            };
+        aig.ands.push(g);
+    }
+
+    try!(validate(&aig));
+    Ok(aig)
+}
+
+
+// Every literal must be in range, and every variable defined at most once:
+fn validate(aig : &Aig) -> io::Result<()> {
+    let max = 2 * aig.maxvar as u32 + 1;
+    let mut defined = vec![false; aig.maxvar + 1];
+    defined[0] = true;
+
+    let defs = aig.inputs.iter().cloned().chain(aig.latches.iter().map(|l| l.lit)).chain(aig.ands.iter().map(|g| g.lhs));
+    for lit in defs {
+        if lit & 1 == 1 || lit > max || defined[(lit >> 1) as usize] {
+            return Err(parseError(&format!("invalid definition of literal {}", lit)));
+        }
+        defined[(lit >> 1) as usize] = true;
+    }
+
+    let uses = aig.latches.iter().map(|l| l.next)
+        .chain(aig.outputs.iter().cloned()).chain(aig.bad.iter().cloned()).chain(aig.constraints.iter().cloned())
+        .chain(aig.ands.iter().flat_map(|g| vec![g.rhs0, g.rhs1]));
+    for lit in uses {
+        if lit > max || !defined[(lit >> 1) as usize] {
+            return Err(parseError(&format!("undefined literal {}", lit)));
+        }
+    }
+
+    // The gates must not depend on themselves, which a depth first search finds as a gate that is
+    // reached again while its inputs are being visited:
+    let mut gate = vec![None; aig.maxvar + 1];
+    for (k, g) in aig.ands.iter().enumerate() {
+        gate[(g.lhs >> 1) as usize] = Some(k);
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark { New, Open, Done }
+
+    let mut marks = vec![Mark::New; aig.ands.len()];
+    for root in 0 .. aig.ands.len() {
+        let mut stack = vec![(root, false)];
+        while let Some((k, leaving)) = stack.pop() {
+            if leaving { marks[k] = Mark::Done; continue; }
+
+            match marks[k] {
+                Mark::Done => { continue; }
+                Mark::Open => { return Err(parseError(&format!("cyclic definition of literal {}", aig.ands[k].lhs))); }
+                Mark::New  => {}
+            }
+
+            marks[k] = Mark::Open;
+            stack.push((k, true));
+            for &lit in [aig.ands[k].rhs0, aig.ands[k].rhs1].iter() {
+                if let Some(j) = gate[(lit >> 1) as usize] {
+                    if marks[j] != Mark::Done { stack.push((j, false)); }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+struct AigParser<'a> {
+    buf : &'a [u8],
+    pos : usize
+}
+
+impl<'a> AigParser<'a> {
+    fn line(&mut self) -> io::Result<&'a str> {
+        if self.pos >= self.buf.len() {
+            return Err(parseError("unexpected end of input"));
+        }
+
+        let start = self.pos;
+        while self.pos < self.buf.len() && self.buf[self.pos] != b'\n' {
+            self.pos += 1;
+        }
+        let end = self.pos;
+        self.pos += 1;
+
+        str::from_utf8(&self.buf[start .. end]).map_err(|_| parseError("unexpected binary data"))
+    }
+
+    // Line of literals, 'min' of them at least:
+    fn lits(&mut self, min : usize) -> io::Result<Vec<u32>> {
+        let line = try!(self.line());
+        let mut lits = Vec::new();
+        for w in line.split_whitespace() {
+            lits.push(try!(parseUInt(w)));
+        }
+
+        if lits.len() < min {
+            return Err(parseError(&format!("unexpected line '{}'", line)));
+        }
+        Ok(lits)
+    }
+
+    // Difference of literals in the binary AND section, 7 bits per byte with the high bit set on all
+    // but the last byte:
+    fn delta(&mut self) -> io::Result<u32> {
+        let mut x : u32 = 0;
+        let mut shift = 0;
+        loop {
+            if self.pos >= self.buf.len() || shift > 28 {
+                return Err(parseError("invalid binary AND section"));
+            }
+
+            let byte = self.buf[self.pos];
+            self.pos += 1;
+            x |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 { return Ok(x); }
+            shift += 7;
+        }
+    }
+}
+
+
+fn parseUInt(w : &str) -> io::Result<u32> {
+    w.parse().map_err(|_| parseError(&format!("unsigned integer expected, found '{}'", w)))
+}
+
+
+fn parseError(msg : &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("PARSE ERROR! {}", msg))
+}
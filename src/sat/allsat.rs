@@ -0,0 +1,195 @@
+use std::io;
+use sat::{TotalResult, Formula, Solver, LearnCallback, TerminateCallback};
+use sat::encode;
+use sat::formula::{Var, Lit, VarMap};
+
+
+// Model enumeration. Every model found is excluded from further search by a blocking clause over
+// the projection variables (by default all variables made through 'newVar', the auxiliary ones of
+// encoded XOR constraints excluded), so models that only differ outside of the projection are
+// reported once. With shrinking enabled, a model is first reduced to the part that is needed to
+// satisfy the clauses added through the enumerator, and the reported models are partial: each
+// stands for all of its extensions.
+pub struct Enumerator<S> {
+    solver     : S,
+    projection : Option<Vec<Var>>,
+    projected  : VarMap<()>,
+    own        : VarMap<()>, // Variables made by the caller, the default projection.
+    encoding   : bool,       // Set while an XOR constraint is encoded, its variables are not the caller's.
+    shrink     : bool,
+    clauses    : Vec<Box<[Lit]>>,
+    done       : bool
+}
+
+impl<S : Solver> Solver for Enumerator<S> {
+    fn nVars(&self) -> usize {
+        self.solver.nVars()
+    }
+
+    fn nClauses(&self) -> usize {
+        self.solver.nClauses()
+    }
+
+    fn newVar(&mut self, upol : Option<bool>, dvar : bool) -> Var {
+        let v = self.solver.newVar(upol, dvar);
+        if !self.encoding {
+            self.own.insert(&v, ());
+        }
+        v
+    }
+
+    fn addClause(&mut self, clause : &[Lit]) -> bool {
+        if self.shrink {
+            self.clauses.push(clause.to_vec().into_boxed_slice());
+        }
+        self.solver.addClause(clause)
+    }
+
+    // Shrinking works on clauses, so it needs XOR constraints in that form:
+    fn addXor(&mut self, lits : &[Lit]) -> bool {
+        if self.shrink {
+            self.encoding = true;
+            let ok = encode::xor(self, lits);
+            self.encoding = false;
+            ok
+        } else {
+            self.solver.addXor(lits)
+        }
+    }
+
+    fn preprocess(&mut self) -> bool {
+        self.solver.preprocess()
+    }
+
+    fn solve(&mut self) -> TotalResult {
+        self.solver.solve()
+    }
+
+    fn printStats(&self) {
+        self.solver.printStats();
+    }
+
+    fn formula(&self) -> Option<Formula> {
+        self.solver.formula()
+    }
+
+    fn finishProof(&mut self) -> io::Result<()> {
+        self.solver.finishProof()
+    }
+
+    fn setLearnCallback(&mut self, max_len : usize, callback : Option<LearnCallback>) {
+        self.solver.setLearnCallback(max_len, callback);
+    }
+
+    fn setTerminateCallback(&mut self, callback : Option<TerminateCallback>) {
+        self.solver.setTerminateCallback(callback);
+    }
+}
+
+impl<S : Solver> Enumerator<S> {
+    pub fn new(solver : S, shrink : bool) -> Enumerator<S> {
+        Enumerator { solver     : solver
+                   , projection : None
+                   , projected  : VarMap::new()
+                   , own        : VarMap::new()
+                   , encoding   : false
+                   , shrink     : shrink
+                   , clauses    : Vec::new()
+                   , done       : false
+                   }
+    }
+
+    pub fn setProjection(&mut self, vars : Vec<Var>) {
+        self.projected = VarMap::new();
+        for v in vars.iter() {
+            self.projected.insert(v, ());
+        }
+        self.projection = Some(vars);
+    }
+
+    pub fn solver(&self) -> &S {
+        &self.solver
+    }
+
+    fn isProjected(&self, v : Var) -> bool {
+        match self.projection {
+            Some(_) => { self.projected.get(&v).is_some() }
+            None    => { self.own.get(&v).is_some() }
+        }
+    }
+
+    // Greedily pick one true literal per clause, preferring literals outside of the projection:
+    fn shrinkModel(&self, model : &VarMap<bool>) -> VarMap<bool> {
+        let isTrue = |lit : Lit| { model.get(&lit.var()).map(|&val| val != lit.sign()).unwrap_or(false) };
+
+        let mut kept = VarMap::new();
+        for c in self.clauses.iter() {
+            if c.iter().any(|&lit| isTrue(lit) && kept.get(&lit.var()).is_some()) { continue; }
+
+            let pick = c.iter().cloned().find(|&lit| isTrue(lit) && !self.isProjected(lit.var()))
+                        .or_else(|| c.iter().cloned().find(|&lit| isTrue(lit)));
+            if let Some(lit) = pick {
+                kept.insert(&lit.var(), !lit.sign());
+            }
+        }
+
+        let mut cube = VarMap::new();
+        for (v, &val) in kept.iter() {
+            if self.isProjected(v) {
+                cube.insert(&v, val);
+            }
+        }
+        cube
+    }
+
+    fn project(&self, model : &VarMap<bool>) -> VarMap<bool> {
+        match self.projection {
+            Some(ref vars) => {
+                let mut cube = VarMap::new();
+                for v in vars.iter() {
+                    if let Some(&val) = model.get(v) {
+                        cube.insert(v, val);
+                    }
+                }
+                cube
+            }
+
+            None           => {
+                let mut cube = VarMap::new();
+                for (v, &val) in model.iter() {
+                    if self.own.get(&v).is_some() {
+                        cube.insert(&v, val);
+                    }
+                }
+                cube
+            }
+        }
+    }
+}
+
+impl<S : Solver> Iterator for Enumerator<S> {
+    type Item = VarMap<bool>;
+
+    fn next(&mut self) -> Option<VarMap<bool>> {
+        if self.done { return None; }
+
+        match self.solver.solve() {
+            TotalResult::SAT(model) => {
+                let cube = if self.shrink { self.shrinkModel(&model) } else { self.project(&model) };
+                let block : Vec<Lit> = cube.iter().map(|(v, &val)| v.lit(val)).collect();
+
+                // The blocking clause is part of the formula the next model is shrunk against, so
+                // reported models never overlap:
+                if block.is_empty() || !self.addClause(&block) {
+                    self.done = true;
+                }
+                Some(cube)
+            }
+
+            _                       => {
+                self.done = true;
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use sat::{PartialResult, IncrementalSolver};
+use sat::formula::{Var, Lit, VarMap};
+
+
+pub enum BackboneResult {
+    UnSAT,
+    Backbone(Vec<Lit>),   // Literals that are true in every model, sorted by variable.
+    Interrupted(Vec<Lit>), // The part of the backbone found before the solver was interrupted.
+    Indeterminate          // Interrupted before a first model was found.
+}
+
+
+// Compute the backbone over the given variables (all of them by default). Every literal of an
+// initial model is a candidate. A candidate is refuted by a model of the formula under its
+// negation, and that model also drops every other candidate it falsifies. Candidates that the
+// solver has already fixed at the ground level need no call at all. Found backbone literals are
+// added to the solver as units, so later calls can use them.
+pub fn compute<S : IncrementalSolver>(solver : &mut S, vars : Option<&[Var]>) -> BackboneResult {
+    let model =
+        match solver.solveUnder(&[]) {
+            PartialResult::SAT(model)     => { model }
+            PartialResult::Interrupted(_) => { return BackboneResult::Indeterminate; }
+            _                             => { return BackboneResult::UnSAT; }
+        };
+
+    let mut candidates : Vec<Lit> =
+        match vars {
+            Some(vars) => { vars.iter().filter_map(|v| model.get(v).map(|&val| v.lit(!val))).collect() }
+            None       => { model.iter().map(|(v, &val)| v.lit(!val)).collect() }
+        };
+
+    let mut backbone = Vec::new();
+    let mut fixed = VarMap::new();
+    let mut seen = 0;
+    loop {
+        // Take over the literals fixed at the ground level since the last call:
+        let ground = solver.groundLits();
+        if ground.len() < seen { seen = 0; }
+        if ground.len() > seen {
+            for &lit in ground[seen ..].iter() {
+                fixed.insert(&lit.var(), lit);
+            }
+            seen = ground.len();
+
+            candidates.retain(|&lit| {
+                if fixed.get(&lit.var()) == Some(&lit) {
+                    backbone.push(lit);
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        let lit =
+            match candidates.pop() {
+                Some(lit) => { lit }
+                None      => { break; }
+            };
+
+        match solver.solveUnder(&[!lit]) {
+            PartialResult::SAT(model)     => {
+                candidates.retain(|&p| model.get(&p.var()).map(|&val| val != p.sign()).unwrap_or(false));
+            }
+
+            PartialResult::UnSATUnder(_)  => {
+                backbone.push(lit);
+                solver.addClause(&[lit]);
+            }
+
+            PartialResult::UnSAT          => { return BackboneResult::UnSAT; }
+
+            PartialResult::Interrupted(_) => {
+                backbone.sort_by_key(|lit| lit.var().index());
+                return BackboneResult::Interrupted(backbone);
+            }
+        }
+    }
+
+    backbone.sort_by_key(|lit| lit.var().index());
+    BackboneResult::Backbone(backbone)
+}
@@ -0,0 +1,116 @@
+use std::io;
+use sat::{PartialResult, IncrementalSolver};
+use sat::aiger::{Aig, Reset};
+use sat::formula::Lit;
+
+
+// Path from an initial state to a bad one, as in AIGER witnesses:
+pub struct Trace {
+    pub property : usize,             // Index of the property that fails in the last step.
+    pub init     : Vec<Option<bool>>, // Initial values of latches, only chosen for uninitialized ones.
+    pub inputs   : Vec<Vec<bool>>     // Values of the inputs in every step.
+}
+
+
+pub enum BmcResult {
+    Counterexample(Trace),
+    Bounded(usize),     // No bad state is reachable in up to this many steps.
+    Unreachable,        // The constraints are violated in every path of some length, so nothing is reachable.
+    Interrupted(usize)  // No bad state is reachable in fewer steps than this.
+}
+
+
+// Bounded model checking by unrolling the transition relation one step at a time into the same
+// solver. Step 'k' is checked by assuming an activation literal of the disjunction of properties
+// at 'k'. Once the step is refuted, its properties are asserted to hold for all longer paths:
+pub fn check<S : IncrementalSolver>(aig : &Aig, solver : &mut S, max_depth : Option<usize>) -> BmcResult {
+    if aig.properties().is_empty() {
+        return BmcResult::Unreachable;
+    }
+
+    let mut state : Vec<Lit> = Vec::with_capacity(aig.latches.len());
+    for l in aig.latches.iter() {
+        let x = solver.newVar(None, true).posLit();
+        match l.reset {
+            Reset::Zero  => { solver.addClause(&[!x]); }
+            Reset::One   => { solver.addClause(&[x]); }
+            Reset::Undef => {}
+        }
+        state.push(x);
+    }
+    let init = state.clone();
+
+    let mut inputs : Vec<Vec<Lit>> = Vec::new();
+    let mut depth = 0;
+    loop {
+        let step : Vec<Lit> = aig.inputs.iter().map(|_| solver.newVar(None, true).posLit()).collect();
+        let frame = aig.encode(solver, &step, &state);
+        inputs.push(step);
+
+        for &c in aig.constraints.iter() {
+            solver.addClause(&[frame.lit(c)]);
+        }
+
+        let bad : Vec<Lit> = aig.properties().iter().map(|&p| frame.lit(p)).collect();
+        let act = solver.newVar(None, false).posLit();
+        let mut clause = vec![!act];
+        clause.extend(bad.iter().cloned());
+        solver.addClause(&clause);
+
+        info!("| BMC depth {:6}   variables {:10}   clauses {:10}", depth, solver.nVars(), solver.nClauses());
+        match solver.solveUnder(&[act]) {
+            PartialResult::SAT(model)       => {
+                let value = |lit : Lit| -> bool { model.get(&lit.var()).map(|&val| val != lit.sign()).unwrap_or(false) };
+                return BmcResult::Counterexample(Trace {
+                    property : bad.iter().position(|&lit| value(lit)).unwrap(),
+                    init     : aig.latches.iter().zip(init.iter()).map(|(l, &x)| if l.reset == Reset::Undef { Some(value(x)) } else { None }).collect(),
+                    inputs   : inputs.iter().map(|step| step.iter().map(|&x| value(x)).collect()).collect()
+                });
+            }
+
+            PartialResult::UnSATUnder(_)    => {
+                solver.addClause(&[!act]);
+                for &lit in bad.iter() {
+                    solver.addClause(&[!lit]);
+                }
+            }
+
+            PartialResult::UnSAT            => { return BmcResult::Unreachable; }
+            PartialResult::Interrupted(_)   => { return BmcResult::Interrupted(depth); }
+        }
+
+        if max_depth.map(|max| depth >= max).unwrap_or(false) {
+            return BmcResult::Bounded(depth);
+        }
+
+        state = aig.latches.iter().map(|l| frame.lit(l.next)).collect();
+        depth += 1;
+    }
+}
+
+
+// Witness in the format of the hardware model checking competition: result, failing property,
+// initial state and one line of inputs per step. Initialized latches are written with their
+// reset values:
+pub fn writeWitness<W : io::Write>(stream : &mut W, aig : &Aig, trace : &Trace) -> io::Result<()> {
+    try!(writeln!(stream, "1"));
+    try!(writeln!(stream, "b{}", trace.property));
+
+    let init : String = aig.latches.iter().zip(trace.init.iter()).map(|(l, &val)| {
+        match (l.reset, val) {
+            (Reset::One, _)  => { '1' }
+            (_, Some(true))  => { '1' }
+            _                => { '0' }
+        }
+    }).collect();
+    try!(writeln!(stream, "{}", init));
+
+    for step in trace.inputs.iter() {
+        let line : String = step.iter().map(|&val| if val { '1' } else { '0' }).collect();
+        try!(writeln!(stream, "{}", line));
+    }
+
+    try!(writeln!(stream, "."));
+    Ok(())
+}
+
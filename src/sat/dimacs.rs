@@ -1,21 +1,214 @@
-// TODO: wait for io stabilization and completely rewrite it
-use std::{io, str};
+use std::{fs, io, ops, path};
+use std::io::BufRead;
 use std::borrow::Borrow;
 use std::collections::{HashSet, HashMap};
 use sat::formula::{Var, Lit, VarMap};
-use sat::Solver;
+use sat::{encode, Solver};
+use flate2::read::MultiGzDecoder;
+use bzip2::read::MultiBzDecoder;
+use xz2::read::XzDecoder;
+use xz2::stream::Stream;
+
+
+// The formula the solver currently holds, in plain CNF. Variables that no longer occur are left out
+// and the rest are numbered densely in the order of their original ids, each listed as 'c map <id>
+// <original id>' before the header. Variables the solver introduced itself come next, without an
+// original id, and XOR constraints are encoded into clauses over fresh variables after those:
+pub fn write<W : io::Write, S : Solver>(stream : &mut W, solver : &S, backward_subst : &VarMap<i32>) -> io::Result<()> {
+    let formula =
+        match solver.formula() {
+            Some(formula) => { formula }
+            None          => { return Err(io::Error::new(io::ErrorKind::Other, "formula has constraints that DIMACS can not express")); }
+        };
+
+    let mut vars : Vec<Var> = formula.clauses.iter().chain(formula.xors.iter()).flat_map(|c| c.iter().map(|lit| lit.var())).collect();
+    vars.sort_by_key(|v| (backward_subst.get(v).cloned().unwrap_or(i32::max_value()), v.index()));
+    vars.dedup();
+
+    let mut ids = VarMap::new();
+    for (i, v) in vars.iter().enumerate() {
+        ids.insert(v, i as i32 + 1);
+    }
+    let id = |lit : &Lit| { let id = ids[&lit.var()]; if lit.sign() { -id } else { id } };
+
+    let mut clauses : Vec<Vec<i32>> = formula.clauses.iter().map(|c| c.iter().map(&id).collect()).collect();
+    let mut next = vars.len() as i32;
+    for x in formula.xors.iter() {
+        let lits : Vec<Id> = x.iter().map(|lit| Id(id(lit))).collect();
+        let encoded = encode::xorClauses(&lits, || { next += 1; Id(next) });
+        clauses.extend(encoded.into_iter().map(|c| c.into_iter().map(|Id(lit_id)| lit_id).collect::<Vec<i32>>()));
+    }
+
+    for (i, v) in vars.iter().enumerate() {
+        if let Some(&orig) = backward_subst.get(v) {
+            try!(writeln!(stream, "c map {} {}", i + 1, orig));
+        }
+    }
+
+    try!(writeln!(stream, "p cnf {} {}", next, clauses.len()));
+    for c in clauses.iter() {
+        for lit_id in c.iter() {
+            try!(write!(stream, "{} ", lit_id));
+        }
+        try!(writeln!(stream, "0"));
+    }
+    Ok(())
+}
+
+
+// DIMACS literal for encoding XOR constraints, negated by '!':
+#[derive(Clone, Copy)]
+struct Id(i32);
+
+impl ops::Not for Id {
+    type Output = Id;
+
+    fn not(self) -> Id {
+        Id(-self.0)
+    }
+}
 
 
-pub fn write<W : io::Write, S : Solver>(_ : &mut W, _ : &S) -> io::Result<()> {
-    // TODO: implement
-    unimplemented!()
+pub struct Parsed {
+    pub backward_subst : VarMap<i32>,
+    pub projection     : Option<Vec<Var>> // Variables listed in 'c ind' lines, if there are any.
 }
 
 
-pub fn parse<R : io::Read, S : Solver>(stream : &mut R, solver : &mut S, validate : bool) -> io::Result<VarMap<i32>> {
+pub fn parse<R : io::Read, S : Solver>(stream : &mut R, solver : &mut S, validate : bool) -> io::Result<Parsed> {
     let mut subst = Subst::new(solver);
-    try!(DimacsParser::parse(stream, validate, |cl| { subst.addClause(cl) }));
-    Ok(subst.backward_subst)
+    let mut assumptions = false;
+    let ind = try!(DimacsParser::parse(stream, validate, |line, cl| {
+        match line {
+            Line::Clause      => { subst.addClause(cl) }
+            Line::Xor         => { subst.addXor(cl) }
+            Line::Assumptions => { assumptions = true; }
+        }
+    }));
+
+    if assumptions {
+        return Err(io::Error::new(io::ErrorKind::Other, "iCNF assumptions are not supported here"));
+    }
+
+    let projection = ind.map(|ids| { ids.iter().map(|&id| { subst.litById(id).var() }).collect() });
+    Ok(Parsed { backward_subst : subst.backward_subst
+              , projection     : projection
+              })
+}
+
+
+// Raw clauses as written in the file, without creating any variables:
+pub fn parseClauses<R : io::Read, F : FnMut(Vec<i32>) -> ()>(stream : &mut R, validate : bool, mut clause : F) -> io::Result<()> {
+    let mut unsupported = None;
+    try!(DimacsParser::parse(stream, validate, |line, cl| {
+        match line {
+            Line::Clause      => { clause(cl); }
+            Line::Xor         => { unsupported = Some("XOR constraints are not supported here"); }
+            Line::Assumptions => { unsupported = Some("iCNF assumptions are not supported here"); }
+        }
+    }));
+
+    match unsupported {
+        Some(msg) => { Err(io::Error::new(io::ErrorKind::Other, msg)) }
+        None      => { Ok(()) }
+    }
+}
+
+
+// Incremental CNF ('p inccnf'): clauses are added as they come, and every 'a' line is passed to
+// 'query' as assumptions, together with the solver holding the clauses read so far. Parsing stops
+// at the first error returned by 'query'. Plain DIMACS is accepted as well, it just has no queries:
+pub fn parseIncremental<R, S, F>(stream : &mut R, solver : &mut S, mut query : F) -> io::Result<VarMap<i32>>
+    where R : io::Read, S : Solver, F : FnMut(&mut S, &VarMap<i32>, Vec<Lit>) -> io::Result<()> {
+    let mut subst = Subst::new(solver);
+    let mut failed = None;
+    try!(DimacsParser::parse(stream, false, |line, cl| {
+        if failed.is_some() { return; }
+        match line {
+            Line::Clause      => { subst.addClause(cl) }
+            Line::Xor         => { subst.addXor(cl) }
+            Line::Assumptions => {
+                let lits = cl.iter().map(|&lit_id| subst.litById(lit_id)).collect();
+                if let Err(err) = query(subst.solver, &subst.backward_subst, lits) {
+                    failed = Some(err);
+                }
+            }
+        }
+    }));
+
+    match failed {
+        Some(err) => { Err(err) }
+        None      => { Ok(subst.backward_subst) }
+    }
+}
+
+
+// Whether the stream starts with an iCNF header, comments aside. Lines are read up to the first one
+// that is not a comment, however long that takes on a pipe, and are handed back in front of the rest
+// of the stream, so that it can be parsed from the start:
+pub fn isIncremental<R : io::BufRead>(mut stream : R) -> io::Result<(bool, io::Chain<io::Cursor<Vec<u8>>, R>)> {
+    let mut head = Vec::new();
+    let incremental =
+        loop {
+            let start = head.len();
+            if try!(stream.read_until(b'\n', &mut head)) == 0 { break false; }
+
+            let line = String::from_utf8_lossy(&head[start ..]);
+            let line = line.trim_left();
+            if line.is_empty() || line.starts_with('c') { continue; }
+            break line.starts_with("p inccnf");
+        };
+    Ok((incremental, io::Read::chain(io::Cursor::new(head), stream)))
+}
+
+
+// Opens the input at 'path', '-' standing for the standard input. Inputs compressed by gzip, bzip2,
+// xz or legacy lzma are recognized by their first bytes and decompressed while they are read:
+pub fn open(path : &path::Path) -> io::Result<Box<io::BufRead>> {
+    let input : Box<io::Read> =
+        if path == path::Path::new("-") {
+            Box::new(io::stdin())
+        } else {
+            Box::new(try!(fs::File::open(path)))
+        };
+
+    let mut input = io::BufReader::with_capacity(BUFFER_SIZE, input);
+    let magic = {
+        let head = try!(input.fill_buf());
+        head[.. head.len().min(6)].to_vec()
+    };
+
+    let decoded : Box<io::Read> =
+        if magic.starts_with(&[0x1F, 0x8B]) {
+            Box::new(MultiGzDecoder::new(input))
+        } else if magic.starts_with(b"BZh") {
+            Box::new(MultiBzDecoder::new(input))
+        } else if magic.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) {
+            Box::new(XzDecoder::new_multi_decoder(input))
+        } else if magic.starts_with(&[0x5D, 0x00, 0x00]) {
+            Box::new(XzDecoder::new_stream(input, try!(Stream::new_lzma_decoder(u64::max_value()))))
+        } else {
+            return Ok(Box::new(input));
+        };
+    Ok(Box::new(io::BufReader::with_capacity(BUFFER_SIZE, decoded)))
+}
+
+
+// Raw clauses with their groups. Plain DIMACS puts every clause into a group of its own, numbered
+// from 1 in file order. In group oriented DIMACS ('p gcnf'), clauses marked '{0}' belong to no group:
+pub fn parseGroups<R : io::Read, F : FnMut(Option<usize>, Vec<i32>) -> ()>(stream : &mut R, clause : F) -> io::Result<()> {
+    let mut p = DimacsParser::new(stream, false);
+    try!(p.next());
+    p.parseGroupsMe(clause)
+}
+
+
+// Raw clauses of weighted partial MaxSAT, both the old format with a 'p wcnf' header and the 2022
+// one without it. Hard clauses come with no weight:
+pub fn parseWeighted<R : io::Read, F : FnMut(Option<u64>, Vec<i32>) -> ()>(stream : &mut R, clause : F) -> io::Result<()> {
+    let mut p = DimacsParser::new(stream, false);
+    try!(p.next());
+    p.parseWeightedMe(clause)
 }
 
 
@@ -29,6 +222,27 @@ pub fn writeModel<W : io::Write>(stream : &mut W, backward_subst : &VarMap<i32>,
 }
 
 
+// Incremental CNF: the clauses after a 'p inccnf' header, then one line of assumptions per cube:
+pub fn writeIcnf<W : io::Write>(stream : &mut W, clauses : &[Vec<i32>], cubes : &[Vec<i32>]) -> io::Result<()> {
+    try!(writeln!(stream, "p inccnf"));
+    for c in clauses.iter() {
+        for lit_id in c.iter() {
+            try!(write!(stream, "{} ", lit_id));
+        }
+        try!(writeln!(stream, "0"));
+    }
+
+    for c in cubes.iter() {
+        try!(write!(stream, "a "));
+        for lit_id in c.iter() {
+            try!(write!(stream, "{} ", lit_id));
+        }
+        try!(writeln!(stream, "0"));
+    }
+    Ok(())
+}
+
+
 pub fn validateModel<R : io::Read>(stream : &mut R, backward_subst : &VarMap<i32>, model : &VarMap<bool>) -> io::Result<bool> {
     let mut lits = HashSet::new();
     for (var, &value) in model.iter() {
@@ -44,7 +258,15 @@ pub fn validateModel<R : io::Read>(stream : &mut R, backward_subst : &VarMap<i32
     }
 
     let mut ok = true;
-    try!(DimacsParser::parse(stream, false, |cl| {
+    try!(DimacsParser::parse(stream, false, |line, cl| {
+        if line == Line::Assumptions { return; }
+        if line == Line::Xor {
+            if cl.iter().filter(|&&lit| lits.contains(&lit)).count() % 2 == 0 {
+                ok = false;
+            }
+            return;
+        }
+
         let mut found = false;
         for lit in cl {
             if lits.contains(&lit) {
@@ -62,6 +284,9 @@ pub fn validateModel<R : io::Read>(stream : &mut R, backward_subst : &VarMap<i32
 }
 
 
+const BUFFER_SIZE : usize = 1 << 16;
+
+
 struct Subst<'s, S : 's> {
     solver         : &'s mut S,
     forward_subst  : HashMap<i32, Var>,
@@ -81,13 +306,16 @@ impl<'s, S : Solver> Subst<'s, S> {
         self.solver.addClause(lits.borrow());
     }
 
+    pub fn addXor(&mut self, raw : Vec<i32>) {
+        let lits : Vec<Lit> = raw.iter().map(|&lit_id| { self.litById(lit_id) }).collect();
+        self.solver.addXor(lits.borrow());
+    }
+
     fn litById(&mut self, lit_id : i32) -> Lit {
-        if !self.forward_subst.contains_key(&lit_id.abs()) {
-            // TODO: finish it
-            while (lit_id.abs() as usize) > self.solver.nVars() {
-                let idx = (self.solver.nVars() + 1) as i32;
-                self.newVar(idx);
-            }
+        // Constraints may bring auxiliary variables of their own, so the solver's count is no guide:
+        while !self.forward_subst.contains_key(&lit_id.abs()) {
+            let idx = (self.forward_subst.len() + 1) as i32;
+            self.newVar(idx);
         }
 
         self.forward_subst[&lit_id.abs()].lit(lit_id < 0)
@@ -101,29 +329,51 @@ impl<'s, S : Solver> Subst<'s, S> {
 }
 
 
-struct DimacsParser<'p> {
-    reader  : str::Chars<'p>,
-    cur     : Option<char>,
-    vars    : HashSet<i32>,
-    clauses : usize
+#[derive(PartialEq)]
+enum Line {
+    Clause,
+    Xor,         // 'x' lines.
+    Assumptions  // 'a' lines of iCNF.
 }
 
-impl<'p> DimacsParser<'p> {
-    pub fn parse<R : io::Read, F : FnMut(Vec<i32>) -> ()>(reader : &'p mut R, validate : bool, clause : F) -> io::Result<()> {
-        let mut buf = String::new();
-        try!(reader.read_to_string(&mut buf));
 
-        let mut p = DimacsParser { reader  : buf.chars()
-                                 , cur     : None
-                                 , vars    : HashSet::new()
-                                 , clauses : 0
-                                 };
+// Reads bytes through a buffer of its own, so clauses are passed on as soon as they are read:
+struct DimacsParser<R> {
+    reader   : R,
+    buf      : Box<[u8]>,
+    pos      : usize,
+    len      : usize,
+    cur      : Option<u8>,
+    validate : bool,
+    vars     : HashSet<i32>,  // Collected only to validate the header.
+    clauses  : usize,
+    ind      : Option<Vec<i32>>
+}
+
+impl<R : io::Read> DimacsParser<R> {
+    fn new(reader : R, validate : bool) -> DimacsParser<R> {
+        DimacsParser { reader   : reader
+                     , buf      : vec![0; BUFFER_SIZE].into_boxed_slice()
+                     , pos      : 0
+                     , len      : 0
+                     , cur      : None
+                     , validate : validate
+                     , vars     : HashSet::new()
+                     , clauses  : 0
+                     , ind      : None
+                     }
+    }
+
+    // Returns variables of 'c ind' lines, if any:
+    pub fn parse<F : FnMut(Line, Vec<i32>) -> ()>(reader : R, validate : bool, clause : F) -> io::Result<Option<Vec<i32>>> {
+        let mut p = DimacsParser::new(reader, validate);
         try!(p.next());
-        p.parseMe(validate, clause)
+        try!(p.parseMe(validate, clause));
+        Ok(p.ind)
     }
 
-    fn parseMe<F : FnMut(Vec<i32>) -> ()>(&mut self, validate : bool, mut clause : F) -> io::Result<()> {
-        enum State { Waiting, Parsing(usize, usize) }
+    fn parseMe<F : FnMut(Line, Vec<i32>) -> ()>(&mut self, validate : bool, mut clause : F) -> io::Result<()> {
+        enum State { Waiting, Parsing(usize, usize), Incremental }
 
         let mut state = State::Waiting;
         loop {
@@ -131,22 +381,28 @@ impl<'p> DimacsParser<'p> {
             match state {
                 State::Waiting => {
                     match self.current() {
-                        Some('c') => { try!(self.skipLine()); }
-
-                        _         => {
-                            try!(self.consume("p cnf"));
-                            let vars = try!(self.nextUInt());
-                            let clauses = try!(self.nextUInt());
-                            state = State::Parsing(vars, clauses);
+                        Some(b'c') => { try!(self.parseComment()); }
+
+                        _          => {
+                            try!(self.consume("p "));
+                            if self.current() == Some(b'i') {
+                                try!(self.consume("inccnf"));
+                                state = State::Incremental;
+                            } else {
+                                try!(self.consume("cnf"));
+                                let vars = try!(self.nextUInt());
+                                let clauses = try!(self.nextUInt());
+                                state = State::Parsing(vars, clauses);
+                            }
                         }
                     }
                 }
 
                 State::Parsing(vars, clauses) => {
                     match self.current() {
-                        Some('c') => { try!(self.skipLine()); }
+                        Some(b'c') => { try!(self.parseComment()); }
 
-                        None      => {
+                        None       => {
                             if validate {
                                 if clauses != self.clauses {
                                     return Err(io::Error::new(io::ErrorKind::Other,
@@ -161,9 +417,41 @@ impl<'p> DimacsParser<'p> {
                             return Ok(());
                         }
 
-                        _         => {
+                        Some(b'x') => {
+                            try!(self.next());
+                            let c = try!(self.parseClause());
+                            clause(Line::Xor, c);
+                        }
+
+                        _          => {
+                            let c = try!(self.parseClause());
+                            clause(Line::Clause, c);
+                        }
+                    }
+                }
+
+                // No header counts to check, and 'a' lines of assumptions between the clauses:
+                State::Incremental => {
+                    match self.current() {
+                        Some(b'c') => { try!(self.parseComment()); }
+
+                        None       => { return Ok(()); }
+
+                        Some(b'a') => {
+                            try!(self.next());
+                            let c = try!(self.parseClause());
+                            clause(Line::Assumptions, c);
+                        }
+
+                        Some(b'x') => {
+                            try!(self.next());
+                            let c = try!(self.parseClause());
+                            clause(Line::Xor, c);
+                        }
+
+                        _          => {
                             let c = try!(self.parseClause());
-                            clause(c);
+                            clause(Line::Clause, c);
                         }
                     }
                 }
@@ -171,6 +459,105 @@ impl<'p> DimacsParser<'p> {
         }
     }
 
+    fn parseGroupsMe<F : FnMut(Option<usize>, Vec<i32>) -> ()>(&mut self, mut clause : F) -> io::Result<()> {
+        let mut grouped = None;
+        loop {
+            try!(self.skipWhitespace());
+            match (self.current(), grouped) {
+                (Some(b'c'), _)  => { try!(self.skipLine()); }
+
+                (None, Some(_))  => { return Ok(()); }
+
+                (_, None)        => {
+                    try!(self.consume("p "));
+                    if self.current() == Some(b'g') {
+                        try!(self.consume("gcnf"));
+                        try!(self.nextUInt());
+                        grouped = Some(true);
+                    } else {
+                        try!(self.consume("cnf"));
+                        grouped = Some(false);
+                    }
+                    try!(self.nextUInt());
+                    try!(self.nextUInt());
+                }
+
+                (_, Some(true))  => {
+                    try!(self.consume("{"));
+                    let group = try!(self.nextUInt());
+                    try!(self.consume("}"));
+                    let c = try!(self.parseClause());
+                    clause(if group == 0 { None } else { Some(group) }, c);
+                }
+
+                (_, Some(false)) => {
+                    let c = try!(self.parseClause());
+                    clause(Some(self.clauses), c);
+                }
+            }
+        }
+    }
+
+    fn parseWeightedMe<F : FnMut(Option<u64>, Vec<i32>) -> ()>(&mut self, mut clause : F) -> io::Result<()> {
+        // Clauses with a weight of at least 'top' are hard. Without it, only 'h' marks hard clauses:
+        let mut top = None;
+        loop {
+            try!(self.skipWhitespace());
+            match self.current() {
+                None       => { return Ok(()); }
+
+                Some(b'c') => { try!(self.skipLine()); }
+
+                Some(b'p') => {
+                    let line = try!(self.readLine());
+                    let words : Vec<&str> = line.split_whitespace().collect();
+                    if words.len() < 4 || words[1] != "wcnf" {
+                        return Err(io::Error::new(io::ErrorKind::Other, format!("PARSE ERROR! Unexpected header '{}'", line)));
+                    }
+
+                    if let Some(w) = words.get(4) {
+                        match w.parse::<u64>() {
+                            Ok(w)  => { top = Some(w); }
+                            Err(_) => {
+                                return Err(io::Error::new(io::ErrorKind::Other, format!("PARSE ERROR! Unexpected '{}' in the header", w)));
+                            }
+                        }
+                    }
+                }
+
+                Some(b'h') => {
+                    try!(self.next());
+                    let c = try!(self.parseClause());
+                    clause(None, c);
+                }
+
+                _          => {
+                    let weight = try!(self.nextUInt()) as u64;
+                    let c = try!(self.parseClause());
+                    clause(if top.map(|top| weight >= top).unwrap_or(false) { None } else { Some(weight) }, c);
+                }
+            }
+        }
+    }
+
+    fn parseComment(&mut self) -> io::Result<()> {
+        let line = try!(self.readLine());
+        let mut words = line[1 ..].split_whitespace();
+        if words.next() == Some("ind") {
+            let ind = self.ind.get_or_insert(Vec::new());
+            for w in words {
+                match w.parse::<i32>() {
+                    Ok(0)   => { break; }
+                    Ok(var) => { ind.push(var.abs()); }
+                    Err(_)  => {
+                        return Err(io::Error::new(io::ErrorKind::Other, format!("PARSE ERROR! Unexpected '{}' in 'c ind' line", w)));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn parseClause(&mut self) -> io::Result<Vec<i32>> {
         let mut lits = Vec::new();
         loop {
@@ -179,7 +566,7 @@ impl<'p> DimacsParser<'p> {
                 self.clauses += 1;
                 return Ok(lits);
             } else {
-                self.vars.insert(lit.abs());
+                if self.validate { self.vars.insert(lit.abs()); }
                 lits.push(lit);
             }
         }
@@ -188,39 +575,70 @@ impl<'p> DimacsParser<'p> {
 
     #[inline]
     pub fn next(&mut self) -> io::Result<()> {
-        self.cur = self.reader.next();
+        if self.pos == self.len {
+            self.pos = 0;
+            self.len = try!(self.fill());
+        }
+
+        if self.pos < self.len {
+            self.cur = Some(self.buf[self.pos]);
+            self.pos += 1;
+        } else {
+            self.cur = None;
+        }
         Ok(())
     }
 
+    fn fill(&mut self) -> io::Result<usize> {
+        loop {
+            match self.reader.read(&mut self.buf) {
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                result                                                   => { return result; }
+            }
+        }
+    }
+
     #[inline]
-    pub fn current(&self) -> Option<char> {
+    pub fn current(&self) -> Option<u8> {
         self.cur
     }
 
     pub fn skipWhitespace(&mut self) -> io::Result<()> {
         loop {
             match self.cur {
-                None                          => break,
-                Some(c) if !c.is_whitespace() => break,
-                _                             => try!(self.next())
+                None                                => break,
+                Some(c) if !c.is_ascii_whitespace() => break,
+                _                                   => try!(self.next())
             }
         }
         Ok(())
     }
 
+    pub fn readLine(&mut self) -> io::Result<String> {
+        let mut line = Vec::new();
+        loop {
+            match self.cur {
+                None        => break,
+                Some(b'\n') => { try!(self.next()); break; }
+                Some(c)     => { line.push(c); try!(self.next()) }
+            }
+        }
+        Ok(String::from_utf8_lossy(&line).into_owned())
+    }
+
     pub fn skipLine(&mut self) -> io::Result<()> {
         loop {
             match self.cur {
-                None       => break,
-                Some('\n') => { try!(self.next()); break; }
-                _          => { try!(self.next()) }
+                None        => break,
+                Some(b'\n') => { try!(self.next()); break; }
+                _           => { try!(self.next()) }
             }
         }
         Ok(())
     }
 
     pub fn consume(&mut self, target : &str) -> io::Result<()> {
-        for tc in target.chars() {
+        for tc in target.bytes() {
             match self.cur {
                 Some(c) if c == tc => { try!(self.next()) }
                 _                  => {
@@ -235,7 +653,7 @@ impl<'p> DimacsParser<'p> {
         let mut len : usize = 0;
         let mut value = 0;
         loop {
-            match self.cur.and_then(|c| { c.to_digit(10) }) {
+            match self.cur.and_then(|c| { (c as char).to_digit(10) }) {
                 Some(d)      => {
                     value = value * 10 + (d as usize);
                     len += 1;
@@ -255,9 +673,9 @@ impl<'p> DimacsParser<'p> {
         try!(self.skipWhitespace());
         let sign =
             match self.cur {
-                Some('+') => { try!(self.next()); 1 }
-                Some('-') => { try!(self.next()); -1 }
-                _         => 1
+                Some(b'+') => { try!(self.next()); 1 }
+                Some(b'-') => { try!(self.next()); -1 }
+                _          => 1
             };
 
         let val = try!(self.readIntBody());
@@ -267,8 +685,8 @@ impl<'p> DimacsParser<'p> {
     pub fn nextUInt(&mut self) -> io::Result<usize> {
         try!(self.skipWhitespace());
         match self.cur {
-            Some('+') => { try!(self.next()) }
-            _         => {}
+            Some(b'+') => { try!(self.next()) }
+            _          => {}
         }
         self.readIntBody()
     }
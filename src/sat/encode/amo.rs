@@ -0,0 +1,36 @@
+use sat::Solver;
+use sat::formula::Lit;
+
+
+pub fn pairwise<S : Solver>(solver : &mut S, lits : &[Lit]) -> bool {
+    let mut ok = true;
+    for i in 0 .. lits.len() {
+        for j in i + 1 .. lits.len() {
+            ok &= solver.addClause(&[!lits[i], !lits[j]]);
+        }
+    }
+    ok
+}
+
+
+// Klieber and Kwon: split the literals into groups of three, each with a commander that is implied
+// by every literal of its group, and recursively allow at most one commander:
+pub fn commander<S : Solver>(solver : &mut S, lits : &[Lit]) -> bool {
+    if lits.len() <= 4 {
+        return pairwise(solver, lits);
+    }
+
+    let mut ok = true;
+    let mut commanders = Vec::new();
+    for group in lits.chunks(3) {
+        ok &= pairwise(solver, group);
+
+        let c = solver.newVar(None, true).posLit();
+        for &lit in group.iter() {
+            ok &= solver.addClause(&[!lit, c]);
+        }
+        commanders.push(c);
+    }
+
+    ok && commander(solver, &commanders)
+}
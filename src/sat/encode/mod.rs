@@ -0,0 +1,117 @@
+use std::ops;
+use sat::Solver;
+use sat::formula::Lit;
+pub use self::totalizer::Totalizer;
+
+mod amo;
+mod network;
+mod pb;
+mod seq_counter;
+mod totalizer;
+mod xor;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmoEncoding {
+    Pairwise,  // No auxiliary variables, quadratic number of clauses.
+    Ladder,    // Linear, 'n - 1' auxiliary variables.
+    Commander  // Linear, pairwise inside groups of three under a commander variable.
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CardEncoding {
+    SequentialCounter,
+    Totalizer,
+    CardinalityNetwork
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PbEncoding {
+    Bdd,
+    Adder,
+    SortingNetwork
+}
+
+
+// Every encoding returns 'false' if the solver became unsatisfiable while adding its clauses, just
+// like 'Solver::addClause'. Auxiliary variables come from 'Solver::newVar' and only have to be true
+// when the constraint is violated, so the clauses are just implications towards them.
+
+pub fn atMostOne<S : Solver>(solver : &mut S, lits : &[Lit], encoding : AmoEncoding) -> bool {
+    match encoding {
+        AmoEncoding::Pairwise  => { amo::pairwise(solver, lits) }
+        AmoEncoding::Ladder    => { seq_counter::atMost(solver, lits, 1) }
+        AmoEncoding::Commander => { amo::commander(solver, lits) }
+    }
+}
+
+
+pub fn atMostK<S : Solver>(solver : &mut S, lits : &[Lit], k : usize, encoding : CardEncoding) -> bool {
+    if k >= lits.len() { return true; }
+    if k == 0 {
+        return lits.iter().all(|&lit| solver.addClause(&[!lit]));
+    }
+
+    match encoding {
+        CardEncoding::SequentialCounter  => { seq_counter::atMost(solver, lits, k) }
+        CardEncoding::CardinalityNetwork => { network::atMost(solver, lits, k) }
+        CardEncoding::Totalizer          => {
+            let mut tot = Totalizer::new(solver, lits, k + 1);
+            match tot.atMost(solver, k) {
+                Some(lit) => { solver.addClause(&[lit]) }
+                None      => { true }
+            }
+        }
+    }
+}
+
+
+// At least 'k' of the literals are true iff at most 'n - k' of their negations are:
+pub fn atLeastK<S : Solver>(solver : &mut S, lits : &[Lit], k : usize, encoding : CardEncoding) -> bool {
+    if k > lits.len() {
+        return solver.addClause(&[]);
+    }
+
+    let negated : Vec<Lit> = lits.iter().map(|&lit| !lit).collect();
+    atMostK(solver, &negated, lits.len() - k, encoding)
+}
+
+
+// Pseudo-Boolean constraint 'sum of coefficient * literal >= bound'. Without an encoding, one is
+// chosen for the constraint at hand:
+pub fn atLeastPB<S : Solver>(solver : &mut S, terms : &[(u64, Lit)], bound : u64, encoding : Option<PbEncoding>) -> bool {
+    match pb::atLeast(solver, terms, bound, encoding) {
+        pb::Root::True      => { true }
+        pb::Root::False     => { solver.addClause(&[]) }
+        pb::Root::Lit(root) => { solver.addClause(&[root]) }
+    }
+}
+
+
+// Same constraint, but only enforced under the returned literal, e.g. as an assumption:
+pub fn atLeastPBLit<S : Solver>(solver : &mut S, terms : &[(u64, Lit)], bound : u64, encoding : Option<PbEncoding>) -> Lit {
+    match pb::atLeast(solver, terms, bound, encoding) {
+        pb::Root::True      => { solver.newVar(None, true).posLit() }
+        pb::Root::False     => {
+            let lit = solver.newVar(None, true).posLit();
+            solver.addClause(&[!lit]);
+            lit
+        }
+        pb::Root::Lit(root) => { root }
+    }
+}
+
+
+// An odd number of the literals is true. Long constraints are cut into pieces of five literals,
+// chained by fresh variables:
+pub fn xor<S : Solver>(solver : &mut S, lits : &[Lit]) -> bool {
+    xor::chain(solver, lits)
+}
+
+// The clauses 'xor' adds, for literals of any kind. 'fresh' makes the ones of chaining variables:
+pub fn xorClauses<L, F>(lits : &[L], fresh : F) -> Vec<Vec<L>>
+    where L : Copy + ops::Not<Output = L>, F : FnMut() -> L {
+    xor::clauses(lits, fresh)
+}
@@ -0,0 +1,140 @@
+use sat::Solver;
+use sat::formula::Lit;
+
+
+// Cardinality networks of Asín et al. with the clauses of one direction only: output 'i' of a
+// network is implied by at least 'i' true inputs. The inputs are padded with false literals to a
+// multiple of 'm', the smallest power of two above 'k', and at most 'k' of them are allowed by
+// forbidding output 'k + 1'.
+pub fn atMost<S : Solver>(solver : &mut S, lits : &[Lit], k : usize) -> bool {
+    if k >= lits.len() { return true; }
+
+    match outputs(solver, lits, k) {
+        Some(outputs) => { solver.addClause(&[!outputs[k]]) }
+        None          => { false }
+    }
+}
+
+
+// Sorted outputs of a network that counts up to 'k + 1' true literals, i.e. output 'i' (from zero)
+// is implied by at least 'i + 1' of them. 'None' if the solver became unsatisfiable:
+pub fn outputs<S : Solver>(solver : &mut S, lits : &[Lit], k : usize) -> Option<Vec<Lit>> {
+    let m = (k + 1).next_power_of_two();
+    let mut inputs = lits.to_vec();
+    if inputs.len() % m != 0 {
+        let f = solver.newVar(None, true).negLit();
+        if !solver.addClause(&[f]) { return None; }
+        while inputs.len() % m != 0 {
+            inputs.push(!f);
+        }
+    }
+
+    let mut net = Network { solver : solver, ok : true };
+    let outputs = net.card(&inputs, m);
+    if net.ok { Some(outputs) } else { None }
+}
+
+
+struct Network<'s, S : 's> {
+    solver : &'s mut S,
+    ok     : bool
+}
+
+impl<'s, S : Solver> Network<'s, S> {
+    fn fresh(&mut self) -> Lit {
+        self.solver.newVar(None, true).posLit()
+    }
+
+    fn imply(&mut self, clause : &[Lit]) {
+        self.ok &= self.solver.addClause(clause);
+    }
+
+    // Two-comparator: 'c1' is the larger and 'c2' the smaller of 'a' and 'b':
+    fn comparator(&mut self, a : Lit, b : Lit) -> (Lit, Lit) {
+        let c1 = self.fresh();
+        let c2 = self.fresh();
+        self.imply(&[!a, c1]);
+        self.imply(&[!b, c1]);
+        self.imply(&[!a, !b, c2]);
+        (c1, c2)
+    }
+
+    // Merge two sorted sequences of the same power of two length:
+    fn merge(&mut self, a : &[Lit], b : &[Lit]) -> Vec<Lit> {
+        assert!(a.len() == b.len());
+        if a.len() == 1 {
+            let (c1, c2) = self.comparator(a[0], b[0]);
+            return vec![c1, c2];
+        }
+
+        let (odd_a, even_a) = split(a);
+        let (odd_b, even_b) = split(b);
+        let d = self.merge(&odd_a, &odd_b);
+        let e = self.merge(&even_a, &even_b);
+
+        let n = a.len();
+        let mut c = Vec::with_capacity(2 * n);
+        c.push(d[0]);
+        for i in 0 .. n - 1 {
+            let (x, y) = self.comparator(d[i + 1], e[i]);
+            c.push(x);
+            c.push(y);
+        }
+        c.push(e[n - 1]);
+        c
+    }
+
+    fn sort(&mut self, a : &[Lit]) -> Vec<Lit> {
+        if a.len() == 1 {
+            return a.to_vec();
+        }
+
+        let (left, right) = a.split_at(a.len() / 2);
+        let l = self.sort(left);
+        let r = self.sort(right);
+        self.merge(&l, &r)
+    }
+
+    // Merge two sorted sequences of length 'n', keeping the first 'n + 1' outputs only:
+    fn simplifiedMerge(&mut self, a : &[Lit], b : &[Lit]) -> Vec<Lit> {
+        assert!(a.len() == b.len());
+        if a.len() == 1 {
+            let (c1, c2) = self.comparator(a[0], b[0]);
+            return vec![c1, c2];
+        }
+
+        let (odd_a, even_a) = split(a);
+        let (odd_b, even_b) = split(b);
+        let d = self.simplifiedMerge(&odd_a, &odd_b);
+        let e = self.simplifiedMerge(&even_a, &even_b);
+
+        let mut c = Vec::with_capacity(a.len() + 1);
+        c.push(d[0]);
+        for i in 0 .. a.len() / 2 {
+            let (x, y) = self.comparator(d[i + 1], e[i]);
+            c.push(x);
+            c.push(y);
+        }
+        c
+    }
+
+    // The 'm' largest of the inputs, sorted. The number of inputs is a multiple of 'm':
+    fn card(&mut self, a : &[Lit], m : usize) -> Vec<Lit> {
+        if a.len() == m {
+            return self.sort(a);
+        }
+
+        let (first, rest) = a.split_at(m);
+        let d = self.card(first, m);
+        let e = self.card(rest, m);
+        let mut c = self.simplifiedMerge(&d, &e);
+        c.truncate(m);
+        c
+    }
+}
+
+
+// Elements at odd and even positions, counting from one:
+fn split(a : &[Lit]) -> (Vec<Lit>, Vec<Lit>) {
+    (a.iter().cloned().step_by(2).collect(), a.iter().cloned().skip(1).step_by(2).collect())
+}
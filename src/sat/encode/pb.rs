@@ -0,0 +1,256 @@
+use std::cmp;
+use std::collections::HashMap;
+use sat::Solver;
+use sat::formula::Lit;
+use super::{network, PbEncoding, Totalizer};
+
+
+const BDD_NODE_LIMIT : usize = 50000;
+const SORTER_INPUT_LIMIT : u64 = 2048;
+
+
+// Root of an encoded constraint: the literal implies the constraint.
+pub enum Root {
+    True,
+    False,
+    Lit(Lit)
+}
+
+
+// Encode 'sum of coefficient * literal >= bound' for positive coefficients. Without an explicit
+// encoding, cardinality constraints go to a totalizer and the rest to a BDD, unless that grows too
+// large. Then small sums are sorted in unary and big ones are added in binary:
+pub fn atLeast<S : Solver>(solver : &mut S, terms : &[(u64, Lit)], bound : u64, encoding : Option<PbEncoding>) -> Root {
+    if bound == 0 { return Root::True; }
+
+    // Coefficients above the bound behave like the bound itself:
+    let mut terms : Vec<(u64, Lit)> = terms.iter().filter(|&&(a, _)| a > 0).map(|&(a, lit)| (cmp::min(a, bound), lit)).collect();
+    terms.sort_by(|x, y| y.0.cmp(&x.0));
+
+    let total : u64 = terms.iter().map(|&(a, _)| a).sum();
+    if total < bound { return Root::False; }
+
+    match encoding {
+        Some(PbEncoding::Bdd)            => { bdd(solver, &terms, bound, usize::max_value()).unwrap() }
+        Some(PbEncoding::Adder)          => { adder(solver, &terms, bound) }
+        Some(PbEncoding::SortingNetwork) => { sorter(solver, &terms, bound, total) }
+        None                             => {
+            if terms.iter().all(|&(a, _)| a == terms[0].0) {
+                return cardinality(solver, &terms, (bound + terms[0].0 - 1) / terms[0].0);
+            }
+
+            match bdd(solver, &terms, bound, BDD_NODE_LIMIT) {
+                Some(root)                          => { root }
+                None if total <= SORTER_INPUT_LIMIT => { sorter(solver, &terms, bound, total) }
+                None                                => { adder(solver, &terms, bound) }
+            }
+        }
+    }
+}
+
+
+// At least 'k' of the literals are true iff at most 'n - k' of their negations are:
+fn cardinality<S : Solver>(solver : &mut S, terms : &[(u64, Lit)], k : u64) -> Root {
+    let negated : Vec<Lit> = terms.iter().map(|&(_, lit)| !lit).collect();
+    let mut tot = Totalizer::new(solver, &negated, 1);
+    match tot.atMost(solver, negated.len() - (k as usize)) {
+        Some(lit) => { Root::Lit(lit) }
+        None      => { Root::True }
+    }
+}
+
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Node {
+    True,
+    False,
+    Inner(usize)
+}
+
+
+// Eén and Sörensson: node '(i, k)' stands for 'the terms from i on sum up to at least k'. It
+// branches on the literal of term 'i'. Equal children are merged, and the whole construction is
+// given up once it needs more than 'limit' nodes.
+fn bdd<S : Solver>(solver : &mut S, terms : &[(u64, Lit)], bound : u64, limit : usize) -> Option<Root> {
+    let mut suffix = vec![0; terms.len() + 1];
+    for i in (0 .. terms.len()).rev() {
+        suffix[i] = suffix[i + 1] + terms[i].0;
+    }
+
+    let mut builder = BddBuilder { terms  : terms
+                                 , suffix : suffix
+                                 , memo   : HashMap::new()
+                                 , nodes  : Vec::new()
+                                 , limit  : limit
+                                 };
+    let root = match builder.build(0, bound) { Some(root) => { root } None => { return None; } };
+
+    let vars : Vec<Lit> = builder.nodes.iter().map(|_| solver.newVar(None, true).posLit()).collect();
+    let lit = |node : Node| { match node { Node::Inner(i) => { Some(vars[i]) } _ => { None } } };
+    for (i, &(x, hi, lo)) in builder.nodes.iter().enumerate() {
+        for &(branch, child) in [(!x, hi), (x, lo)].iter() {
+            match child {
+                Node::True     => {}
+                Node::False    => { solver.addClause(&[!vars[i], branch]); }
+                Node::Inner(_) => { solver.addClause(&[!vars[i], branch, lit(child).unwrap()]); }
+            }
+        }
+    }
+
+    Some(match root {
+        Node::True     => { Root::True }
+        Node::False    => { Root::False }
+        Node::Inner(i) => { Root::Lit(vars[i]) }
+    })
+}
+
+
+struct BddBuilder<'t> {
+    terms  : &'t [(u64, Lit)],
+    suffix : Vec<u64>,
+    memo   : HashMap<(usize, u64), Node>,
+    nodes  : Vec<(Lit, Node, Node)>,
+    limit  : usize
+}
+
+impl<'t> BddBuilder<'t> {
+    fn build(&mut self, i : usize, k : u64) -> Option<Node> {
+        if k == 0 { return Some(Node::True); }
+        if self.suffix[i] < k { return Some(Node::False); }
+        if let Some(&node) = self.memo.get(&(i, k)) { return Some(node); }
+
+        let (a, x) = self.terms[i];
+        let hi = match self.build(i + 1, k.saturating_sub(a)) { Some(node) => { node } None => { return None; } };
+        let lo = match self.build(i + 1, k) { Some(node) => { node } None => { return None; } };
+
+        let node =
+            if hi == lo {
+                hi
+            } else {
+                if self.nodes.len() >= self.limit { return None; }
+                self.nodes.push((x, hi, lo));
+                Node::Inner(self.nodes.len() - 1)
+            };
+
+        self.memo.insert((i, k), node);
+        Some(node)
+    }
+}
+
+
+// The terms are sorted in unary by a cardinality network, with every literal repeated as many times
+// as its coefficient. At least 'bound' of them are true iff at most 'total - bound' are false:
+fn sorter<S : Solver>(solver : &mut S, terms : &[(u64, Lit)], bound : u64, total : u64) -> Root {
+    let mut negated = Vec::with_capacity(total as usize);
+    for &(a, lit) in terms.iter() {
+        for _ in 0 .. a {
+            negated.push(!lit);
+        }
+    }
+
+    let k = (total - bound) as usize;
+    match network::outputs(solver, &negated, k) {
+        Some(outputs) => { Root::Lit(!outputs[k]) }
+        None          => { Root::False }
+    }
+}
+
+
+// Binary sum by a tree of full and half adders, compared with the bound bit by bit. The adders are
+// encoded in both directions, since the comparison needs the exact sum.
+fn adder<S : Solver>(solver : &mut S, terms : &[(u64, Lit)], bound : u64) -> Root {
+    let mut buckets : Vec<Vec<Lit>> = Vec::new();
+    for &(a, lit) in terms.iter() {
+        for bit in 0 .. 64 - a.leading_zeros() as usize {
+            if isSet(a, bit) {
+                while buckets.len() <= bit { buckets.push(Vec::new()); }
+                buckets[bit].push(lit);
+            }
+        }
+    }
+
+    let mut sum : Vec<Option<Lit>> = Vec::new();
+    let mut bit = 0;
+    while bit < buckets.len() {
+        let mut i = 0;
+        while buckets[bit].len() - i >= 2 {
+            let carry =
+                if buckets[bit].len() - i >= 3 {
+                    let (a, b, c) = (buckets[bit][i], buckets[bit][i + 1], buckets[bit][i + 2]);
+                    i += 3;
+                    let (s, carry) = fullAdder(solver, a, b, c);
+                    buckets[bit].push(s);
+                    carry
+                } else {
+                    let (a, b) = (buckets[bit][i], buckets[bit][i + 1]);
+                    i += 2;
+                    let (s, carry) = halfAdder(solver, a, b);
+                    buckets[bit].push(s);
+                    carry
+                };
+
+            if buckets.len() <= bit + 1 { buckets.push(Vec::new()); }
+            buckets[bit + 1].push(carry);
+        }
+
+        sum.push(buckets[bit].get(i).cloned());
+        bit += 1;
+    }
+
+    // 'sum >= bound' iff for every 1 bit of the bound, the sum has a 1 bit there or at a higher
+    // position where the bound has a 0:
+    let root = solver.newVar(None, true).posLit();
+    for i in 0 .. sum.len() {
+        if !isSet(bound, i) { continue; }
+
+        let mut clause = vec![!root];
+        for j in i .. sum.len() {
+            if j == i || !isSet(bound, j) {
+                if let Some(s) = sum[j] { clause.push(s); }
+            }
+        }
+        solver.addClause(&clause);
+    }
+    Root::Lit(root)
+}
+
+
+fn isSet(x : u64, bit : usize) -> bool {
+    bit < 64 && (x >> bit) & 1 == 1
+}
+
+
+fn halfAdder<S : Solver>(solver : &mut S, a : Lit, b : Lit) -> (Lit, Lit) {
+    let s = solver.newVar(None, true).posLit();
+    let c = solver.newVar(None, true).posLit();
+    solver.addClause(&[!a, !b, !s]);
+    solver.addClause(&[a, b, !s]);
+    solver.addClause(&[!a, b, s]);
+    solver.addClause(&[a, !b, s]);
+
+    solver.addClause(&[!a, !b, c]);
+    solver.addClause(&[a, !c]);
+    solver.addClause(&[b, !c]);
+    (s, c)
+}
+
+
+fn fullAdder<S : Solver>(solver : &mut S, a : Lit, b : Lit, c : Lit) -> (Lit, Lit) {
+    let s = solver.newVar(None, true).posLit();
+    let carry = solver.newVar(None, true).posLit();
+    for mask in 0 .. 8 {
+        // One clause per assignment of the inputs, fixing the parity:
+        let lits = [a, b, c];
+        let mut clause : Vec<Lit> = (0 .. 3).map(|i| if mask & (1 << i) != 0 { !lits[i] } else { lits[i] }).collect();
+        clause.push(if (mask as u32).count_ones() % 2 == 1 { s } else { !s });
+        solver.addClause(&clause);
+    }
+
+    solver.addClause(&[!a, !b, carry]);
+    solver.addClause(&[!a, !c, carry]);
+    solver.addClause(&[!b, !c, carry]);
+    solver.addClause(&[a, b, !carry]);
+    solver.addClause(&[a, c, !carry]);
+    solver.addClause(&[b, c, !carry]);
+    (s, carry)
+}
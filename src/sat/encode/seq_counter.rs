@@ -0,0 +1,34 @@
+use sat::Solver;
+use sat::formula::Lit;
+
+
+// Sinz's sequential counter. Register 's[i][j]' is implied by at least 'j + 1' true literals among
+// the first 'i + 1'. With 'k = 1' this is the ladder encoding of at-most-one.
+pub fn atMost<S : Solver>(solver : &mut S, lits : &[Lit], k : usize) -> bool {
+    let n = lits.len();
+    if k >= n { return true; }
+
+    let mut ok = true;
+    let mut prev : Vec<Lit> = Vec::new();
+    for (i, &x) in lits.iter().enumerate() {
+        if i + 1 == n {
+            // The last literal only has to respect the full register:
+            ok &= solver.addClause(&[!x, !prev[k - 1]]);
+            break;
+        }
+
+        let regs : Vec<Lit> = (0 .. k).map(|_| solver.newVar(None, true).posLit()).collect();
+        ok &= solver.addClause(&[!x, regs[0]]);
+        if i > 0 {
+            for j in 0 .. k {
+                ok &= solver.addClause(&[!prev[j], regs[j]]);
+                if j > 0 {
+                    ok &= solver.addClause(&[!x, !prev[j - 1], regs[j]]);
+                }
+            }
+            ok &= solver.addClause(&[!x, !prev[k - 1]]);
+        }
+        prev = regs;
+    }
+    ok
+}
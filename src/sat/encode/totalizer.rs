@@ -0,0 +1,128 @@
+use std::cmp;
+use std::mem;
+use sat::Solver;
+use sat::formula::Lit;
+
+
+// Totalizer of Bailleux and Boufkhad over a set of input literals: output 'k' is implied by at
+// least 'k' true inputs. Outputs are only created up to the current bound, which can be raised
+// later, and more inputs can be added, so a bound can be tightened or relaxed between solver calls
+// by assuming 'atMost()' literals.
+pub struct Totalizer {
+    root  : Node,
+    bound : usize
+}
+
+enum Node {
+    Leaf(Lit),
+    Sum(Box<Node>, Box<Node>, usize, Vec<Lit>) // Children, number of inputs below, outputs.
+}
+
+impl Totalizer {
+    pub fn new<S : Solver>(solver : &mut S, lits : &[Lit], bound : usize) -> Totalizer {
+        assert!(!lits.is_empty());
+        let mut tot = Totalizer { root : Node::build(lits), bound : bound };
+        tot.root.extend(solver, bound);
+        tot
+    }
+
+    pub fn size(&self) -> usize {
+        self.root.size()
+    }
+
+    pub fn bound(&self) -> usize {
+        self.bound
+    }
+
+    // Output 'k', counting from one, for 'k' up to the current bound:
+    pub fn output(&self, k : usize) -> Lit {
+        assert!(1 <= k && k <= cmp::min(self.bound, self.size()));
+        self.root.output(k)
+    }
+
+    pub fn extend<S : Solver>(&mut self, solver : &mut S, bound : usize) {
+        if bound > self.bound {
+            self.bound = bound;
+            self.root.extend(solver, bound);
+        }
+    }
+
+    // Literals returned by 'atMost()' before keep counting the old inputs only:
+    pub fn addInputs<S : Solver>(&mut self, solver : &mut S, lits : &[Lit]) {
+        if lits.is_empty() { return; }
+
+        let new = Node::build(lits);
+        let old = mem::replace(&mut self.root, Node::Leaf(lits[0]));
+        let size = old.size() + new.size();
+        self.root = Node::Sum(Box::new(old), Box::new(new), size, Vec::new());
+        self.root.extend(solver, self.bound);
+    }
+
+    // Literal that allows at most 'k' true inputs when assumed or added as a unit. Extends the
+    // totalizer as needed. There is none if the bound can not be exceeded anyway:
+    pub fn atMost<S : Solver>(&mut self, solver : &mut S, k : usize) -> Option<Lit> {
+        if k >= self.size() { return None; }
+        self.extend(solver, k + 1);
+        Some(!self.root.output(k + 1))
+    }
+}
+
+impl Node {
+    fn build(lits : &[Lit]) -> Node {
+        if lits.len() == 1 {
+            Node::Leaf(lits[0])
+        } else {
+            let (left, right) = lits.split_at(lits.len() / 2);
+            Node::Sum(Box::new(Node::build(left)), Box::new(Node::build(right)), lits.len(), Vec::new())
+        }
+    }
+
+    fn size(&self) -> usize {
+        match *self {
+            Node::Leaf(_)             => { 1 }
+            Node::Sum(_, _, size, _)  => { size }
+        }
+    }
+
+    fn outputs(&self) -> usize {
+        match *self {
+            Node::Leaf(_)                   => { 1 }
+            Node::Sum(_, _, _, ref outputs) => { outputs.len() }
+        }
+    }
+
+    fn output(&self, k : usize) -> Lit {
+        match *self {
+            Node::Leaf(lit)                 => { lit }
+            Node::Sum(_, _, _, ref outputs) => { outputs[k - 1] }
+        }
+    }
+
+    fn extend<S : Solver>(&mut self, solver : &mut S, bound : usize) {
+        if let Node::Sum(ref mut left, ref mut right, size, ref mut outputs) = *self {
+            let bound = cmp::min(bound, size);
+            if outputs.len() >= bound { return; }
+
+            left.extend(solver, bound);
+            right.extend(solver, bound);
+
+            let old = outputs.len();
+            while outputs.len() < bound {
+                outputs.push(solver.newVar(None, true).posLit());
+            }
+
+            // Output 'k' for every split of 'k' true inputs between the children:
+            for k in old + 1 .. bound + 1 {
+                for a in 0 .. cmp::min(k, left.outputs()) + 1 {
+                    let b = k - a;
+                    if b > right.outputs() { continue; }
+
+                    let mut clause = vec![outputs[k - 1]];
+                    if a > 0 { clause.push(!left.output(a)); }
+                    if b > 0 { clause.push(!right.output(b)); }
+                    solver.addClause(&clause);
+                }
+            }
+        }
+    }
+}
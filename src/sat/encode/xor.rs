@@ -0,0 +1,44 @@
+use std::ops;
+use sat::Solver;
+use sat::formula::Lit;
+
+
+const XOR_CUT : usize = 5;
+
+
+pub fn chain<S : Solver>(solver : &mut S, lits : &[Lit]) -> bool {
+    let clauses = clauses(lits, || solver.newVar(None, true).posLit());
+    let mut ok = true;
+    for c in clauses.iter() {
+        ok &= solver.addClause(c);
+    }
+    ok
+}
+
+
+// The clauses of 'chain' over any kind of literal, 'fresh' making the chaining ones:
+pub fn clauses<L, F>(lits : &[L], mut fresh : F) -> Vec<Vec<L>>
+    where L : Copy + ops::Not<Output = L>, F : FnMut() -> L {
+    let mut clauses = Vec::new();
+    let mut rest = lits.to_vec();
+    while rest.len() > XOR_CUT {
+        // 't' is the parity of the first literals, so 't = l1 ^ ... ^ lk' is 'l1 ^ ... ^ lk ^ ¬t':
+        let t = fresh();
+        let mut piece : Vec<L> = rest.drain(.. XOR_CUT - 1).collect();
+        piece.push(!t);
+        direct(&piece, &mut clauses);
+        rest.insert(0, t);
+    }
+
+    direct(&rest, &mut clauses);
+    clauses
+}
+
+
+// One clause for each assignment with an even number of true literals, excluding it:
+fn direct<L : Copy + ops::Not<Output = L>>(lits : &[L], clauses : &mut Vec<Vec<L>>) {
+    for mask in 0u32 .. 1 << lits.len() {
+        if mask.count_ones() % 2 == 1 { continue; }
+        clauses.push((0 .. lits.len()).map(|i| if mask & (1 << i) != 0 { !lits[i] } else { lits[i] }).collect());
+    }
+}
@@ -102,6 +102,9 @@ impl Assignment {
     }
 
     pub fn freeVar(&mut self, Var(v) : Var) {
+        let ref mut line = self.assignment[v];
+        line.assign = [LitVal::Undef, LitVal::Undef];
+        line.vd.reason = None;
         self.free_vars.push(v);
     }
 
@@ -133,6 +136,14 @@ impl Assignment {
         self.trail.push(Lit(p));
     }
 
+    // Explanation of a literal that was implied without one at the time:
+    #[inline]
+    pub fn setReason(&mut self, Lit(p) : Lit, reason : clause::ClauseRef) {
+        let ref mut line = self.assignment[p >> 1];
+        assert!(line.vd.reason.is_none());
+        line.vd.reason = Some(reason);
+    }
+
     #[inline]
     pub fn rewindUntilLevel<F : FnMut(DecisionLevel, Lit) -> ()>(&mut self, DecisionLevel(target_level) : DecisionLevel, mut f : F) {
         while self.lim.len() > target_level {
@@ -191,6 +202,11 @@ impl Assignment {
         self.trail[index]
     }
 
+    #[inline]
+    pub fn groundAssigns(&self) -> &[Lit] {
+        &self.trail[.. self.numberOfGroundAssigns()]
+    }
+
 
     #[inline]
     pub fn isUndef(&self, Var(v) : Var) -> bool {
@@ -212,7 +212,7 @@ impl<'c> Iterator for ClauseIter<'c> {
 }
 
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub struct ClauseRef(usize);
 
 
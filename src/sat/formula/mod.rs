@@ -11,6 +11,11 @@ pub mod util;
 pub struct Var(usize);
 
 impl Var {
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.0
+    }
+
     #[inline]
     pub fn lit(&self, sign : bool) -> Lit {
         Lit((self.0 << 1) | (sign as usize))
@@ -0,0 +1,201 @@
+use std::default::Default;
+use sat::{PartialResult, Solver};
+use sat::encode::Totalizer;
+use sat::formula::{Var, Lit, VarMap, LitMap};
+use sat::minisat::{self, CoreSolver, InterruptHandle};
+
+
+pub struct Settings {
+    pub core     : minisat::Settings,
+    pub stratify : bool, // Solve for heavier soft clauses first.
+    pub harden   : bool  // Turn soft clauses into hard ones once violating them cannot improve the best solution.
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings { core     : Default::default()
+                 , stratify : true
+                 , harden   : true
+                 }
+    }
+}
+
+
+pub enum MaxSatResult {
+    UnSAT,                                      // Hard clauses alone are unsatisfiable.
+    Optimum(u64, VarMap<bool>),
+    Interrupted(Option<(u64, VarMap<bool>)>)    // Best solution found so far, if any.
+}
+
+
+// Weighted partial MaxSAT by the OLL algorithm. Every soft clause is represented by an assumption
+// literal that implies it. A final conflict over the assumptions is a core, i.e. a set of soft
+// clauses some of which must be violated. The cheapest of them pays for the core: its weight is
+// added to the lower bound and subtracted from every member, and the core is relaxed by a totalizer
+// that counts the violated members. The next, less restrictive bound on that counter becomes a new
+// assumption. A model under all of the remaining assumptions is optimal.
+pub struct MaxSatSolver {
+    solver      : CoreSolver,
+    softs       : Vec<(Box<[Lit]>, u64)>,
+    weights     : LitMap<u64>,            // Weights of current assumptions.
+    sums        : LitMap<(usize, usize)>, // Assumption that at most 'k' members of a core are violated -> (totalizer, k).
+    totalizers  : Vec<Totalizer>,
+    lower_bound : u64,
+    stratify    : bool,
+    harden      : bool
+}
+
+impl MaxSatSolver {
+    pub fn new(settings : Settings) -> MaxSatSolver {
+        MaxSatSolver { solver      : CoreSolver::new(settings.core)
+                     , softs       : Vec::new()
+                     , weights     : LitMap::new()
+                     , sums        : LitMap::new()
+                     , totalizers  : Vec::new()
+                     , lower_bound : 0
+                     , stratify    : settings.stratify
+                     , harden      : settings.harden
+                     }
+    }
+
+    pub fn newVar(&mut self) -> Var {
+        self.solver.newVar(None, true)
+    }
+
+    pub fn addHard(&mut self, clause : &[Lit]) {
+        self.solver.addClause(clause);
+    }
+
+    pub fn addSoft(&mut self, clause : &[Lit], weight : u64) {
+        self.softs.push((clause.to_vec().into_boxed_slice(), weight));
+        if weight == 0 { return; }
+
+        let lit =
+            if clause.len() == 1 {
+                clause[0]
+            } else {
+                let sel = self.solver.newVar(None, true);
+                let mut ps = clause.to_vec();
+                ps.push(sel.negLit());
+                self.solver.addClause(&ps);
+                sel.posLit()
+            };
+
+        self.addWeight(lit, weight);
+    }
+
+    pub fn interruptHandle(&self) -> InterruptHandle {
+        self.solver.interruptHandle()
+    }
+
+    // Total weight of soft clauses violated by the model:
+    pub fn cost(&self, model : &VarMap<bool>) -> u64 {
+        let isTrue = |lit : &Lit| { model.get(&lit.var()).map(|&val| val != lit.sign()).unwrap_or(false) };
+        self.softs.iter().filter(|&&(ref c, _)| !c.iter().any(&isTrue)).map(|&(_, w)| w).sum()
+    }
+
+    // Every improving solution is passed to 'improved' as soon as it is found:
+    pub fn solve<F : FnMut(u64, &VarMap<bool>) -> ()>(&mut self, mut improved : F) -> MaxSatResult {
+        let mut best : Option<(u64, VarMap<bool>)> = None;
+        let mut level = if self.stratify { self.nextLevel(u64::max_value()) } else { 0 };
+
+        loop {
+            let assumptions : Vec<Lit> = self.weights.iter().filter(|&(_, &w)| w >= level).map(|(lit, _)| lit).collect();
+
+            match self.solver.solveLimited(&assumptions) {
+                PartialResult::SAT(model)       => {
+                    let cost = self.cost(&model);
+                    if best.as_ref().map(|&(c, _)| cost < c).unwrap_or(true) {
+                        improved(cost, &model);
+                        best = Some((cost, model));
+                    }
+
+                    let ub = best.as_ref().unwrap().0;
+                    if ub == self.lower_bound { break; }
+                    if self.harden && !self.hardenSofts(ub) { break; }
+
+                    // All of the assumptions hold in the model, so it is optimal:
+                    if !self.weights.iter().any(|(_, &w)| w < level) { break; }
+                    level = self.nextLevel(level);
+                }
+
+                PartialResult::UnSATUnder(core) => {
+                    self.relaxCore(&core);
+                    info!("| Lower bound: {:12}   core size: {:8}                               |", self.lower_bound, core.len());
+                    if best.as_ref().map(|&(c, _)| c == self.lower_bound).unwrap_or(false) { break; }
+                }
+
+                PartialResult::UnSAT            => {
+                    // Only hardened soft clauses can make the formula unsatisfiable after a model was
+                    // found, and that means the best solution can not be improved:
+                    if best.is_none() { return MaxSatResult::UnSAT; }
+                    break;
+                }
+
+                PartialResult::Interrupted(_)   => { return MaxSatResult::Interrupted(best); }
+            }
+        }
+
+        match best {
+            Some((cost, model)) => { MaxSatResult::Optimum(cost, model) }
+            None                => { MaxSatResult::UnSAT }
+        }
+    }
+
+    // Weight threshold of the next stratification level: the heaviest assumption below 'current':
+    fn nextLevel(&self, current : u64) -> u64 {
+        self.weights.iter().map(|(_, &w)| w).filter(|&w| w < current).max().unwrap_or(0)
+    }
+
+    fn addWeight(&mut self, lit : Lit, weight : u64) {
+        let w = self.weights.get(&lit).cloned().unwrap_or(0) + weight;
+        self.weights.insert(&lit, w);
+    }
+
+    fn relaxCore(&mut self, core : &[Lit]) {
+        let min = core.iter().map(|lit| self.weights[lit]).min().unwrap_or(0);
+        self.lower_bound += min;
+
+        for lit in core.iter() {
+            let w = self.weights[lit] - min;
+            if w == 0 {
+                self.weights.remove(lit);
+            } else {
+                self.weights.insert(lit, w);
+            }
+
+            // A totalizer bound in the core is relaxed further, at the price of the core:
+            if let Some(&(t, k)) = self.sums.get(lit) {
+                if let Some(next) = self.totalizers[t].atMost(&mut self.solver, k + 1) {
+                    self.sums.insert(&next, (t, k + 1));
+                    self.addWeight(next, min);
+                }
+            }
+        }
+
+        // At least one member of the core is violated, at most one is assumed from now on:
+        if core.len() > 1 {
+            let inputs : Vec<Lit> = core.iter().map(|&lit| !lit).collect();
+            let mut tot = Totalizer::new(&mut self.solver, &inputs, 2);
+            if let Some(lit) = tot.atMost(&mut self.solver, 1) {
+                self.sums.insert(&lit, (self.totalizers.len(), 1));
+                self.addWeight(lit, min);
+            }
+            self.totalizers.push(tot);
+        }
+    }
+
+    // Assumptions too heavy to be violated by any solution better than 'ub' become units:
+    fn hardenSofts(&mut self, ub : u64) -> bool {
+        let slack = ub - self.lower_bound;
+        let hard : Vec<Lit> = self.weights.iter().filter(|&(_, &w)| w > slack).map(|(lit, _)| lit).collect();
+        for lit in hard.iter() {
+            self.weights.remove(lit);
+            if !self.solver.addClause(&[*lit]) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
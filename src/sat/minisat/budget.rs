@@ -1,25 +1,77 @@
-use std::sync::atomic;
+use std::sync::{atomic, Arc};
+use sat::TerminateCallback;
+
+
+// Cloneable handle to interrupt the solver asynchronously (e.g. from another thread):
+#[derive(Clone)]
+pub struct InterruptHandle {
+    flag : Arc<atomic::AtomicBool>
+}
+
+impl Default for InterruptHandle {
+    fn default() -> InterruptHandle {
+        InterruptHandle::new()
+    }
+}
+
+impl InterruptHandle {
+    pub fn new() -> InterruptHandle {
+        InterruptHandle { flag : Arc::new(atomic::AtomicBool::new(false)) }
+    }
+
+    pub fn interrupt(&self) {
+        self.flag.store(true, atomic::Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        self.flag.store(false, atomic::Ordering::Relaxed);
+    }
+
+    pub fn isInterrupted(&self) -> bool {
+        self.flag.load(atomic::Ordering::Relaxed)
+    }
+}
 
 
 // Resource contraints:
 pub struct Budget {
     conflict_budget    : i64, // -1 means no budget.
     propagation_budget : i64, // -1 means no budget.
-    asynch_interrupt   : atomic::AtomicBool
+    asynch_interrupt   : Arc<atomic::AtomicBool>,
+    pub terminate      : Option<TerminateCallback>
 }
 
 impl Budget {
     pub fn new() -> Budget {
         Budget { conflict_budget    : -1
                , propagation_budget : -1
-               , asynch_interrupt   : atomic::AtomicBool::new(false)
+               , asynch_interrupt   : Arc::new(atomic::AtomicBool::new(false))
+               , terminate          : None
                }
     }
 
-    pub fn within(&self, conflicts : u64, propagations : u64) -> bool {
+    pub fn handle(&self) -> InterruptHandle {
+        InterruptHandle { flag : self.asynch_interrupt.clone() }
+    }
+
+    // Interrupted together with everything else that shares the handle:
+    pub fn share(&mut self, handle : InterruptHandle) {
+        self.asynch_interrupt = handle.flag;
+    }
+
+    pub fn setConflictBudget(&mut self, conflicts : u64, x : u64) {
+        self.conflict_budget = (conflicts + x) as i64;
+    }
+
+    pub fn setPropagationBudget(&mut self, propagations : u64, x : u64) {
+        self.propagation_budget = (propagations + x) as i64;
+    }
+
+    pub fn within(&mut self, conflicts : u64, propagations : u64) -> bool {
         !self.asynch_interrupt.load(atomic::Ordering::Relaxed) &&
             (self.conflict_budget    < 0 || conflicts < self.conflict_budget as u64) &&
-            (self.propagation_budget < 0 || propagations < self.propagation_budget as u64)
+            (self.propagation_budget < 0 || propagations < self.propagation_budget as u64) &&
+            !self.terminate.as_mut().map(|terminate| terminate()).unwrap_or(false)
     }
 
     pub fn interrupted(&self) -> bool {
@@ -1,11 +1,14 @@
 use std::cmp::Ordering;
+use sat::LearnCallback;
 use sat::formula::Lit;
 use sat::formula::assignment::Assignment;
 use sat::formula::clause::*;
 use sat::formula::util::*;
+use super::proof::Proof;
 use super::watches::Watches;
 
 
+#[derive(Clone)]
 pub struct ClauseDBSettings {
     pub remove_satisfied : bool, // Indicates whether possibly inefficient linear scan for satisfied clauses should be performed in 'simplify'.
     pub clause_decay     : f64
@@ -30,6 +33,9 @@ pub struct ClauseDB {
     pub num_learnts      : usize,
     pub clauses_literals : u64,
     pub learnts_literals : u64,
+    pub collections      : u64,              // Number of garbage collections performed so far.
+    pub proof            : Option<Proof>,
+    pub export           : Option<(usize, LearnCallback)> // Callback for learnt clauses up to the given length.
 }
 
 impl ClauseDB {
@@ -43,9 +49,18 @@ impl ClauseDB {
                  , num_learnts      : 0
                  , clauses_literals : 0
                  , learnts_literals : 0
+                 , collections      : 0
+                 , proof            : None
+                 , export           : None
                  }
     }
 
+    // Iterate over problem clauses that are still alive:
+    pub fn clauses<'a>(&'a self) -> Box<Iterator<Item = ClauseRef> + 'a> {
+        let ca = &self.ca;
+        Box::new(self.clauses.iter().map(|&cr| cr).filter(move |&cr| !ca.isDeleted(cr)))
+    }
+
     pub fn addClause(&mut self, ps : Box<[Lit]>) -> (&Clause, ClauseRef) {
         self.num_clauses += 1;
         self.clauses_literals += ps.len() as u64;
@@ -55,7 +70,22 @@ impl ClauseDB {
         (c, cr)
     }
 
-    pub fn learnClause(&mut self, ps : Box<[Lit]>) -> (&Clause, ClauseRef) {
+    // The LBD is taken from the assignment, where unassigned literals count as one more level:
+    pub fn learnClause(&mut self, assigns : &Assignment, ps : Box<[Lit]>) -> (&Clause, ClauseRef) {
+        self.proofAdd(&ps);
+        if self.export.is_some() {
+            let mut levels : Vec<usize> = ps.iter().map(|lit| {
+                if assigns.isUndef(lit.var()) { usize::max_value() } else { assigns.vardata(lit.var()).level.offset() }
+            }).collect();
+            levels.sort();
+            levels.dedup();
+            self.exportLearnt(&ps, levels.len());
+        }
+        self.importClause(ps)
+    }
+
+    // Learnt clause that came from outside, so it is not exported again:
+    pub fn importClause(&mut self, ps : Box<[Lit]>) -> (&Clause, ClauseRef) {
         self.num_learnts += 1;
         self.learnts_literals += ps.len() as u64;
 
@@ -65,6 +95,14 @@ impl ClauseDB {
         (self.ca.view(cr), cr)
     }
 
+    pub fn exportLearnt(&mut self, ps : &[Lit], lbd : usize) {
+        if let Some((max_len, ref mut callback)) = self.export {
+            if ps.len() <= max_len {
+                callback(ps, lbd);
+            }
+        }
+    }
+
     pub fn removeClause(&mut self, assigns : &mut Assignment, cr : ClauseRef) {
         {
             let c = self.ca.view(cr);
@@ -77,6 +115,10 @@ impl ClauseDB {
             }
         }
 
+        if let Some(ref mut proof) = self.proof {
+            proof.delete(self.ca.view(cr).iter());
+        }
+
         // TODO: do we really need this?
         assigns.forgetReason(&self.ca, cr);
 
@@ -90,6 +132,18 @@ impl ClauseDB {
         if c.is_learnt() { self.learnts_literals += c.len() as u64; } else { self.clauses_literals += c.len() as u64; }
     }
 
+    pub fn proofAdd(&mut self, lits : &[Lit]) {
+        if let Some(ref mut proof) = self.proof {
+            proof.add(lits.iter().cloned());
+        }
+    }
+
+    pub fn proofDelete(&mut self, lits : &[Lit]) {
+        if let Some(ref mut proof) = self.proof {
+            proof.delete(lits.iter().cloned());
+        }
+    }
+
     pub fn bumpActivity(&mut self, cr : ClauseRef) {
         let new = {
             let c = self.ca.edit(cr);
@@ -171,7 +225,18 @@ impl ClauseDB {
         } else {
             let c = self.ca.edit(cr);
             assert!({ let (c0, c1) = c.headPair(); assigns.isUndef(c0.var()) && assigns.isUndef(c1.var()) });
-            c.retainSuffix(2, |&lit| !assigns.isUnsat(lit));
+            match self.proof {
+                Some(ref mut proof) => {
+                    let old : Vec<Lit> = c.iter().collect();
+                    c.retainSuffix(2, |&lit| !assigns.isUnsat(lit));
+                    if c.len() < old.len() {
+                        proof.add(c.iter());
+                        proof.delete(old);
+                    }
+                }
+
+                None                => { c.retainSuffix(2, |&lit| !assigns.isUnsat(lit)); }
+            }
             true
         }
     }
@@ -231,5 +296,6 @@ impl ClauseDB {
 
         debug!("|  Garbage collection:   {:12} bytes => {:12} bytes             |", self.ca.size(), to.size());
         self.ca = to;
+        self.collections += 1;
     }
 }
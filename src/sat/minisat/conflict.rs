@@ -5,7 +5,7 @@ use sat::minisat::clause_db::*;
 use sat::minisat::decision_heuristic::*;
 
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone, Copy)]
 pub enum CCMinMode {
     None,
     Basic,
@@ -230,6 +230,7 @@ impl AnalyzeContext {
         let mut out_conflict = LitMap::new();
         out_conflict.insert(&p, ());
 
+        self.seen[&p.var()] = Seen::Source;
         assigns.inspectUntilLevel(GroundLevel, |lit| {
             let x = lit.var();
             if self.seen[&x] != Seen::Undef {
@@ -248,8 +249,10 @@ impl AnalyzeContext {
                         }
                     }
                 }
+                self.seen[&x] = Seen::Undef;
             }
         });
+        self.seen[&p.var()] = Seen::Undef;
 
         out_conflict
     }
@@ -0,0 +1,220 @@
+use std::{panic, thread};
+use std::sync::{mpsc, Arc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use sat::{PartialResult, TotalResult};
+use sat::formula::{Var, Lit, VarMap};
+use sat::formula::assignment::GroundLevel;
+use super::{CoreSolver, InterruptHandle};
+
+
+#[derive(Clone)]
+pub struct CubeSettings {
+    pub depth      : usize, // Cubes have at most this many decisions.
+    pub candidates : usize  // Variables looked ahead on at every split, the ones occurring most often.
+}
+
+impl Default for CubeSettings {
+    fn default() -> CubeSettings {
+        CubeSettings { depth      : 10
+                     , candidates : 100
+                     }
+    }
+}
+
+
+#[derive(Default)]
+pub struct CubeStats {
+    pub probes  : usize, // Trial propagations.
+    pub failed  : usize, // Literals found to fail on a trial level.
+    pub refuted : usize  // Branches closed by propagation alone, they produce no cube.
+}
+
+
+// Splits the problem into cubes by lookahead. At every node both polarities of the candidate
+// variables are propagated on a trial decision level, and the variable that propagates the most
+// in both directions is branched on. A failing literal is replaced by its negation on the spot.
+// Every model of the problem extends one of the cubes, so no cubes at all means UNSAT:
+pub fn cube(solver : &mut CoreSolver, settings : &CubeSettings, stats : &mut CubeStats) -> Vec<Vec<Lit>> {
+    let mut cubes = Vec::new();
+    if !solver.simplify() {
+        return cubes;
+    }
+
+    let candidates = {
+        let mut occurs : VarMap<usize> = VarMap::new();
+        for cr in solver.db.clauses() {
+            for lit in solver.db.ca.view(cr).iter() {
+                let n = occurs.get(&lit.var()).cloned().unwrap_or(0);
+                occurs.insert(&lit.var(), n + 1);
+            }
+        }
+
+        let mut vars : Vec<(usize, Var)> = occurs.iter().map(|(v, &n)| (n, v)).collect();
+        vars.sort_by(|a, b| b.cmp(a));
+        vars.into_iter().take(settings.candidates).map(|(_, v)| v).collect::<Vec<Var>>()
+    };
+
+    let mut prefix = Vec::new();
+    let mut cuber = Cuber { solver     : solver
+                          , candidates : candidates
+                          , stats      : stats
+                          };
+    cuber.split(settings.depth, &mut prefix, &mut cubes);
+    cuber.solver.cancelUntil(GroundLevel);
+    if cubes.is_empty() {
+        cuber.solver.setUnsat();
+    }
+    cubes
+}
+
+
+struct Cuber<'s> {
+    solver     : &'s mut CoreSolver,
+    candidates : Vec<Var>,
+    stats      : &'s mut CubeStats
+}
+
+impl<'s> Cuber<'s> {
+    fn split(&mut self, depth : usize, prefix : &mut Vec<Lit>, cubes : &mut Vec<Vec<Lit>>) {
+        let best =
+            match self.lookahead() {
+                None                         => { self.stats.refuted += 1; return; }
+                Some(Some(lit)) if depth > 0 => { lit }
+                Some(_)                      => { cubes.push(prefix.clone()); return; }
+            };
+
+        let level = self.solver.assigns.decisionLevel();
+        for &lit in [best, !best].iter() {
+            if self.probe(lit).is_some() {
+                prefix.push(lit);
+                self.split(depth - 1, prefix, cubes);
+                prefix.pop();
+            } else {
+                self.stats.refuted += 1;
+            }
+            self.solver.cancelUntil(level);
+        }
+    }
+
+    // The literal to branch on first, none if every candidate is assigned. Fails if propagation
+    // alone refutes the current node:
+    fn lookahead(&mut self) -> Option<Option<Lit>> {
+        'restart: loop {
+            let mut best = None;
+            let mut best_score = 0;
+            for i in 0 .. self.candidates.len() {
+                let v = self.candidates[i];
+                if !self.solver.assigns.isUndef(v) { continue; }
+
+                match (self.trial(v.posLit()), self.trial(v.negLit())) {
+                    (None, None)       => { return None; }
+                    (None, Some(_))    => { if self.imply(v.negLit()) { continue 'restart; } else { return None; } }
+                    (Some(_), None)    => { if self.imply(v.posLit()) { continue 'restart; } else { return None; } }
+                    (Some(p), Some(n)) => {
+                        let score = (p + 1) * (n + 1);
+                        if best.is_none() || score > best_score {
+                            best = Some(if p >= n { v.posLit() } else { v.negLit() });
+                            best_score = score;
+                        }
+                    }
+                }
+            }
+            return Some(best);
+        }
+    }
+
+    // Number of literals propagated by 'lit' on a trial level, none on a conflict:
+    fn trial(&mut self, lit : Lit) -> Option<usize> {
+        let level = self.solver.assigns.decisionLevel();
+        let before = self.solver.assigns.numberOfAssigns();
+        let propagated = self.probe(lit).map(|after| after - before);
+        self.solver.cancelUntil(level);
+        propagated
+    }
+
+    // Decide 'lit' on a new level and propagate, the number of assignments if there is no conflict:
+    fn probe(&mut self, lit : Lit) -> Option<usize> {
+        self.stats.probes += 1;
+        self.solver.assigns.newDecisionLevel();
+        self.solver.assigns.assignLit(lit, None);
+        match self.solver.watches.propagate(&mut self.solver.db.ca, &mut self.solver.assigns) {
+            None    => { Some(self.solver.assigns.numberOfAssigns()) }
+            Some(_) => { None }
+        }
+    }
+
+    // The negation of a failed literal holds for the rest of the current node:
+    fn imply(&mut self, lit : Lit) -> bool {
+        self.stats.failed += 1;
+        self.solver.assigns.assignLit(lit, None);
+        self.solver.watches.propagate(&mut self.solver.db.ca, &mut self.solver.assigns).is_none()
+    }
+}
+
+
+enum Outcome {
+    Model(VarMap<bool>),
+    Refuted,     // The problem itself is UNSAT.
+    Exhausted,   // Every cube taken by the thread was refuted.
+    Interrupted
+}
+
+
+// Solves the cubes as assumptions on 'threads' threads. Every thread gets a solver of its own from
+// 'build', which must number variables the same way as the solver the cubes came from, and keeps
+// it for all the cubes it takes, so that what is learnt on one cube helps with the next. The first
+// model found ends the search, the problem is UNSAT once every cube has been refuted:
+pub fn conquer<F>(build : F, cubes : Vec<Vec<Lit>>, threads : usize) -> TotalResult
+    where F : Fn() -> CoreSolver + Send + Sync + 'static {
+    let threads = threads.max(1);
+    let build = Arc::new(build);
+    let cubes = Arc::new(cubes);
+    let next = Arc::new(AtomicUsize::new(0));
+    let stop = InterruptHandle::new();
+    let (tx, rx) = mpsc::channel();
+
+    let mut workers = Vec::with_capacity(threads);
+    for _ in 0 .. threads {
+        let (build, cubes, next, stop, tx) = (build.clone(), cubes.clone(), next.clone(), stop.clone(), tx.clone());
+        workers.push(thread::spawn(move || {
+            let mut solver = build();
+            solver.setInterruptHandle(stop);
+            let outcome =
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    if i >= cubes.len() { break Outcome::Exhausted; }
+
+                    match solver.solveLimited(&cubes[i]) {
+                        PartialResult::SAT(model)     => { break Outcome::Model(model); }
+                        PartialResult::UnSAT          => { break Outcome::Refuted; }
+                        PartialResult::UnSATUnder(_)  => {}
+                        PartialResult::Interrupted(_) => { break Outcome::Interrupted; }
+                    }
+                };
+            let _ = tx.send(outcome);
+        }));
+    }
+    drop(tx);
+
+    let mut result = TotalResult::Interrupted;
+    let mut exhausted = 0;
+    for outcome in rx.iter() {
+        match outcome {
+            Outcome::Model(model) => { if let TotalResult::Interrupted = result { result = TotalResult::SAT(model); stop.interrupt(); } }
+            Outcome::Refuted      => { if let TotalResult::Interrupted = result { result = TotalResult::UnSAT; stop.interrupt(); } }
+            Outcome::Exhausted    => { exhausted += 1; }
+            Outcome::Interrupted  => {}
+        }
+    }
+
+    for w in workers {
+        if let Err(err) = w.join() {
+            panic::resume_unwind(err);
+        }
+    }
+
+    if exhausted == threads {
+        result = TotalResult::UnSAT;
+    }
+    result
+}
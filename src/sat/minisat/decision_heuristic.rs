@@ -3,10 +3,11 @@ use sat::formula::assignment::Assignment;
 use super::util;
 
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone, Copy)]
 pub enum PhaseSaving { None, Limited, Full }
 
 
+#[derive(Clone)]
 pub struct DecisionHeuristicSettings {
     pub var_decay       : f64,
     pub random_seed     : f64,
@@ -65,6 +66,13 @@ impl DecisionHeuristic {
     pub fn initVar(&mut self, v : Var, upol : Option<bool>, dvar : bool) {
         self.activity.insert(&v, if self.settings.rnd_init_act { self.rand.drand() * 0.00001 } else { 0.0 });
         self.var.insert(&v, VarLine { polarity : true, user_pol : upol, decision : false });
+
+        // Recycled variable may still sit in the queue with its old activity:
+        {
+            let ref act = self.activity;
+            self.queue.update(&v, |a, b| { act[a] > act[b] });
+        }
+
         self.setDecisionVar(v, dvar);
     }
 
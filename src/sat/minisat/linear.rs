@@ -0,0 +1,179 @@
+use sat::formula::{Var, Lit, LitMap};
+use sat::formula::assignment::Assignment;
+use sat::formula::clause::*;
+
+
+// Constraint 'sum of coefficient * literal >= bound' with positive coefficients. The slack is the
+// sum of the coefficients minus the bound, less the coefficients of the literals that propagation
+// has seen false so far. The constraint is violated once the slack drops below zero, and forces
+// every unassigned literal whose coefficient is above it.
+struct Linear {
+    terms : Box<[(u64, Lit)]>, // Sorted by decreasing coefficient, at most one term per variable.
+    slack : i64
+}
+
+
+// Counter-based propagation of linear constraints. Propagations and conflicts are explained by
+// clauses over the false literals of the constraint, allocated next to the real clauses, so that
+// conflict analysis does not have to know where a reason came from. These clauses live as long as
+// they are locked by the assignment.
+pub struct LinearConstraints {
+    constrs : Vec<Linear>,
+    occurs  : LitMap<Vec<(usize, u64)>>, // 'occurs[p]' lists the constraints with a term on '!p', with its coefficient.
+    undo    : Vec<Lit>,                  // Propagated literals in trail order, their terms are subtracted from the slacks.
+    reasons : Vec<ClauseRef>             // Explanations in trail order, the last one may be a conflict.
+}
+
+impl LinearConstraints {
+    pub fn new() -> LinearConstraints {
+        LinearConstraints { constrs : Vec::new()
+                          , occurs  : LitMap::new()
+                          , undo    : Vec::new()
+                          , reasons : Vec::new()
+                          }
+    }
+
+    pub fn len(&self) -> usize {
+        self.constrs.len()
+    }
+
+    // Whether some constraint has a term on 'v':
+    pub fn occurs(&self, v : Var) -> bool {
+        [v.posLit(), v.negLit()].iter().any(|lit| self.occurs.get(lit).map(|occs| !occs.is_empty()).unwrap_or(false))
+    }
+
+    // Add a constraint at ground level. Literals that are already assigned are taken out, and the
+    // literals it forces right away are assigned without reason. Returns 'false' if the constraint
+    // can not be satisfied:
+    pub fn add(&mut self, assigns : &mut Assignment, terms : &[(u64, Lit)], bound : u64) -> bool {
+        assert!(assigns.isGroundLevel());
+
+        // Merge terms on the same variable, 'a * x + b * ¬x' is 'b + (a - b) * x' for 'a >= b':
+        let mut coefs : LitMap<u64> = LitMap::new();
+        let mut lits = Vec::new();
+        for &(a, lit) in terms.iter() {
+            if a == 0 { continue; }
+            let prev = match coefs.get(&lit) { Some(&b) => { b } None => { lits.push(lit); 0 } };
+            coefs.insert(&lit, prev + a);
+        }
+
+        let mut bound = bound as i64;
+        let mut merged = Vec::with_capacity(lits.len());
+        for &lit in lits.iter() {
+            let a = coefs[&lit];
+            let b = coefs.get(&!lit).cloned().unwrap_or(0);
+            if a > b {
+                bound -= b as i64;
+                merged.push((a - b, lit));
+            } else if a == b && lit.sign() {
+                bound -= b as i64;
+            }
+        }
+
+        let mut linear = Vec::with_capacity(merged.len());
+        for &(a, lit) in merged.iter() {
+            if assigns.isSat(lit) {
+                bound -= a as i64;
+            } else if !assigns.isUnsat(lit) {
+                linear.push((a, lit));
+            }
+        }
+
+        if bound <= 0 { return true; }
+
+        // Coefficients above the bound behave like the bound itself:
+        for term in linear.iter_mut() {
+            if term.0 as i64 > bound { term.0 = bound as u64; }
+        }
+        linear.sort_by(|x, y| y.0.cmp(&x.0));
+
+        let slack = linear.iter().map(|&(a, _)| a as i64).sum::<i64>() - bound;
+        if slack < 0 { return false; }
+
+        for &(a, lit) in linear.iter() {
+            if a as i64 <= slack { break; }
+            assigns.assignLit(lit, None);
+        }
+
+        let index = self.constrs.len();
+        for &(a, lit) in linear.iter() {
+            if self.occurs.get(&!lit).is_none() {
+                self.occurs.insert(&!lit, Vec::new());
+            }
+            self.occurs[&!lit].push((index, a));
+        }
+
+        self.constrs.push(Linear { terms : linear.into_boxed_slice(), slack : slack });
+        true
+    }
+
+    // Visit constraints with a term on '!p'. Returns the explanation of a conflict, if any:
+    pub fn propagateLit(&mut self, ca : &mut ClauseAllocator, assigns : &mut Assignment, p : Lit) -> Option<ClauseRef> {
+        let occs = match self.occurs.get(&p) { Some(occs) if !occs.is_empty() => { occs } _ => { return None; } };
+
+        self.undo.push(p);
+        for &(i, a) in occs.iter() {
+            self.constrs[i].slack -= a as i64;
+        }
+
+        for &(i, _) in occs.iter() {
+            let ref c = self.constrs[i];
+            if c.slack < 0 {
+                assigns.dequeueAll();
+                let cr = explain(ca, assigns, c, None);
+                self.reasons.push(cr);
+                return Some(cr);
+            }
+
+            for &(a, lit) in c.terms.iter() {
+                if a as i64 <= c.slack { break; }
+                if assigns.isUndef(lit.var()) {
+                    let cr = explain(ca, assigns, c, Some(lit));
+                    self.reasons.push(cr);
+                    assigns.assignLit(lit, Some(cr));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Restore the slacks after backtracking and free the explanations that are no longer locked:
+    pub fn cancel(&mut self, ca : &mut ClauseAllocator, assigns : &Assignment) {
+        while let Some(&p) = self.undo.last() {
+            if !assigns.isUndef(p.var()) { break; }
+            self.undo.pop();
+            for &(i, a) in self.occurs[&p].iter() {
+                self.constrs[i].slack += a as i64;
+            }
+        }
+
+        while let Some(&cr) = self.reasons.last() {
+            if assigns.isLocked(ca, cr) { break; }
+            self.reasons.pop();
+            ca.free(cr);
+        }
+    }
+
+    pub fn relocGC(&mut self, from : &mut ClauseAllocator, to : &mut ClauseAllocator) {
+        self.reasons.retain(|&cr| { !from.isDeleted(cr) });
+        for cr in self.reasons.iter_mut() {
+            *cr = from.relocTo(to, *cr);
+        }
+    }
+}
+
+
+// Clause of the implied literal, if any, and all false literals of the constraint. The literals
+// that are not false sum up to less than the bound, or to less than the bound plus the coefficient
+// of the implied literal, so the clause follows from the constraint:
+fn explain(ca : &mut ClauseAllocator, assigns : &Assignment, c : &Linear, implied : Option<Lit>) -> ClauseRef {
+    let mut lits = Vec::new();
+    if let Some(lit) = implied { lits.push(lit); }
+    for &(_, lit) in c.terms.iter() {
+        if assigns.isUnsat(lit) { lits.push(lit); }
+    }
+
+    let (_, cr) = ca.alloc(lits.into_boxed_slice(), false);
+    cr
+}
@@ -1,24 +1,36 @@
 extern crate time;
 use std::default::Default;
-use sat::{PartialResult, TotalResult, Solver};
+use std::io;
+use sat::{PartialResult, TotalResult, Formula, Solver, IncrementalSolver, LinearSolver, LearnCallback, TerminateCallback};
+use sat::encode;
 use sat::formula::{Var, Lit, LitMap};
 use sat::formula::clause::*;
 use sat::formula::assignment::*;
 use self::clause_db::*;
+pub use self::budget::InterruptHandle;
+pub use self::proof::{Proof, ProofFormat};
 use self::conflict::{AnalyzeContext, Seen, Conflict};
 pub use self::conflict::CCMinMode;
 use self::decision_heuristic::{DecisionHeuristicSettings, DecisionHeuristic};
 pub use self::decision_heuristic::PhaseSaving;
+pub use self::propagator::UserPropagator;
 
 mod budget;
 mod clause_db;
 mod conflict;
+pub mod cube;
 mod decision_heuristic;
+mod linear;
+pub mod portfolio;
+mod proof;
+mod propagator;
 pub mod simp;
 mod util;
-mod watches;
+pub mod watches;
+mod xor;
 
 
+#[derive(Clone)]
 pub struct Settings {
     pub heur       : DecisionHeuristicSettings,
     pub db         : ClauseDBSettings,
@@ -41,6 +53,7 @@ impl Default for Settings {
 }
 
 
+#[derive(Clone)]
 pub struct RestartStrategy {
     pub luby_restart  : bool,
     pub restart_first : f64,   // The initial restart limit.
@@ -70,6 +83,7 @@ impl Default for RestartStrategy {
 }
 
 
+#[derive(Clone)]
 pub struct LearningStrategySettings {
     pub min_learnts_lim         : i32,  // Minimum number to set the learnts limit to.
     pub size_factor             : f64,  // The intitial limit for learnt clauses is a factor of the original clauses.
@@ -152,18 +166,32 @@ impl SimplifyGuard {
 }
 
 
-enum SearchResult { UnSAT, SAT, Interrupted(f64), AssumpsConfl(LitMap<()>) }
+// Blocks of 2^(k-1) clauses grow fast, longer XORs are rarely encoded directly:
+const MAX_XOR_RECOVER_LEN : usize = 6;
 
 
+enum SearchResult { UnSAT, SAT, Interrupted(f64), AssumpsConfl(LitMap<()>), Rejected }
+
+
+enum ExternalResult { Quiet, Progress, Conflict(ClauseRef), UnSAT }
+
+
+// Clauses to add at restarts, which must be implied by the problem clauses:
+pub type ImportCallback = Box<FnMut() -> Vec<Vec<Lit>>>;
+
+
+#[derive(Clone)]
 pub struct CoreSettings {
     pub garbage_frac : f64,  // The fraction of wasted memory allowed before a garbage collection is triggered.
     pub use_rcheck   : bool, // Check if a clause is already implied. Prett costly, and subsumes subsumptions :)
+    pub recover_xors : bool  // Look for XOR constraints encoded into clauses during preprocessing.
 }
 
 impl Default for CoreSettings {
     fn default() -> CoreSettings {
         CoreSettings { garbage_frac : 0.20
                      , use_rcheck   : false
+                     , recover_xors : true
                      }
     }
 }
@@ -198,7 +226,10 @@ pub struct CoreSolver {
     released_vars : Vec<Var>,
     analyze       : AnalyzeContext,
     learnt        : LearningStrategy,
-    budget        : budget::Budget
+    budget        : budget::Budget,
+    external      : Option<propagator::External>,
+    import        : Option<ImportCallback>,
+    xors          : Vec<Vec<Lit>>           // XOR constraints as added, recovered ones aside, since those keep their clauses.
 }
 
 impl Solver for CoreSolver {
@@ -225,7 +256,23 @@ impl Solver for CoreSolver {
         }
     }
 
+    fn addXor(&mut self, lits : &[Lit]) -> bool {
+        assert!(self.assigns.isGroundLevel());
+        if !self.ok { return false; }
+        if self.encodesConstraints() {
+            return encode::xor(self, lits);
+        }
+
+        self.xors.push(lits.to_vec());
+        self.addXor_(lits)
+    }
+
     fn preprocess(&mut self) -> bool {
+        if self.recoversXors() {
+            for lits in self.findXors() {
+                if !self.addXor_(&lits) { return false; }
+            }
+        }
         self.simplify()
     }
 
@@ -233,9 +280,9 @@ impl Solver for CoreSolver {
         self.budget.off();
         match self.solveLimited(&[]) {
             PartialResult::UnSAT          => { TotalResult::UnSAT }
+            PartialResult::UnSATUnder(_)  => { TotalResult::UnSAT }
             PartialResult::SAT(model)     => { TotalResult::SAT(model) }
             PartialResult::Interrupted(_) => { TotalResult::Interrupted }
-            // _                             => { panic!("Impossible happened") }
         }
     }
 
@@ -264,6 +311,83 @@ impl Solver for CoreSolver {
         info!("CPU time              : {} s", cpu_time);
         info!("");
     }
+
+    fn formula(&self) -> Option<Formula> {
+        if self.watches.numberOfLinear() > 0 {
+            return None;
+        }
+
+        if !self.ok {
+            return Some(Formula { clauses : vec![Vec::new()], xors : Vec::new() });
+        }
+
+        // Clauses satisfied at ground level are left out, and so are the false literals of the rest:
+        let mut clauses : Vec<Vec<Lit>> = self.assigns.groundAssigns().iter().map(|&lit| vec![lit]).collect();
+        for cr in self.db.clauses() {
+            let c = self.db.ca.view(cr);
+            if c.iter().any(|lit| self.assigns.isSat(lit)) { continue; }
+            clauses.push(c.iter().filter(|&lit| !self.assigns.isUnsat(lit)).collect());
+        }
+
+        // Literals of XOR constraints that are assigned at ground level only change the parity:
+        let mut xors = Vec::new();
+        for x in self.xors.iter() {
+            let odd = x.iter().filter(|&&lit| self.assigns.isSat(lit)).count() % 2 == 1;
+            let mut lits : Vec<Lit> = x.iter().cloned().filter(|&lit| self.assigns.isUndef(lit.var())).collect();
+            if lits.is_empty() { continue; }
+            if odd { lits[0] = !lits[0]; }
+            xors.push(lits);
+        }
+
+        Some(Formula { clauses : clauses
+                     , xors    : xors
+                     })
+    }
+
+    fn finishProof(&mut self) -> io::Result<()> {
+        match self.db.proof {
+            Some(ref mut proof) => { proof.flush() }
+            None                => { Ok(()) }
+        }
+    }
+
+    fn setLearnCallback(&mut self, max_len : usize, callback : Option<LearnCallback>) {
+        self.db.export = callback.map(|callback| (max_len, callback));
+    }
+
+    fn setTerminateCallback(&mut self, callback : Option<TerminateCallback>) {
+        self.budget.terminate = callback;
+    }
+}
+
+impl IncrementalSolver for CoreSolver {
+    fn solveUnder(&mut self, assumptions : &[Lit]) -> PartialResult {
+        self.solveLimited(assumptions)
+    }
+
+    fn groundLits(&self) -> &[Lit] {
+        self.assigns.groundAssigns()
+    }
+}
+
+impl LinearSolver for CoreSolver {
+    fn addAtLeast(&mut self, terms : &[(u64, Lit)], bound : u64) -> bool {
+        assert!(self.assigns.isGroundLevel());
+        if !self.ok { return false; }
+        if self.encodesConstraints() {
+            return encode::atLeastPB(self, terms, bound, None);
+        }
+
+        if !self.watches.watchLinear(&mut self.assigns, terms, bound) {
+            self.setUnsat();
+            return false;
+        }
+
+        match self.watches.propagate(&mut self.db.ca, &mut self.assigns) {
+            None    => { true }
+            Some(_) => { self.setUnsat(); false }
+        }
+    }
 }
 
 enum AddClause { UnSAT, Consumed, Added(ClauseRef) }
@@ -283,9 +407,86 @@ impl CoreSolver {
                    , analyze       : AnalyzeContext::new(settings.ccmin_mode)
                    , learnt        : LearningStrategy::new(settings.learnt)
                    , budget        : budget::Budget::new()
+                   , external      : None
+                   , import        : None
+                   , xors          : Vec::new()
                    }
     }
 
+    // Log every clause addition and removal as DRAT proof. Should be set before any clause is added:
+    pub fn setProof(&mut self, proof : Proof) {
+        self.db.proof = Some(proof);
+    }
+
+    // Make literal 'lit' permanently true. Clauses satisfied by it are removed and the variable itself
+    // is recycled by a later 'newVar()' once the next 'simplify()' has purged it from the database.
+    // Variables of linear or XOR constraints just stay assigned:
+    pub fn releaseVar(&mut self, lit : Lit) {
+        if self.assigns.isUndef(lit.var()) {
+            self.addClause(&[lit]);
+            if !self.watches.isConstrained(lit.var()) {
+                self.released_vars.push(lit.var());
+            }
+        }
+    }
+
+    // Limit the number of conflicts for the following 'solveLimited' calls (counted from now):
+    pub fn setConfBudget(&mut self, x : u64) {
+        self.budget.setConflictBudget(self.stats.conflicts, x);
+    }
+
+    // Limit the number of propagations for the following 'solveLimited' calls (counted from now):
+    pub fn setPropBudget(&mut self, x : u64) {
+        self.budget.setPropagationBudget(self.watches.propagations, x);
+    }
+
+    pub fn budgetOff(&mut self) {
+        self.budget.off();
+    }
+
+    // Trigger asynchronous interruption. The flag stays raised until 'clearInterrupt()' is called:
+    pub fn interrupt(&self) {
+        self.budget.handle().interrupt();
+    }
+
+    pub fn clearInterrupt(&self) {
+        self.budget.handle().clear();
+    }
+
+    // Handle that can be moved to another thread to interrupt running search:
+    pub fn interruptHandle(&self) -> InterruptHandle {
+        self.budget.handle()
+    }
+
+    // Replace the interrupt flag, e.g. by one that several solvers share:
+    pub fn setInterruptHandle(&mut self, handle : InterruptHandle) {
+        self.budget.share(handle);
+    }
+
+    // Clauses from other solvers of the same problem, taken at every restart:
+    pub fn setImportCallback(&mut self, callback : Option<ImportCallback>) {
+        self.import = callback;
+    }
+
+    // Only one propagator is connected at a time, and only between calls to 'solveLimited':
+    pub fn connectPropagator(&mut self, propagator : Box<UserPropagator>) {
+        assert!(self.assigns.isGroundLevel());
+        self.external = Some(propagator::External::new(propagator));
+    }
+
+    // Clauses learnt from its reasons and clauses stay:
+    pub fn disconnectPropagator(&mut self) -> Option<Box<UserPropagator>> {
+        assert!(self.assigns.isGroundLevel());
+        self.external.take().map(|ext| ext.propagator)
+    }
+
+    // Assignments of 'v' are reported to the propagator from now on:
+    pub fn observeVar(&mut self, v : Var) {
+        if let Some(ref mut ext) = self.external {
+            ext.observe(v);
+        }
+    }
+
     fn addClause_(&mut self, clause : &[Lit]) -> AddClause {
         assert!(self.assigns.isGroundLevel());
         if !self.ok { return AddClause::UnSAT; }
@@ -301,6 +502,7 @@ impl CoreSolver {
             // Check if clause is satisfied and remove false/duplicate literals:
             ps.sort();
             ps.dedup();
+            let full = if self.db.proof.is_some() { Some(ps.clone()) } else { None };
             ps.retain(|&lit| { !self.assigns.isUnsat(lit) });
 
             {
@@ -313,12 +515,19 @@ impl CoreSolver {
                 }
             }
 
+            if let Some(full) = full {
+                if full.len() > ps.len() {
+                    self.db.proofAdd(&ps);
+                    self.db.proofDelete(&full);
+                }
+            }
+
             ps.into_boxed_slice()
         };
 
         match ps.len() {
             0 => {
-                self.ok = false;
+                self.setUnsat();
                 AddClause::UnSAT
             }
 
@@ -326,7 +535,7 @@ impl CoreSolver {
                 self.assigns.assignLit(ps[0], None);
                 match self.watches.propagate(&mut self.db.ca, &mut self.assigns) {
                     None    => { AddClause::Consumed }
-                    Some(_) => { self.ok = false; AddClause::UnSAT }
+                    Some(_) => { self.setUnsat(); AddClause::UnSAT }
                 }
             }
 
@@ -338,6 +547,44 @@ impl CoreSolver {
         }
     }
 
+    // The empty clause is derived at this point, so it is also the last step of the proof:
+    fn setUnsat(&mut self) {
+        self.ok = false;
+        self.db.proofAdd(&[]);
+    }
+
+    // Propagate the XOR constraint natively, without recording it for 'formula()':
+    fn addXor_(&mut self, lits : &[Lit]) -> bool {
+        if !self.watches.watchXor(&mut self.assigns, lits) {
+            self.setUnsat();
+            return false;
+        }
+
+        match self.watches.propagate(&mut self.db.ca, &mut self.assigns) {
+            None    => { true }
+            Some(_) => { self.setUnsat(); false }
+        }
+    }
+
+    // Clauses derived from native XOR or linear constraints can not be checked against the problem
+    // clauses of a DRAT proof, so while a proof is written such constraints go into clauses instead:
+    fn encodesConstraints(&self) -> bool {
+        self.db.proof.is_some()
+    }
+
+    fn recoversXors(&self) -> bool {
+        self.settings.recover_xors && !self.encodesConstraints()
+    }
+
+    // XOR constraints among the problem clauses, see 'xor::recover'. The clauses themselves stay:
+    pub fn findXors(&self) -> Vec<Vec<Lit>> {
+        let xors = xor::recover(&self.db.ca, self.db.clauses(), MAX_XOR_RECOVER_LEN);
+        if !xors.is_empty() {
+            info!("|  Recovered XORs:       {:12}                                         |", xors.len());
+        }
+        xors
+    }
+
     // Description:
     //   Simplify the clause database according to the current top-level assigment. Currently, the only
     //   thing done here is the removal of satisfied clauses, but more things can be put here.
@@ -346,7 +593,7 @@ impl CoreSolver {
         if !self.ok { return false; }
 
         if let Some(_) = self.watches.propagate(&mut self.db.ca, &mut self.assigns) {
-            self.ok = false;
+            self.setUnsat();
             return false;
         }
 
@@ -368,6 +615,7 @@ impl CoreSolver {
                 let seen = &self.analyze.seen;
                 self.assigns.retainAssignments(|l| { seen[&l.var()] == Seen::Undef });
             }
+            self.watches.cancel(&mut self.db.ca, &self.assigns);
 
             for v in self.released_vars.iter() {
                 self.analyze.seen[v] = Seen::Undef;
@@ -375,6 +623,7 @@ impl CoreSolver {
 
             // Released variables are now ready to be reused:
             for &v in self.released_vars.iter() {
+                self.heur.setDecisionVar(v, false);
                 self.assigns.freeVar(v);
             }
             self.released_vars.clear();
@@ -396,6 +645,10 @@ impl CoreSolver {
         let ref mut heur = self.heur;
         let top_level = self.assigns.decisionLevel();
         self.assigns.rewindUntilLevel(target_level, |level, lit| { heur.cancel(lit, level == top_level); });
+        self.watches.cancel(&mut self.db.ca, &self.assigns);
+        if let Some(ref mut ext) = self.external {
+            ext.cancel(&mut self.db.ca, &self.assigns);
+        }
     }
 
     pub fn solveLimited(&mut self, assumptions : &[Lit]) -> PartialResult {
@@ -428,18 +681,28 @@ impl CoreSolver {
                 }
 
                 SearchResult::UnSAT           => {
-                    self.ok = false;
+                    self.setUnsat();
                     return PartialResult::UnSAT;
                 }
 
-                SearchResult::AssumpsConfl(_) => { // TODO: implement
-                    return PartialResult::UnSAT;
+                SearchResult::AssumpsConfl(conflict) => {
+                    // The final conflict is a clause over negated assumptions, report the assumptions themselves:
+                    return PartialResult::UnSATUnder(conflict.iter().map(|(lit, _)| !lit).collect());
+                }
+
+                SearchResult::Rejected        => {
+                    // Nothing excludes the rejected model, so search would only find it again:
+                    return PartialResult::Interrupted(progressEstimate(&self.assigns));
                 }
 
                 SearchResult::Interrupted(c)  => {
                     if !self.budget.within(self.stats.conflicts, self.watches.propagations) {
                         return PartialResult::Interrupted(c);
                     }
+
+                    if !self.importClauses() {
+                        return PartialResult::UnSAT;
+                    }
                 }
             }
         }
@@ -458,12 +721,31 @@ impl CoreSolver {
         self.stats.starts += 1;
 
         let mut conflictC = 0;
+        let mut rejected = None; // Conflict with the clause that excludes a rejected model.
         loop {
-            match self.watches.propagate(&mut self.db.ca, &mut self.assigns) {
+            let confl =
+                match rejected.take().or_else(|| self.watches.propagate(&mut self.db.ca, &mut self.assigns)) {
+                    None  => {
+                        match self.propagateExternal() {
+                            ExternalResult::Quiet          => { None }
+                            ExternalResult::Progress       => { continue; }
+                            ExternalResult::Conflict(cr)   => { Some(cr) }
+                            ExternalResult::UnSAT          => { return SearchResult::UnSAT; }
+                        }
+                    }
+
+                    confl => { confl }
+                };
+
+            match confl {
                 Some(confl) => {
                     self.stats.conflicts += 1;
                     conflictC += 1;
 
+                    if let Some(ref mut ext) = self.external {
+                        ext.explain(&mut self.db.ca, &mut self.assigns);
+                    }
+
                     match self.analyze.analyze(&mut self.db, &mut self.heur, &self.assigns, confl) {
                         Conflict::Ground => {
                             return SearchResult::UnSAT;
@@ -471,12 +753,14 @@ impl CoreSolver {
 
                         Conflict::Unit(level, unit) => {
                             self.cancelUntil(level);
+                            self.db.proofAdd(&[unit]);
+                            self.db.exportLearnt(&[unit], 1);
                             self.assigns.assignLit(unit, None);
                         }
 
                         Conflict::Learned(level, lit, clause) => {
                             self.cancelUntil(level);
-                            let (c, cr) = self.db.learnClause(clause);
+                            let (c, cr) = self.db.learnClause(&self.assigns, clause);
                             self.watches.watchClause(c, cr);
                             self.assigns.assignLit(lit, Some(cr));
                         }
@@ -530,6 +814,9 @@ impl CoreSolver {
                                 self.assigns.newDecisionLevel();
                             }
                             LitVal::False => {
+                                if let Some(ref mut ext) = self.external {
+                                    ext.explain(&mut self.db.ca, &mut self.assigns);
+                                }
                                 let conflict = self.analyze.analyzeFinal(&self.db.ca, &self.assigns, !p);
                                 return SearchResult::AssumpsConfl(conflict);
                             }
@@ -543,10 +830,22 @@ impl CoreSolver {
                     if let None = next {
                         // New variable decision:
                         self.stats.decisions += 1;
-                        match self.heur.pickBranchLit(&self.assigns) {
-                            Some(n) => { next = Some(n) }
-                            None    => { return SearchResult::SAT; } // Model found:
-                        };
+                        next = self.external.as_mut().and_then(|ext| ext.propagator.decide()).filter(|lit| self.assigns.isUndef(lit.var()));
+                        if let None = next {
+                            match self.heur.pickBranchLit(&self.assigns) {
+                                Some(n) => { next = Some(n) }
+                                None    => {
+                                    // Model found, unless the propagator rejects it:
+                                    match self.checkExternalModel() {
+                                        Some(ExternalResult::Quiet)        => { return SearchResult::SAT; }
+                                        Some(ExternalResult::Progress)     => { continue; }
+                                        Some(ExternalResult::Conflict(cr)) => { rejected = Some(cr); continue; }
+                                        Some(ExternalResult::UnSAT)        => { return SearchResult::UnSAT; }
+                                        None                               => { return SearchResult::Rejected; }
+                                    }
+                                }
+                            };
+                        }
                     }
 
                     // Increase decision level and enqueue 'next'
@@ -557,6 +856,158 @@ impl CoreSolver {
         }
     }
 
+    // Clauses from the import callback, added on the ground level as learnt ones:
+    fn importClauses(&mut self) -> bool {
+        let clauses =
+            match self.import {
+                Some(ref mut import) => { import() }
+                None                 => { return true; }
+            };
+
+        for mut ps in clauses {
+            ps.sort();
+            ps.dedup();
+            if ps.iter().any(|&lit| self.assigns.isSat(lit)) || ps.windows(2).any(|w| w[0] == !w[1]) {
+                continue;
+            }
+
+            ps.retain(|&lit| !self.assigns.isUnsat(lit));
+            match ps.len() {
+                0 => { self.setUnsat(); return false; }
+                1 => { self.assigns.assignLit(ps[0], None); }
+                _ => {
+                    let (c, cr) = self.db.importClause(ps.into_boxed_slice());
+                    self.watches.watchClause(c, cr);
+                }
+            }
+        }
+
+        match self.watches.propagate(&mut self.db.ca, &mut self.assigns) {
+            None    => { true }
+            Some(_) => { self.setUnsat(); false }
+        }
+    }
+
+    // Report new assignments to the propagator, then take its clauses and implied literals. Reports
+    // 'Quiet' if nothing changed:
+    fn propagateExternal(&mut self) -> ExternalResult {
+        match self.external {
+            Some(ref mut ext) => { ext.notify(&self.assigns); }
+            None              => { return ExternalResult::Quiet; }
+        }
+
+        let mut result = ExternalResult::Quiet;
+        while let Some(clause) = self.external.as_mut().unwrap().propagator.externalClause() {
+            match self.addClauseDuringSearch(&clause, false) {
+                ExternalResult::Quiet    => {}
+                ExternalResult::Progress => { result = ExternalResult::Progress; }
+                conflict                 => { return conflict; }
+            }
+        }
+
+        if let ExternalResult::Progress = result {
+            return result;
+        }
+
+        let lits = self.external.as_mut().unwrap().propagator.propagate();
+        for lit in lits {
+            match self.assigns.ofLit(lit) {
+                LitVal::Undef => {
+                    self.external.as_mut().unwrap().implied(&mut self.assigns, lit);
+                    result = ExternalResult::Progress;
+                }
+
+                LitVal::True  => {}
+
+                // The reason is needed right away, and is kept like a learnt clause:
+                LitVal::False => {
+                    let reason = self.external.as_mut().unwrap().propagator.reason(lit);
+                    return self.addClauseDuringSearch(&reason, true);
+                }
+            }
+        }
+
+        result
+    }
+
+    // The propagator has the last word on a complete assignment. A rejected one must be followed by
+    // a clause that excludes it, None if it is not:
+    fn checkExternalModel(&mut self) -> Option<ExternalResult> {
+        let accepted =
+            match self.external {
+                Some(ref mut ext) => { ext.propagator.checkModel(&extractModel(&self.assigns)) }
+                None              => { return Some(ExternalResult::Quiet); }
+            };
+
+        if accepted { return Some(ExternalResult::Quiet); }
+
+        match self.propagateExternal() {
+            ExternalResult::Quiet => { None }
+            result                => { Some(result) }
+        }
+    }
+
+    // Add a clause above the ground level. If it is unit or falsified under the assignment, search
+    // backtracks to the level where it propagates, or returns it as a conflict at the highest level
+    // of its literals:
+    fn addClauseDuringSearch(&mut self, clause : &[Lit], learnt : bool) -> ExternalResult {
+        let mut ps : Vec<Lit> = clause.to_vec();
+        ps.sort();
+        ps.dedup();
+        if ps.windows(2).any(|w| w[0] == !w[1]) { return ExternalResult::Quiet; }
+
+        {
+            let ref assigns = self.assigns;
+            let level = |lit : &Lit| assigns.vardata(lit.var()).level;
+            ps.retain(|lit| !assigns.isUnsat(*lit) || level(lit) != GroundLevel);
+            if ps.iter().any(|lit| assigns.isSat(*lit) && level(lit) == GroundLevel) { return ExternalResult::Quiet; }
+
+            // True literals first, then unassigned ones, then false ones from the highest level down:
+            ps.sort_by_key(|lit| {
+                match assigns.ofLit(*lit) {
+                    LitVal::True  => { (0, 0) }
+                    LitVal::Undef => { (1, 0) }
+                    LitVal::False => { (2, usize::max_value() - level(lit).offset()) }
+                }
+            });
+        }
+
+        match ps.len() {
+            0 => { return ExternalResult::UnSAT; }
+
+            1 => {
+                self.cancelUntil(GroundLevel);
+                self.assigns.assignLit(ps[0], None);
+                return ExternalResult::Progress;
+            }
+
+            _ => {}
+        }
+
+        let cr = {
+            let (c, cr) = if learnt { self.db.learnClause(&self.assigns, ps.clone().into_boxed_slice()) } else { self.db.addClause(ps.clone().into_boxed_slice()) };
+            self.watches.watchClause(c, cr);
+            cr
+        };
+
+        let level0 = self.assigns.vardata(ps[0].var()).level;
+        let level1 = self.assigns.vardata(ps[1].var()).level;
+        match (self.assigns.ofLit(ps[0]), self.assigns.ofLit(ps[1])) {
+            (LitVal::False, _) if level0 == level1 => {
+                self.cancelUntil(level0);
+                ExternalResult::Conflict(cr)
+            }
+
+            (LitVal::False, _) | (LitVal::Undef, LitVal::False) => {
+                self.cancelUntil(level1);
+                self.assigns.assignLit(ps[0], Some(cr));
+                ExternalResult::Progress
+            }
+
+            _ => { ExternalResult::Quiet }
+        }
+    }
+
     fn garbageCollect(&mut self) {
         // Initialize the next region to a size corresponding to the estimated utilization degree. This
         // is not precise but should avoid some unnecessary reallocations for the new region:
@@ -567,6 +1018,9 @@ impl CoreSolver {
 
     fn relocAll(&mut self, mut to : ClauseAllocator) {
         self.watches.relocGC(&mut self.db.ca, &mut to);
+        if let Some(ref mut ext) = self.external {
+            ext.relocGC(&mut self.db.ca, &mut to);
+        }
         self.assigns.relocGC(&mut self.db.ca, &mut to);
         self.db.relocGC(to);
     }
@@ -0,0 +1,305 @@
+use std::{io, panic, thread, usize};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use sat::{PartialResult, TotalResult, Formula, Solver, LearnCallback, TerminateCallback};
+use sat::formula::{Var, Lit};
+use sat::formula::assignment::Assignment;
+use super::{CoreSolver, Settings, InterruptHandle, CCMinMode, PhaseSaving};
+
+
+// Learnt clauses are shared if they are this short, or have this low an LBD:
+const SHARE_LEN : usize = 8;
+const SHARE_LBD : usize = 2;
+
+
+// Variations of 'base' for 'n' threads. The first thread keeps 'base' as it is, the others differ
+// in random seed, restart strategy, phase saving and conflict clause minimization:
+pub fn diversify(base : &Settings, n : usize) -> Vec<Settings> {
+    (0 .. n).map(|i| {
+        let mut s = base.clone();
+        if i > 0 {
+            s.heur.random_seed = base.heur.random_seed + (i as f64) * 1000003.0;
+            s.heur.rnd_init_act = true;
+            if i % 4 == 3 { s.heur.random_var_freq = base.heur.random_var_freq.max(0.02); }
+
+            s.restart.luby_restart = i % 2 == 0;
+            if !s.restart.luby_restart { s.restart.restart_inc = 1.5; }
+
+            s.heur.phase_saving =
+                match i % 3 {
+                    0 => { PhaseSaving::Full }
+                    1 => { PhaseSaving::Limited }
+                    _ => { PhaseSaving::None }
+                };
+            s.ccmin_mode = if i % 4 == 2 { CCMinMode::Basic } else { CCMinMode::Deep };
+        }
+        s
+    }).collect()
+}
+
+
+#[derive(Clone)]
+enum Constraint {
+    Clause(Vec<Lit>),
+    Xor(Vec<Lit>)
+}
+
+
+#[derive(Clone)]
+struct Shared {
+    from : usize, // Thread that learnt the clause.
+    lits : Arc<[Lit]>,
+    lbd  : usize
+}
+
+
+// Learnt clauses on their way between the threads. Every reader, a thread or the report to the learn
+// callback, has a cursor of its own, and the clauses that all readers are past are dropped:
+struct Pool {
+    clauses : VecDeque<Shared>,
+    dropped : usize,     // Number of clauses dropped from the front so far.
+    cursors : Vec<usize> // Number of clauses every reader is past.
+}
+
+impl Pool {
+    fn new(readers : usize) -> Pool {
+        Pool { clauses : VecDeque::new()
+             , dropped : 0
+             , cursors : vec![0; readers]
+             }
+    }
+
+    fn len(&self) -> usize {
+        self.dropped + self.clauses.len()
+    }
+
+    fn push<I : Iterator<Item = Shared>>(&mut self, clauses : I) {
+        self.clauses.extend(clauses);
+    }
+
+    // Clauses that 'reader' has not read yet:
+    fn read(&mut self, reader : usize) -> Vec<Shared> {
+        let unread = self.clauses.range(self.cursors[reader] - self.dropped ..).cloned().collect();
+        self.cursors[reader] = self.len();
+
+        let read = *self.cursors.iter().min().unwrap();
+        self.clauses.drain(.. read - self.dropped);
+        self.dropped = read;
+        unread
+    }
+}
+
+
+// Several differently configured core solvers on threads of their own. The problem is kept here and
+// loaded into fresh solvers on every call to 'solve', so all of them number variables the same way.
+// Short learnt clauses are exchanged at restarts, and the first definitive answer interrupts the
+// other threads. Learnt clauses are reported as they are shared:
+pub struct Portfolio {
+    settings    : Vec<Settings>,
+    assigns     : Assignment,               // Only hands out variables.
+    vars        : Vec<(Option<bool>, bool)>,
+    constraints : Vec<Constraint>,
+    num_clauses : usize,
+    learn       : Option<(usize, LearnCallback)>,
+    terminate   : Option<TerminateCallback>,
+    winner      : Option<usize>,            // Thread that answered the last call.
+    shared      : usize                     // Number of clauses shared in the last call.
+}
+
+impl Solver for Portfolio {
+    fn nVars(&self) -> usize {
+        self.vars.len()
+    }
+
+    fn nClauses(&self) -> usize {
+        self.num_clauses
+    }
+
+    fn newVar(&mut self, upol : Option<bool>, dvar : bool) -> Var {
+        self.vars.push((upol, dvar));
+        self.assigns.newVar()
+    }
+
+    fn addClause(&mut self, clause : &[Lit]) -> bool {
+        self.num_clauses += 1;
+        self.constraints.push(Constraint::Clause(clause.to_vec()));
+        true
+    }
+
+    fn addXor(&mut self, lits : &[Lit]) -> bool {
+        self.constraints.push(Constraint::Xor(lits.to_vec()));
+        true
+    }
+
+    // Every thread preprocesses on its own:
+    fn preprocess(&mut self) -> bool {
+        true
+    }
+
+    fn solve(&mut self) -> TotalResult {
+        let vars = Arc::new(self.vars.clone());
+        let constraints = Arc::new(self.constraints.clone());
+        let pool = Arc::new(Mutex::new(Pool::new(self.settings.len() + 1)));
+        let stop = InterruptHandle::new();
+        let (tx, rx) = mpsc::channel();
+
+        let mut threads = Vec::with_capacity(self.settings.len());
+        for (id, settings) in self.settings.iter().cloned().enumerate() {
+            let (vars, constraints, pool, stop, tx) = (vars.clone(), constraints.clone(), pool.clone(), stop.clone(), tx.clone());
+            threads.push(thread::spawn(move || {
+                let result = work(id, settings, &vars, &constraints, pool, stop);
+                let _ = tx.send((id, result));
+            }));
+        }
+        drop(tx);
+
+        self.winner = None;
+        let mut result = TotalResult::Interrupted;
+        let mut running = threads.len();
+        while running > 0 {
+            match rx.recv_timeout(Duration::from_millis(10)) {
+                Ok((_, TotalResult::Interrupted)) => { running -= 1; }
+                Ok((id, answer))                  => {
+                    running -= 1;
+                    if self.winner.is_none() {
+                        self.winner = Some(id);
+                        result = answer;
+                        stop.interrupt();
+                    }
+                }
+
+                Err(mpsc::RecvTimeoutError::Timeout)      => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => { break; } // Some thread panicked.
+            }
+
+            self.report(&pool);
+            if self.terminate.as_mut().map(|terminate| terminate()).unwrap_or(false) {
+                stop.interrupt();
+            }
+        }
+
+        stop.interrupt();
+        for t in threads {
+            if let Err(err) = t.join() {
+                panic::resume_unwind(err);
+            }
+        }
+
+        self.shared = pool.lock().unwrap().len();
+        result
+    }
+
+    fn printStats(&self) {
+        info!("threads               : {:<12}", self.settings.len());
+        info!("shared clauses        : {:<12}", self.shared);
+        if let Some(id) = self.winner {
+            info!("answered by thread    : {:<12}", id);
+        }
+        info!("");
+    }
+
+    // The threads simplify copies of their own, so this is the formula as it was given:
+    fn formula(&self) -> Option<Formula> {
+        let mut formula = Formula { clauses : Vec::new(), xors : Vec::new() };
+        for c in self.constraints.iter() {
+            match *c {
+                Constraint::Clause(ref lits) => { formula.clauses.push(lits.clone()); }
+                Constraint::Xor(ref lits)    => { formula.xors.push(lits.clone()); }
+            }
+        }
+        Some(formula)
+    }
+
+    // The threads write no proof:
+    fn finishProof(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn setLearnCallback(&mut self, max_len : usize, callback : Option<LearnCallback>) {
+        self.learn = callback.map(|callback| (max_len, callback));
+    }
+
+    fn setTerminateCallback(&mut self, callback : Option<TerminateCallback>) {
+        self.terminate = callback;
+    }
+}
+
+impl Portfolio {
+    pub fn new(settings : Vec<Settings>) -> Portfolio {
+        assert!(!settings.is_empty());
+        Portfolio { settings    : settings
+                  , assigns     : Assignment::new()
+                  , vars        : Vec::new()
+                  , constraints : Vec::new()
+                  , num_clauses : 0
+                  , learn       : None
+                  , terminate   : None
+                  , winner      : None
+                  , shared      : 0
+                  }
+    }
+
+    // Pass clauses shared since the last report to the learn callback. The report reads the pool
+    // after the threads:
+    fn report(&mut self, pool : &Mutex<Pool>) {
+        let shared = pool.lock().unwrap().read(self.settings.len());
+        if let Some((max_len, ref mut callback)) = self.learn {
+            for c in shared.iter().filter(|c| c.lits.len() <= max_len) {
+                callback(&c.lits, c.lbd);
+            }
+        }
+    }
+}
+
+
+fn work(id : usize, settings : Settings, vars : &[(Option<bool>, bool)], constraints : &[Constraint], pool : Arc<Mutex<Pool>>, stop : InterruptHandle) -> TotalResult {
+    let mut solver = CoreSolver::new(settings);
+    solver.setInterruptHandle(stop);
+    for &(upol, dvar) in vars.iter() {
+        solver.newVar(upol, dvar);
+    }
+
+    for c in constraints.iter() {
+        let ok =
+            match *c {
+                Constraint::Clause(ref lits) => { solver.addClause(lits) }
+                Constraint::Xor(ref lits)    => { solver.addXor(lits) }
+            };
+        if !ok { return TotalResult::UnSAT; }
+    }
+
+    if !solver.preprocess() {
+        return TotalResult::UnSAT;
+    }
+
+    // Clauses learnt since the last restart wait here until they are shared:
+    let outbox = Rc::new(RefCell::new(Vec::new()));
+    {
+        let outbox = outbox.clone();
+        solver.setLearnCallback(usize::MAX, Some(Box::new(move |lits : &[Lit], lbd| {
+            if lits.len() <= SHARE_LEN || lbd <= SHARE_LBD {
+                outbox.borrow_mut().push(Shared { from : id, lits : Arc::from(lits), lbd : lbd });
+            }
+        })));
+    }
+
+    // Only the shared clauses are copied under the lock, their literals after it:
+    solver.setImportCallback(Some(Box::new(move || {
+        let shared = {
+            let mut pool = pool.lock().unwrap();
+            pool.push(outbox.borrow_mut().drain(..));
+            pool.read(id)
+        };
+        shared.iter().filter(|c| c.from != id).map(|c| c.lits.to_vec()).collect()
+    })));
+
+    match solver.solveLimited(&[]) {
+        PartialResult::SAT(model)     => { TotalResult::SAT(model) }
+        PartialResult::UnSAT          => { TotalResult::UnSAT }
+        PartialResult::UnSATUnder(_)  => { TotalResult::UnSAT }
+        PartialResult::Interrupted(_) => { TotalResult::Interrupted }
+    }
+}
@@ -0,0 +1,83 @@
+use std::io;
+use sat::formula::Lit;
+
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ProofFormat { Text, Binary }
+
+
+// DRAT proof log. Variables are numbered as DIMACS ids, i.e. 'x0' is written as '1':
+pub struct Proof {
+    out    : Box<io::Write + Send>,
+    format : ProofFormat,
+    error  : Option<io::Error>      // First write error, reported by 'flush()'.
+}
+
+impl Proof {
+    pub fn new(out : Box<io::Write + Send>, format : ProofFormat) -> Proof {
+        Proof { out    : out
+              , format : format
+              , error  : None
+              }
+    }
+
+    pub fn add<I : IntoIterator<Item = Lit>>(&mut self, lits : I) {
+        let res = self.write(None, lits);
+        self.check(res);
+    }
+
+    pub fn delete<I : IntoIterator<Item = Lit>>(&mut self, lits : I) {
+        let res = self.write(Some(b'd'), lits);
+        self.check(res);
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(err) => { Err(err) }
+            None      => { self.out.flush() }
+        }
+    }
+
+    fn check(&mut self, res : io::Result<()>) {
+        if let Err(err) = res {
+            if self.error.is_none() {
+                self.error = Some(err);
+            }
+        }
+    }
+
+    fn write<I : IntoIterator<Item = Lit>>(&mut self, tag : Option<u8>, lits : I) -> io::Result<()> {
+        match self.format {
+            ProofFormat::Text   => {
+                if let Some(_) = tag {
+                    try!(write!(self.out, "d "));
+                }
+                for lit in lits {
+                    let id = (lit.var().index() + 1) as i64;
+                    try!(write!(self.out, "{} ", if lit.sign() { -id } else { id }));
+                }
+                writeln!(self.out, "0")
+            }
+
+            ProofFormat::Binary => {
+                let mut buf = vec![tag.unwrap_or(b'a')];
+                for lit in lits {
+                    let mut u = 2 * (lit.var().index() + 1) + (lit.sign() as usize);
+                    while u > 127 {
+                        buf.push(((u & 127) | 128) as u8);
+                        u >>= 7;
+                    }
+                    buf.push(u as u8);
+                }
+                buf.push(0);
+                self.out.write_all(&buf)
+            }
+        }
+    }
+}
+
+impl Drop for Proof {
+    fn drop(&mut self) {
+        let _ = self.out.flush();
+    }
+}
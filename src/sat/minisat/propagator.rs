@@ -0,0 +1,143 @@
+use sat::formula::{Var, Lit, VarMap};
+use sat::formula::assignment::*;
+use sat::formula::clause::*;
+
+
+// Domain specific reasoning plugged into search, in the manner of IPASIR-UP. The solver reports
+// assignments of observed variables level by level, and every backtrack below a reported level.
+// In return, the propagator may suggest decisions, imply literals and add clauses at any time.
+// Implied literals are explained only when conflict analysis gets to them. Clauses that come from
+// the propagator are not part of DRAT proofs.
+pub trait UserPropagator {
+    // Assignments of observed variables at the current decision level, in trail order:
+    fn notifyAssignment(&mut self, lits : &[Lit]);
+
+    fn notifyNewDecisionLevel(&mut self);
+
+    // Assignments above 'level' are undone:
+    fn notifyBacktrack(&mut self, level : usize);
+
+    // Called before the solver picks a decision itself. Suggestions of assigned variables are ignored:
+    fn decide(&mut self) -> Option<Lit> {
+        None
+    }
+
+    // Literals implied by the assignments reported so far. The solver asks again after every round
+    // of its own propagation:
+    fn propagate(&mut self) -> Vec<Lit> {
+        Vec::new()
+    }
+
+    // Clause of 'lit' and false literals that implies it. Above the ground level, it must contain
+    // at least one literal besides 'lit':
+    fn reason(&mut self, lit : Lit) -> Vec<Lit>;
+
+    // Clauses to add, asked for until there are none left:
+    fn externalClause(&mut self) -> Option<Vec<Lit>> {
+        None
+    }
+
+    // Called on every complete assignment. A rejected model must be excluded by a clause that is
+    // returned from 'externalClause', otherwise search stops there and reports an interrupt:
+    fn checkModel(&mut self, _ : &VarMap<bool>) -> bool {
+        true
+    }
+}
+
+
+pub struct External {
+    pub propagator : Box<UserPropagator>,
+    observed       : VarMap<()>,
+    notified       : usize,         // Length of the trail reported so far.
+    level          : usize,         // Decision level the propagator knows about.
+    pending        : Vec<Lit>,      // Literals implied by the propagator that have no reason clause yet, in trail order.
+    reasons        : Vec<ClauseRef> // Reason clauses in trail order.
+}
+
+impl External {
+    pub fn new(propagator : Box<UserPropagator>) -> External {
+        External { propagator : propagator
+                 , observed   : VarMap::new()
+                 , notified   : 0
+                 , level      : 0
+                 , pending    : Vec::new()
+                 , reasons    : Vec::new()
+                 }
+    }
+
+    pub fn observe(&mut self, v : Var) {
+        self.observed.insert(&v, ());
+    }
+
+    // Report the part of the trail the propagator has not seen yet:
+    pub fn notify(&mut self, assigns : &Assignment) {
+        let mut batch = Vec::new();
+        while self.notified < assigns.numberOfAssigns() {
+            let lit = assigns.assignAt(self.notified);
+            let level = assigns.vardata(lit.var()).level.offset();
+            if level > self.level {
+                if !batch.is_empty() { self.propagator.notifyAssignment(&batch); batch.clear(); }
+                while self.level < level {
+                    self.propagator.notifyNewDecisionLevel();
+                    self.level += 1;
+                }
+            }
+
+            if self.observed.get(&lit.var()).is_some() { batch.push(lit); }
+            self.notified += 1;
+        }
+
+        if !batch.is_empty() { self.propagator.notifyAssignment(&batch); }
+    }
+
+    // The literal is assigned without reason. Unless on the ground level, it gets one once needed:
+    pub fn implied(&mut self, assigns : &mut Assignment, lit : Lit) {
+        assigns.assignLit(lit, None);
+        if !assigns.isGroundLevel() { self.pending.push(lit); }
+    }
+
+    // Ask the propagator for the reasons that are still missing, before the trail is analyzed:
+    pub fn explain(&mut self, ca : &mut ClauseAllocator, assigns : &mut Assignment) {
+        for &lit in self.pending.iter() {
+            let mut lits = self.propagator.reason(lit);
+            lits.retain(|&x| x != lit);
+            assert!(!lits.is_empty() && lits.iter().all(|&x| assigns.isUnsat(x)), "invalid reason of {:?}", lit);
+
+            lits.insert(0, lit);
+            let (_, cr) = ca.alloc(lits.into_boxed_slice(), false);
+            assigns.setReason(lit, cr);
+            self.reasons.push(cr);
+        }
+        self.pending.clear();
+    }
+
+    // Must follow every rewind of the assignment:
+    pub fn cancel(&mut self, ca : &mut ClauseAllocator, assigns : &Assignment) {
+        let level = assigns.decisionLevel().offset();
+        if level < self.level {
+            self.propagator.notifyBacktrack(level);
+            self.level = level;
+        }
+        if self.notified > assigns.numberOfAssigns() {
+            self.notified = assigns.numberOfAssigns();
+        }
+
+        while let Some(&lit) = self.pending.last() {
+            if !assigns.isUndef(lit.var()) { break; }
+            self.pending.pop();
+        }
+
+        while let Some(&cr) = self.reasons.last() {
+            if assigns.isLocked(ca, cr) { break; }
+            self.reasons.pop();
+            ca.free(cr);
+        }
+    }
+
+    pub fn relocGC(&mut self, from : &mut ClauseAllocator, to : &mut ClauseAllocator) {
+        self.reasons.retain(|&cr| { !from.isDeleted(cr) });
+        for cr in self.reasons.iter_mut() {
+            *cr = from.relocTo(to, *cr);
+        }
+    }
+}
@@ -6,7 +6,8 @@ use sat::formula::clause::Clause;
 pub struct ElimClauses {
     extend_model : bool,
     literals     : Vec<Lit>,
-    sizes        : Vec<usize>
+    sizes        : Vec<usize>,
+    eliminated   : VarMap<()>
 }
 
 impl ElimClauses {
@@ -14,12 +15,19 @@ impl ElimClauses {
         ElimClauses { extend_model : extend_model
                     , literals     : Vec::new()
                     , sizes        : Vec::new()
+                    , eliminated   : VarMap::new()
                     }
     }
 
+    // Unit with the default value closes the record of an eliminated variable:
     pub fn mkElimUnit(&mut self, x : Lit) {
         self.literals.push(x);
         self.sizes.push(1);
+        self.eliminated.insert(&x.var(), ());
+    }
+
+    pub fn isEliminated(&self, v : Var) -> bool {
+        self.eliminated.get(&v).is_some()
     }
 
     pub fn mkElimClause(&mut self, v : Var, c : &Clause) {
@@ -47,6 +55,44 @@ impl ElimClauses {
         self.sizes.push(c.len());
     }
 
+    // Remove the record of eliminated variable 'v' and return the clauses it was keeping:
+    pub fn restore(&mut self, v : Var) -> Vec<Vec<Lit>> {
+        assert!(self.isEliminated(v));
+        self.eliminated.remove(&v);
+
+        // Find the unit closing the record:
+        let mut lit_end = 0;
+        let mut cl_end = 0;
+        while cl_end < self.sizes.len() {
+            lit_end += self.sizes[cl_end];
+            cl_end += 1;
+            if self.sizes[cl_end - 1] == 1 && self.literals[lit_end - 1].var() == v {
+                break;
+            }
+        }
+
+        // Clauses of the record are right before it, the previous record ends with its own unit:
+        let mut cl_begin = cl_end - 1;
+        let mut lit_begin = lit_end - 1;
+        while cl_begin > 0 && self.sizes[cl_begin - 1] > 1 {
+            cl_begin -= 1;
+            lit_begin -= self.sizes[cl_begin];
+        }
+
+        let mut clauses = Vec::new();
+        {
+            let mut i = lit_begin;
+            for &size in self.sizes[cl_begin .. cl_end - 1].iter() {
+                clauses.push(self.literals[i .. i + size].to_vec());
+                i += size;
+            }
+        }
+
+        self.literals.drain(lit_begin .. lit_end);
+        self.sizes.drain(cl_begin .. cl_end);
+        clauses
+    }
+
     pub fn extendModel(&self, model : &mut VarMap<bool>) {
         if !self.extend_model { return; }
 
@@ -56,6 +56,12 @@ impl ElimQueue {
         self.heap.clear();
     }
 
+    pub fn clearOcc(&mut self) {
+        for (_, n) in self.n_occ.iter_mut() {
+            *n = 0;
+        }
+    }
+
     pub fn bumpLitOcc(&mut self, lit : &Lit, delta : isize) {
         self.n_occ[lit] += delta;
 
@@ -90,8 +96,18 @@ impl OccLists {
         self.occs.insert(v, OccLine { occs : Vec::new(), dirty : false });
     }
 
+    // Units of an eliminated variable can still come from learnt clauses, so the line stays:
     pub fn clearVar(&mut self, v : &Var) {
-        self.occs.remove(v);
+        let ref mut ol = self.occs[v];
+        ol.occs = Vec::new();
+        ol.dirty = false;
+    }
+
+    pub fn clearAll(&mut self) {
+        for (_, ol) in self.occs.iter_mut() {
+            ol.occs = Vec::new();
+            ol.dirty = false;
+        }
     }
 
     pub fn pushOcc(&mut self, v : &Var, x : ClauseRef) {
@@ -1,11 +1,13 @@
 use std::borrow::Borrow;
 use std::default::Default;
-use sat::{TotalResult, PartialResult, Solver};
+use std::io;
+use sat::{TotalResult, PartialResult, Formula, Solver, IncrementalSolver, LearnCallback, TerminateCallback};
+use sat::encode;
 use sat::formula::{Var, Lit, VarMap};
 use sat::formula::assignment::*;
 use sat::formula::clause::*;
 use sat::formula::util::*;
-use super::CoreSolver;
+use super::{CoreSolver, InterruptHandle, Proof};
 use self::elim_clauses::*;
 use self::elim_queue::*;
 use self::subsumption_queue::*;
@@ -17,7 +19,8 @@ mod subsumption_queue;
 pub struct Settings {
     pub core         : super::Settings,
     pub simp         : SimpSettings,
-    pub extend_model : bool // Flag to indicate whether the user needs to look at the full model.
+    pub extend_model : bool, // Flag to indicate whether the user needs to look at the full model.
+    pub keep_simp    : bool  // Suspend simplification instead of dropping it, so later calls can resume it.
 }
 
 impl Default for Settings {
@@ -25,7 +28,8 @@ impl Default for Settings {
         Settings {
             core         : Default::default(),
             simp         : Default::default(),
-            extend_model : true
+            extend_model : true,
+            keep_simp    : false
         }
     }
 }
@@ -33,7 +37,8 @@ impl Default for Settings {
 pub struct SimpSolver {
     core        : CoreSolver,
     elimclauses : ElimClauses,
-    simp        : Option<Simplificator>
+    simp        : Option<Simplificator>,
+    keep_simp   : bool
 }
 
 impl Solver for SimpSolver {
@@ -54,13 +59,40 @@ impl Solver for SimpSolver {
     }
 
     fn addClause(&mut self, ps : &[Lit]) -> bool {
+        for lit in ps.iter() {
+            if self.isEliminated(lit.var()) && !self.reintroduce(lit.var()) {
+                return false;
+            }
+        }
+
         match self.simp {
             Some(ref mut simp) => { simp.addClause(&mut self.core, ps) }
             None               => { self.core.addClause(ps) }
         }
     }
 
+    // Variables of XOR constraints are frozen, since elimination only resolves clauses. Encoded ones
+    // are added here instead of in the core, so that elimination sees their clauses:
+    fn addXor(&mut self, lits : &[Lit]) -> bool {
+        if self.core.encodesConstraints() {
+            return encode::xor(self, lits);
+        }
+
+        for lit in lits.iter() {
+            self.setFrozen(lit.var(), true);
+        }
+        self.core.addXor(lits)
+    }
+
     fn preprocess(&mut self) -> bool {
+        if self.core.recoversXors() {
+            for lits in self.core.findXors() {
+                for lit in lits.iter() {
+                    self.setFrozen(lit.var(), true);
+                }
+                if !self.core.addXor_(&lits) { return false; }
+            }
+        }
         self.eliminate(true)
     }
 
@@ -68,15 +100,42 @@ impl Solver for SimpSolver {
         self.core.budget.off();
         match self.solveLimited(&[], true, false) {
                 PartialResult::UnSAT          => { TotalResult::UnSAT }
+                PartialResult::UnSATUnder(_)  => { TotalResult::UnSAT }
                 PartialResult::SAT(model)     => { TotalResult::SAT(model) }
                 PartialResult::Interrupted(_) => { TotalResult::Interrupted }
-                // _                             => { panic!("Impossible happened") }
             }
     }
 
     fn printStats(&self) {
         self.core.printStats();
     }
+
+    // Clauses of eliminated variables are kept aside for extending models, they are not in the core:
+    fn formula(&self) -> Option<Formula> {
+        self.core.formula()
+    }
+
+    fn finishProof(&mut self) -> io::Result<()> {
+        self.core.finishProof()
+    }
+
+    fn setLearnCallback(&mut self, max_len : usize, callback : Option<LearnCallback>) {
+        self.core.setLearnCallback(max_len, callback);
+    }
+
+    fn setTerminateCallback(&mut self, callback : Option<TerminateCallback>) {
+        self.core.setTerminateCallback(callback);
+    }
+}
+
+impl IncrementalSolver for SimpSolver {
+    fn solveUnder(&mut self, assumptions : &[Lit]) -> PartialResult {
+        self.solveLimited(assumptions, true, false)
+    }
+
+    fn groundLits(&self) -> &[Lit] {
+        self.core.groundLits()
+    }
 }
 
 impl SimpSolver {
@@ -87,10 +146,71 @@ impl SimpSolver {
         SimpSolver { core        : core
                    , elimclauses : ElimClauses::new(settings.extend_model)
                    , simp        : Some(Simplificator::new(settings.simp))
+                   , keep_simp   : settings.keep_simp
                    }
     }
 
+    // Frozen variables are never eliminated, so they can be safely used in future clauses and assumptions:
+    pub fn setFrozen(&mut self, v : Var, b : bool) {
+        if b && self.isEliminated(v) {
+            self.reintroduce(v);
+        }
+
+        if let Some(ref mut simp) = self.simp {
+            simp.setFrozen(&self.core, v, b);
+        }
+    }
+
+    pub fn isEliminated(&self, v : Var) -> bool {
+        self.elimclauses.isEliminated(v)
+    }
+
+    // Released variables are recycled only after simplification is turned off, since occurrence
+    // lists and elimination queue may still refer to them:
+    pub fn releaseVar(&mut self, lit : Lit) {
+        if self.core.assigns.isUndef(lit.var()) {
+            self.addClause(&[lit]);
+            if !self.core.watches.isConstrained(lit.var()) {
+                self.core.released_vars.push(lit.var());
+            }
+        }
+    }
+
+    pub fn setProof(&mut self, proof : Proof) {
+        self.core.setProof(proof);
+    }
+
+    pub fn setConfBudget(&mut self, x : u64) {
+        self.core.setConfBudget(x);
+    }
+
+    pub fn setPropBudget(&mut self, x : u64) {
+        self.core.setPropBudget(x);
+    }
+
+    pub fn budgetOff(&mut self) {
+        self.core.budgetOff();
+    }
+
+    pub fn interrupt(&self) {
+        self.core.interrupt();
+    }
+
+    pub fn clearInterrupt(&self) {
+        self.core.clearInterrupt();
+    }
+
+    pub fn interruptHandle(&self) -> InterruptHandle {
+        self.core.interruptHandle()
+    }
+
     pub fn solveLimited(&mut self, assumptions : &[Lit], do_simp : bool, turn_off_simp : bool) -> PartialResult {
+        for lit in assumptions.iter() {
+            if self.isEliminated(lit.var()) {
+                self.reintroduce(lit.var());
+            }
+        }
+
         let mut result =
             match self.simp {
                 Some(ref mut simp) if do_simp => {
@@ -137,8 +257,51 @@ impl SimpSolver {
         result
     }
 
+    // Undo elimination of 'v' by putting its saved clauses back into the database. Those clauses may
+    // mention variables eliminated later than 'v', so they are reintroduced as well:
+    fn reintroduce(&mut self, v : Var) -> bool {
+        let mut clauses = Vec::new();
+        let mut pending = vec![v];
+        while let Some(x) = pending.pop() {
+            if !self.elimclauses.isEliminated(x) { continue; }
+
+            for c in self.elimclauses.restore(x) {
+                for lit in c.iter() {
+                    if self.elimclauses.isEliminated(lit.var()) {
+                        pending.push(lit.var());
+                    }
+                }
+                clauses.push(c);
+            }
+
+            self.core.heur.setDecisionVar(x, true);
+            if let Some(ref mut simp) = self.simp {
+                simp.reintroduceVar(x);
+            }
+        }
+
+        for c in clauses.iter() {
+            // The clauses were never deleted from the proof, so adding them back is trivially valid:
+            self.core.db.proofAdd(c);
+            if !self.addClause(c) {
+                return false;
+            }
+        }
+
+        true
+    }
+
     fn simpOff(&mut self) {
-        if let Some(_) = self.simp {
+        if self.keep_simp {
+            if let Some(ref mut simp) = self.simp {
+                if !simp.suspended {
+                    // Clauses keep their abstractions, occurrence lists will be rebuilt on resume:
+                    simp.suspend(&mut self.core);
+                    self.core.heur.rebuildOrderHeap(&self.core.assigns);
+                    self.core.garbageCollect();
+                }
+            }
+        } else if let Some(_) = self.simp {
             self.simp = None;
             self.core.db.settings.remove_satisfied = true;
             self.core.db.ca.set_extra_clause_field(false);
@@ -187,7 +350,9 @@ struct Simplificator {
     elim              : ElimQueue,
     touched           : VarMap<i8>,
     n_touched         : usize,
-    subsumption_queue : SubsumptionQueue
+    subsumption_queue : SubsumptionQueue,
+    collections       : u64,              // Garbage collections of the core database seen by occurrence lists.
+    suspended         : bool              // Occurrence lists and queues are released until the next 'eliminate()'.
 }
 
 impl Simplificator {
@@ -202,6 +367,8 @@ impl Simplificator {
                       , touched            : VarMap::new()
                       , n_touched          : 0
                       , subsumption_queue  : SubsumptionQueue::new()
+                      , collections        : 0
+                      , suspended          : false
                       }
     }
 
@@ -212,6 +379,20 @@ impl Simplificator {
         self.elim.initVar(v);
     }
 
+    fn reintroduceVar(&mut self, v : Var) {
+        self.var_status[&v].eliminated = 0;
+        self.occurs.initVar(&v);
+        self.touched[&v] = 1;
+        self.n_touched += 1;
+    }
+
+    fn setFrozen(&mut self, core : &CoreSolver, v : Var, b : bool) {
+        self.var_status[&v].frozen = b as i8;
+        if !b {
+            self.elim.updateElimHeap(v, &self.var_status, &core.assigns);
+        }
+    }
+
     fn addClause(&mut self, core : &mut CoreSolver, ps : &[Lit]) -> bool {
         //#ifndef NDEBUG
         for l in ps.iter() {
@@ -222,6 +403,7 @@ impl Simplificator {
         match core.addClause_(ps) {
             super::AddClause::UnSAT     => { false }
             super::AddClause::Consumed  => { true }
+            super::AddClause::Added(_) if self.suspended => { true }
             super::AddClause::Added(cr) => {
                 // NOTE: the clause is added to the queue immediately and then
                 // again during 'gatherTouchedClauses()'. If nothing happens
@@ -277,12 +459,18 @@ impl Simplificator {
     }
 
     fn eliminate(&mut self, core : &mut CoreSolver, elimclauses : &mut ElimClauses) -> bool {
+        if self.suspended {
+            self.resume(core);
+        } else {
+            self.syncWithCore(core);
+        }
+
         // Main simplification loop:
         'cleanup: while self.n_touched > 0 || self.subsumption_queue.assignsLeft(&core.assigns) > 0 || self.elim.len() > 0 {
             self.gatherTouchedClauses(&mut core.db.ca);
 
             if !self.backwardSubsumptionCheck(core, true) {
-                core.ok = false;
+                core.setUnsat();
                 break 'cleanup;
             }
 
@@ -309,7 +497,7 @@ impl Simplificator {
                         let was_frozen = self.var_status[&elim].frozen;
                         self.var_status[&elim].frozen = 1;
                         if !self.asymmVar(core, elim) {
-                            core.ok = false;
+                            core.setUnsat();
                             break 'cleanup;
                         }
                         self.var_status[&elim].frozen = was_frozen;
@@ -318,7 +506,7 @@ impl Simplificator {
                     // At this point, the variable may have been set by assymetric branching, so check it
                     // again. Also, don't eliminate frozen variables:
                     if self.settings.use_elim && core.assigns.isUndef(elim) && self.var_status[&elim].frozen == 0 && !self.eliminateVar(core, elimclauses, elim) {
-                        core.ok = false;
+                        core.setUnsat();
                         break 'cleanup;
                     }
 
@@ -383,12 +571,22 @@ impl Simplificator {
 
         let len = core.db.ca.view(cr).len();
         if len == 2 {
+            if core.db.proof.is_some() {
+                let unit = core.db.ca.view(cr).iter().find(|&lit| lit != l).unwrap();
+                core.db.proofAdd(&[unit]);
+            }
             self.removeClause(core, cr);
             let unit = { let c = core.db.ca.edit(cr); c.strengthen(l); c.head() }; // TODO: it produces clauses of length 1. Not good.
             tryAssignLit(&mut core.assigns, unit, None) && core.watches.propagate(&mut core.db.ca, &mut core.assigns).is_none()
         } else {
             core.watches.unwatchClauseStrict(core.db.ca.view(cr), cr);
+            let old = if core.db.proof.is_some() { Some(core.db.ca.view(cr).iter().collect::<Vec<Lit>>()) } else { None };
             core.db.editClause(cr, |c| { c.strengthen(l); assert!(c.len() == len - 1); });
+            if let Some(old) = old {
+                let new : Vec<Lit> = core.db.ca.view(cr).iter().collect();
+                core.db.proofAdd(&new);
+                core.db.proofDelete(&old);
+            }
             core.watches.watchClause(core.db.ca.view(cr), cr);
 
             self.occurs.removeOcc(&l.var(), cr);
@@ -435,27 +633,25 @@ impl Simplificator {
         core.heur.setDecisionVar(v, false);
         self.eliminated_vars += 1;
 
-        if pos.len() > neg.len() {
-            for &cr in neg.iter() {
-                elimclauses.mkElimClause(v, core.db.ca.view(cr));
-            }
-            elimclauses.mkElimUnit(v.posLit());
-        } else {
-            for &cr in pos.iter() {
-                elimclauses.mkElimClause(v, core.db.ca.view(cr));
-            }
-            elimclauses.mkElimUnit(v.negLit());
+        // Model extension needs only the smaller side, but both are kept to be able to reintroduce 'v':
+        for &cr in cls.iter() {
+            elimclauses.mkElimClause(v, core.db.ca.view(cr));
         }
+        elimclauses.mkElimUnit(if pos.len() > neg.len() { v.posLit() } else { v.negLit() });
 
+        // Eliminated clauses are kept in the proof, since they may be reintroduced later:
+        let proof = core.db.proof.take();
         for &cr in cls.iter() {
             self.removeClause(core, cr);
         }
+        core.db.proof = proof;
 
         // Produce clauses in cross product:
         for &pr in pos.iter() {
             for &nr in neg.iter() {
                 self.merges += 1;
                 if let Some(resolvent) = merge(v, core.db.ca.view(pr), core.db.ca.view(nr)) {
+                    core.db.proofAdd(resolvent.borrow());
                     if !self.addClause(core, resolvent.borrow()) {
                         return false;
                     }
@@ -586,10 +782,69 @@ impl Simplificator {
         self.n_touched = 0;
     }
 
+    // Core solver collects garbage on its own during 'simplify()' and 'search()'. It does not know
+    // about occurrence lists and subsumption queue, so they have to be rebuilt after that:
+    fn syncWithCore(&mut self, core : &CoreSolver) {
+        if self.collections == core.db.collections { return; }
+
+        self.occurs.clearAll();
+        for cr in core.db.clauses() {
+            for lit in core.db.ca.view(cr).iter() {
+                self.occurs.pushOcc(&lit.var(), cr);
+            }
+        }
+
+        // Queued clauses are also reachable through touched variables:
+        self.subsumption_queue.forgetClauses();
+        self.collections = core.db.collections;
+    }
+
+    fn suspend(&mut self, core : &mut CoreSolver) {
+        self.occurs.clearAll();
+        self.subsumption_queue.clear(&core.assigns);
+        self.elim.clear();
+        for (_, touched) in self.touched.iter_mut() {
+            *touched = 0;
+        }
+        self.n_touched = 0;
+
+        // Without occurrence lists satisfied clauses can be removed as usual:
+        core.db.settings.remove_satisfied = true;
+        self.suspended = true;
+    }
+
+    fn resume(&mut self, core : &mut CoreSolver) {
+        core.db.settings.remove_satisfied = false;
+
+        // Clauses might have been shrunk in the meantime, so recalculate abstractions as well:
+        self.elim.clearOcc();
+        let clauses : Vec<ClauseRef> = core.db.clauses().collect();
+        for &cr in clauses.iter() {
+            let c = core.db.ca.edit(cr);
+            c.calcAbstraction();
+            for lit in c.iter() {
+                self.occurs.pushOcc(&lit.var(), cr);
+                self.elim.bumpLitOcc(&lit, 1);
+            }
+            self.subsumption_queue.push(cr);
+        }
+
+        // Units were already applied to the clauses by 'simplify()':
+        self.subsumption_queue.skipAssigns(&core.assigns);
+
+        for (v, _) in self.var_status.iter() {
+            self.elim.updateElimHeap(v, &self.var_status, &core.assigns);
+        }
+
+        self.collections = core.db.collections;
+        self.suspended = false;
+    }
+
     fn garbageCollect(&mut self, core : &mut CoreSolver) {
         let mut to = ClauseAllocator::newForGC(&core.db.ca);
         self.relocAll(&mut core.db.ca, &mut to);
         core.relocAll(to);
+        self.collections = core.db.collections;
     }
 
     fn relocAll(&mut self, from : &mut ClauseAllocator, to : &mut ClauseAllocator) {
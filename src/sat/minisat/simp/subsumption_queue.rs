@@ -60,6 +60,14 @@ impl SubsumptionQueue {
         self.bwdsub_assigns = assigns.numberOfGroundAssigns();
     }
 
+    pub fn skipAssigns(&mut self, assigns : &Assignment) {
+        self.bwdsub_assigns = assigns.numberOfGroundAssigns();
+    }
+
+    pub fn forgetClauses(&mut self) {
+        self.subsumption_queue.clear();
+    }
+
     pub fn remarkQueued(&mut self, ca : &mut ClauseAllocator, src : u32, dst : u32) {
         for &cr in self.subsumption_queue.iter() {
             let c = ca.edit(cr);
@@ -1,6 +1,8 @@
 use sat::formula::{Lit, Var, LitMap};
 use sat::formula::assignment::Assignment;
 use sat::formula::clause::*;
+use super::linear::LinearConstraints;
+use super::xor::XorMatrix;
 
 
 #[derive(Clone, Copy, Debug)]
@@ -19,12 +21,16 @@ struct WatchesLine {
 
 pub struct Watches {
     watches          : LitMap<WatchesLine>,
+    linear           : LinearConstraints,
+    xors             : XorMatrix,
     pub propagations : u64
 }
 
 impl Watches {
     pub fn new() -> Watches {
         Watches { watches      : LitMap::new()
+                , linear       : LinearConstraints::new()
+                , xors         : XorMatrix::new()
                 , propagations : 0
                 }
     }
@@ -46,9 +52,12 @@ impl Watches {
         self.tryClearLit(var.negLit());
     }
 
+    // The line itself stays: learnt clauses may still mention an eliminated variable, and
+    // propagation can pick it as a new watch.
     fn tryClearLit(&mut self, lit : Lit) {
-        if self.watches[&lit].watchers.is_empty() {
-            self.watches.remove(&lit);
+        let ref mut line = self.watches[&lit];
+        if line.watchers.is_empty() {
+            line.watchers = Vec::new();
         }
     }
 
@@ -72,6 +81,36 @@ impl Watches {
         self.watches[&!c1].dirty = true;
     }
 
+    // Linear constraints are propagated next to clauses. Returns 'false' if the constraint can not
+    // be satisfied, see 'LinearConstraints::add':
+    pub fn watchLinear(&mut self, assigns : &mut Assignment, terms : &[(u64, Lit)], bound : u64) -> bool {
+        self.linear.add(assigns, terms, bound)
+    }
+
+    // Returns 'false' if the XOR constraints have no solution anymore, see 'XorMatrix::add':
+    pub fn watchXor(&mut self, assigns : &mut Assignment, lits : &[Lit]) -> bool {
+        self.xors.add(assigns, lits)
+    }
+
+    pub fn numberOfXors(&self) -> usize {
+        self.xors.len()
+    }
+
+    // Linear and XOR constraints keep their variables for good, so these can not be recycled:
+    pub fn isConstrained(&self, var : Var) -> bool {
+        self.linear.occurs(var) || self.xors.occurs(var)
+    }
+
+    pub fn numberOfLinear(&self) -> usize {
+        self.linear.len()
+    }
+
+    // Must follow every rewind of the assignment:
+    pub fn cancel(&mut self, ca : &mut ClauseAllocator, assigns : &Assignment) {
+        self.linear.cancel(ca, assigns);
+        self.xors.cancel(ca, assigns);
+    }
+
     // Description:
     //   Propagates all enqueued facts. If a conflict arises, the conflicting clause is returned,
     //   otherwise CRef_Undef.
@@ -79,88 +118,114 @@ impl Watches {
     //   Post-conditions:
     //     * the propagation queue is empty, even if there was a conflict.
     pub fn propagate(&mut self, ca : &mut ClauseAllocator, assigns : &mut Assignment) -> Option<ClauseRef> {
-        while let Some(p) = assigns.dequeue() {
-            self.propagations += 1;
-            let false_lit = !p;
-
-            {
-                let ref mut line = self.watches[&p];
-                if line.dirty {
-                    line.watchers.retain(|w| { !ca.isDeleted(w.cref) });
-                    line.dirty = false;
+        loop {
+            while let Some(p) = assigns.dequeue() {
+                let confl = self.propagateLit(ca, assigns, p);
+                if confl.is_some() {
+                    return confl;
                 }
             }
 
-            let mut i = 0;
-            let mut j = 0;
-            loop {
-                let (cw, new_watch) = {
-                    let ref mut p_watches = self.watches[&p].watchers;
-                    if i >= p_watches.len() { break; }
-                    let pwi = p_watches[i];
-                    i += 1;
-
-                    if assigns.isSat(pwi.blocker) {
-                        p_watches[j] = pwi;
-                        j += 1;
-                        continue;
-                    }
+            // XOR constraints are propagated at the fixpoint of the others, since elimination is costly:
+            let assigned = assigns.numberOfAssigns();
+            let confl = self.xors.propagate(ca, assigns);
+            if confl.is_some() || assigns.numberOfAssigns() == assigned {
+                return confl;
+            }
+        }
+    }
 
-                    let c = ca.edit(pwi.cref);
-                    if c.head() == false_lit {
-                        c.swap(0, 1);
-                    }
-                    assert!(c[1] == false_lit);
-
-                    // If 0th watch is true, then clause is already satisfied.
-                    let cw = Watcher { cref : pwi.cref, blocker : c.head() };
-                    if cw.blocker != pwi.blocker && assigns.isSat(cw.blocker) {
-                        p_watches[j] = cw;
-                        j += 1;
-                        continue;
-                    }
+    // Visit constraints watching '!p' only, clauses first. Used when the assignment queue is shared
+    // by several sets of watches:
+    pub fn propagateLit(&mut self, ca : &mut ClauseAllocator, assigns : &mut Assignment, p : Lit) -> Option<ClauseRef> {
+        self.propagations += 1;
+        match self.propagateClauses(ca, assigns, p) {
+            None  => { self.linear.propagateLit(ca, assigns, p) }
+            confl => { confl }
+        }
+    }
 
-                    // Look for new watch:
-                    (cw, c.pullLiteral(1, |lit| { !assigns.isUnsat(lit) }))
-                };
+    fn propagateClauses(&mut self, ca : &mut ClauseAllocator, assigns : &mut Assignment, p : Lit) -> Option<ClauseRef> {
+        let false_lit = !p;
 
-                match new_watch {
-                    Some(lit) => {
-                        self.watches[&!lit].watchers.push(cw);
-                    }
+        {
+            let ref mut line = self.watches[&p];
+            if line.dirty {
+                line.watchers.retain(|w| { !ca.isDeleted(w.cref) });
+                line.dirty = false;
+            }
+        }
+
+        let mut i = 0;
+        let mut j = 0;
+        loop {
+            let (cw, new_watch) = {
+                let ref mut p_watches = self.watches[&p].watchers;
+                if i >= p_watches.len() { break; }
+                let pwi = p_watches[i];
+                i += 1;
+
+                if assigns.isSat(pwi.blocker) {
+                    p_watches[j] = pwi;
+                    j += 1;
+                    continue;
+                }
+
+                let c = ca.edit(pwi.cref);
+                if c.head() == false_lit {
+                    c.swap(0, 1);
+                }
+                assert!(c[1] == false_lit);
+
+                // If 0th watch is true, then clause is already satisfied.
+                let cw = Watcher { cref : pwi.cref, blocker : c.head() };
+                if cw.blocker != pwi.blocker && assigns.isSat(cw.blocker) {
+                    p_watches[j] = cw;
+                    j += 1;
+                    continue;
+                }
+
+                // Look for new watch:
+                (cw, c.pullLiteral(1, |lit| { !assigns.isUnsat(lit) }))
+            };
 
-                    // Did not find watch -- clause is unit under assignment:
-                    None      => {
-                        let ref mut p_watches = self.watches[&p].watchers;
-                        p_watches[j] = cw;
-                        j += 1;
-
-                        if assigns.isUnsat(cw.blocker) {
-                            assigns.dequeueAll();
-
-                            // Copy the remaining watches:
-                            while i < p_watches.len() {
-                                p_watches[j] = p_watches[i];
-                                j += 1;
-                                i += 1;
-                            }
-
-                            p_watches.truncate(j);
-                            return Some(cw.cref);
-                        } else {
-                            assigns.assignLit(cw.blocker, Some(cw.cref));
+            match new_watch {
+                Some(lit) => {
+                    self.watches[&!lit].watchers.push(cw);
+                }
+
+                // Did not find watch -- clause is unit under assignment:
+                None      => {
+                    let ref mut p_watches = self.watches[&p].watchers;
+                    p_watches[j] = cw;
+                    j += 1;
+
+                    if assigns.isUnsat(cw.blocker) {
+                        assigns.dequeueAll();
+
+                        // Copy the remaining watches:
+                        while i < p_watches.len() {
+                            p_watches[j] = p_watches[i];
+                            j += 1;
+                            i += 1;
                         }
+
+                        p_watches.truncate(j);
+                        return Some(cw.cref);
+                    } else {
+                        assigns.assignLit(cw.blocker, Some(cw.cref));
                     }
                 }
             }
-
-            self.watches[&p].watchers.truncate(j);
         }
 
+        self.watches[&p].watchers.truncate(j);
         None
     }
 
     pub fn relocGC(&mut self, from : &mut ClauseAllocator, to : &mut ClauseAllocator) {
+        self.linear.relocGC(from, to);
+        self.xors.relocGC(from, to);
         for (_, line) in self.watches.iter_mut() {
             line.dirty = false;
             line.watchers.retain(|w| { !from.isDeleted(w.cref) });
@@ -0,0 +1,315 @@
+use std::{cmp, mem};
+use std::collections::HashMap;
+use sat::formula::{Var, Lit, VarMap};
+use sat::formula::assignment::Assignment;
+use sat::formula::clause::*;
+
+
+#[derive(Clone)]
+struct Row {
+    bits : Vec<u64>, // Bit 'i' stands for column 'i'.
+    rhs  : bool      // Parity of the variables of the row.
+}
+
+impl Row {
+    fn get(&self, col : usize) -> bool {
+        (self.bits[col / 64] >> (col % 64)) & 1 == 1
+    }
+
+    fn flip(&mut self, col : usize) {
+        self.bits[col / 64] ^= 1 << (col % 64);
+    }
+
+    fn add(&mut self, other : &Row) {
+        for (x, y) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *x ^= *y;
+        }
+        self.rhs ^= other.rhs;
+    }
+
+    fn columns<'r>(&'r self) -> Box<Iterator<Item = usize> + 'r> {
+        Box::new(self.bits.iter().enumerate().flat_map(|(i, &word)| {
+            (0 .. 64).filter(move |&b| (word >> b) & 1 == 1).map(move |b| i * 64 + b)
+        }))
+    }
+}
+
+
+// XOR constraints as rows of a matrix over GF(2), kept in reduced row echelon form across calls:
+// every row has a basic column that no other row has, and while the row has unassigned variables
+// its basic variable is one of them. When that gets assigned, the row pivots on another one. So a
+// row with no unassigned variable left and the wrong parity is a conflict, and a row with a single
+// one forces it. Row operations never need to be undone, backtracking only makes rows that were
+// fully assigned pivot again. Every row is a sum of input rows, so the explanation of either is the
+// clause over the assigned variables of the row, like for linear constraints.
+pub struct XorMatrix {
+    vars    : Vec<Var>,            // Column 'i' stands for variable 'vars[i]'.
+    columns : VarMap<usize>,
+    rows    : Vec<Row>,
+    basic   : Vec<usize>,          // Column that row 'i' has alone.
+    full    : Vec<(usize, usize)>, // Rows found with every variable assigned, and the size of the trail then.
+    dirty   : Vec<usize>,          // Rows to check again, since backtracking unassigned some of their variables.
+    head    : usize,               // Assignments on the trail before this one are taken into account.
+    reasons : Vec<ClauseRef>
+}
+
+impl XorMatrix {
+    pub fn new() -> XorMatrix {
+        XorMatrix { vars    : Vec::new()
+                  , columns : VarMap::new()
+                  , rows    : Vec::new()
+                  , basic   : Vec::new()
+                  , full    : Vec::new()
+                  , dirty   : Vec::new()
+                  , head    : 0
+                  , reasons : Vec::new()
+                  }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    // Whether 'v' has a column. Columns are never removed:
+    pub fn occurs(&self, v : Var) -> bool {
+        self.columns.get(&v).is_some()
+    }
+
+    // Add a constraint at ground level, assigning the variables that the matrix determines without
+    // reason. Returns 'false' if the matrix has no solution anymore:
+    pub fn add(&mut self, assigns : &mut Assignment, lits : &[Lit]) -> bool {
+        assert!(assigns.isGroundLevel());
+
+        let mut row = Row { bits : vec![0; self.words()], rhs : true };
+        for &lit in lits.iter() {
+            let col = self.column(lit.var());
+            if row.bits.len() < self.words() { row.bits.push(0); }
+            row.flip(col);
+            row.rhs ^= lit.sign();
+        }
+
+        // Reduced by the other rows, so that their basic columns stay theirs alone:
+        for (r, &col) in self.basic.iter().enumerate() {
+            if row.get(col) { row.add(&self.rows[r]); }
+        }
+
+        if row.bits.iter().all(|&w| w == 0) {
+            return !row.rhs;
+        }
+
+        let col = {
+            let vars = &self.vars;
+            let free = row.columns().find(|&c| assigns.isUndef(vars[c]));
+            free.unwrap_or_else(|| row.columns().next().unwrap())
+        };
+
+        self.rows.push(row);
+        self.basic.push(col);
+        let r = self.rows.len() - 1;
+        let mut changed = self.pivot(r, col);
+        changed.push(r);
+
+        match self.check(assigns, changed) {
+            Ok(units) => {
+                for (lit, _) in units {
+                    assigns.assignLit(lit, None);
+                }
+                true
+            }
+
+            Err(_)    => { false }
+        }
+    }
+
+    // Checks the rows with variables assigned since the last call. The position on the trail only
+    // moves on without a conflict, the rows of a conflict are checked again after backtracking:
+    pub fn propagate(&mut self, ca : &mut ClauseAllocator, assigns : &mut Assignment) -> Option<ClauseRef> {
+        let n = assigns.numberOfAssigns();
+        if self.rows.is_empty() || (self.head == n && self.dirty.is_empty()) {
+            return None;
+        }
+
+        let mut queue = self.dirty.clone();
+        for i in self.head .. n {
+            if let Some(&col) = self.columns.get(&assigns.assignAt(i).var()) {
+                queue.extend((0 .. self.rows.len()).filter(|&r| self.rows[r].get(col)));
+            }
+        }
+
+        match self.check(assigns, queue) {
+            Ok(units) => {
+                self.dirty.clear();
+                self.head = n;
+                for (lit, mut lits) in units {
+                    if lits.is_empty() {
+                        // Only rows on a single variable, which are assigned at ground level:
+                        assert!(assigns.isGroundLevel());
+                        assigns.assignLit(lit, None);
+                    } else {
+                        lits.insert(0, lit);
+                        let (_, cr) = ca.alloc(lits.into_boxed_slice(), false);
+                        self.reasons.push(cr);
+                        assigns.assignLit(lit, Some(cr));
+                    }
+                }
+                None
+            }
+
+            Err(lits) => {
+                assigns.dequeueAll();
+                let (_, cr) = ca.alloc(lits.into_boxed_slice(), false);
+                self.reasons.push(cr);
+                Some(cr)
+            }
+        }
+    }
+
+    // Rows that were fully assigned may have unassigned variables again, and have to pivot on one
+    // of them. Then free the explanations that are no longer locked:
+    pub fn cancel(&mut self, ca : &mut ClauseAllocator, assigns : &Assignment) {
+        let n = assigns.numberOfAssigns();
+        self.head = cmp::min(self.head, n);
+        while let Some(&(len, r)) = self.full.last() {
+            if len <= n { break; }
+            self.full.pop();
+            self.dirty.push(r);
+        }
+
+        while let Some(&cr) = self.reasons.last() {
+            if assigns.isLocked(ca, cr) { break; }
+            self.reasons.pop();
+            ca.free(cr);
+        }
+    }
+
+    pub fn relocGC(&mut self, from : &mut ClauseAllocator, to : &mut ClauseAllocator) {
+        self.reasons.retain(|&cr| { !from.isDeleted(cr) });
+        for cr in self.reasons.iter_mut() {
+            *cr = from.relocTo(to, *cr);
+        }
+    }
+
+    fn words(&self) -> usize {
+        (self.vars.len() + 63) / 64
+    }
+
+    fn column(&mut self, v : Var) -> usize {
+        if let Some(&col) = self.columns.get(&v) {
+            return col;
+        }
+
+        let col = self.vars.len();
+        self.vars.push(v);
+        self.columns.insert(&v, col);
+        if col % 64 == 0 {
+            for row in self.rows.iter_mut() {
+                row.bits.push(0);
+            }
+        }
+        col
+    }
+
+    // Make 'col' the basic column of row 'r', eliminating it from the other rows. Returns the rows
+    // that changed:
+    fn pivot(&mut self, r : usize, col : usize) -> Vec<usize> {
+        self.basic[r] = col;
+        let p = self.rows[r].clone();
+        let mut changed = Vec::new();
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            if i != r && row.get(col) {
+                row.add(&p);
+                changed.push(i);
+            }
+        }
+        changed
+    }
+
+    // Brings the given rows back to an unassigned basic variable where they have one. Returns the
+    // forced literals with the false literals of their rows, or the false literals of a conflicting
+    // row:
+    fn check(&mut self, assigns : &Assignment, mut queue : Vec<usize>) -> Result<Vec<(Lit, Vec<Lit>)>, Vec<Lit>> {
+        let n = assigns.numberOfAssigns();
+        let mut queued = vec![false; self.rows.len()];
+        queue.retain(|&r| !mem::replace(&mut queued[r], true));
+
+        let mut units = Vec::new();
+        while let Some(r) = queue.pop() {
+            queued[r] = false;
+            if !assigns.isUndef(self.vars[self.basic[r]]) {
+                let free = {
+                    let vars = &self.vars;
+                    self.rows[r].columns().find(|&c| assigns.isUndef(vars[c]))
+                };
+
+                if let Some(col) = free {
+                    for i in self.pivot(r, col) {
+                        if !mem::replace(&mut queued[i], true) { queue.push(i); }
+                    }
+                }
+            }
+
+            let mut parity = self.rows[r].rhs;
+            let mut free = Vec::new();
+            let mut lits = Vec::new();
+            for col in self.rows[r].columns() {
+                let v = self.vars[col];
+                if assigns.isUndef(v) {
+                    free.push(v);
+                } else {
+                    let val = assigns.isSat(v.posLit());
+                    parity ^= val;
+                    lits.push(v.lit(val));
+                }
+            }
+
+            // Only the basic variable can be left unassigned alone, and that one is in no other row:
+            match free.len() {
+                0 if parity => { return Err(lits); }
+                0           => { self.full.push((n, r)); }
+                1           => { units.push((free[0].lit(!parity), lits)); }
+                _           => {}
+            }
+        }
+
+        Ok(units)
+    }
+}
+
+
+// XOR constraints written as blocks of clauses: all '2^(k-1)' clauses over the same 'k' variables
+// that exclude the assignments of one parity. Returns them in the form of 'Solver::addXor':
+pub fn recover<I : Iterator<Item = ClauseRef>>(ca : &ClauseAllocator, clauses : I, max_len : usize) -> Vec<Vec<Lit>> {
+    let mut blocks : HashMap<Vec<usize>, (Vec<Var>, Vec<u32>)> = HashMap::new();
+    for cr in clauses {
+        let c = ca.view(cr);
+        if c.len() < 3 || c.len() > max_len { continue; }
+
+        let mut lits : Vec<Lit> = c.iter().collect();
+        lits.sort();
+        let vars : Vec<Var> = lits.iter().map(|lit| lit.var()).collect();
+        if vars.windows(2).any(|w| w[0] == w[1]) { continue; }
+
+        // The clause excludes the assignment with its negative literals true:
+        let mask = lits.iter().enumerate().filter(|&(_, lit)| lit.sign()).fold(0, |m, (i, _)| m | (1 << i));
+        let key = vars.iter().map(|v| v.index()).collect();
+        blocks.entry(key).or_insert((vars, Vec::new())).1.push(mask);
+    }
+
+    let mut xors = Vec::new();
+    for (_, (vars, mut masks)) in blocks.into_iter() {
+        masks.sort();
+        masks.dedup();
+
+        let half = 1 << (vars.len() - 1);
+        for &parity in [0, 1].iter() {
+            let excluded = masks.iter().filter(|&&m| m.count_ones() % 2 == parity).count();
+            if excluded == half {
+                // The excluded assignments have this parity, so the constraint has the other one:
+                let mut lits : Vec<Lit> = vars.iter().map(|v| v.posLit()).collect();
+                if parity == 1 { lits[0] = !lits[0]; }
+                xors.push(lits);
+            }
+        }
+    }
+    xors
+}
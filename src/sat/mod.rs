@@ -1,12 +1,23 @@
+use std::io;
 use sat::formula::{Var, Lit, VarMap};
 
+pub mod aiger;
+pub mod allsat;
+pub mod backbone;
+pub mod bmc;
 pub mod dimacs;
+pub mod encode;
 pub mod formula;
+pub mod maxsat;
 pub mod minisat;
+pub mod mus;
+pub mod opb;
+pub mod proof;
 
 
 pub enum PartialResult {
     UnSAT,
+    UnSATUnder(Vec<Lit>), // Unsatisfiable under the given subset of assumptions.
     SAT(VarMap<bool>),
     Interrupted(f64)
 }
@@ -19,12 +30,57 @@ pub enum TotalResult {
 }
 
 
+// A formula as the solver holds it, to be handed on in DIMACS form:
+pub struct Formula {
+    pub clauses : Vec<Vec<Lit>>, // Irredundant clauses and the units implied at ground level.
+    pub xors    : Vec<Vec<Lit>>  // An odd number of the literals of each is true.
+}
+
+
+// Receives learnt clauses with their LBD, the number of distinct decision levels among the literals:
+pub type LearnCallback = Box<FnMut(&[Lit], usize)>;
+
+// Asked whenever the solver checks its budget. Search stops once it returns true:
+pub type TerminateCallback = Box<FnMut() -> bool>;
+
+
 pub trait Solver {
     fn nVars(&self) -> usize;
     fn nClauses(&self) -> usize;
     fn newVar(&mut self, upol : Option<bool>, dvar : bool) -> Var;
     fn addClause(&mut self, clause : &[Lit]) -> bool;
+
+    // An odd number of the literals is true. Encoded into clauses, unless the solver handles XOR
+    // constraints natively:
+    fn addXor(&mut self, lits : &[Lit]) -> bool where Self : Sized {
+        encode::xor(self, lits)
+    }
+
     fn preprocess(&mut self) -> bool;
     fn solve(&mut self) -> TotalResult;
     fn printStats(&self);
+
+    // The current formula, simplified by whatever the solver has learnt at ground level so far.
+    // None if some of the constraints have no form in clauses or XOR constraints:
+    fn formula(&self) -> Option<Formula>;
+
+    // Write out the rest of the proof log, if there is one. Fails with the first error in writing it:
+    fn finishProof(&mut self) -> io::Result<()>;
+
+    // Only clauses of at most 'max_len' literals are reported, units included:
+    fn setLearnCallback(&mut self, max_len : usize, callback : Option<LearnCallback>);
+    fn setTerminateCallback(&mut self, callback : Option<TerminateCallback>);
+}
+
+
+// Solvers that keep their state between calls and can be solved under assumptions:
+pub trait IncrementalSolver : Solver {
+    fn solveUnder(&mut self, assumptions : &[Lit]) -> PartialResult;
+    fn groundLits(&self) -> &[Lit]; // Literals implied by the clauses alone.
+}
+
+
+// Solvers that propagate linear constraints natively instead of encoding them into clauses:
+pub trait LinearSolver : Solver {
+    fn addAtLeast(&mut self, terms : &[(u64, Lit)], bound : u64) -> bool; // 'sum of coefficient * literal >= bound'
 }
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::default::Default;
+use sat::{PartialResult, Solver};
+use sat::formula::{Var, Lit, VarMap};
+use sat::minisat::{self, CoreSolver, InterruptHandle};
+
+
+pub struct Settings {
+    pub core        : minisat::Settings,
+    pub trim_rounds : usize // Re-solve under the current core at most this many times before deletion starts.
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings { core        : Default::default()
+                 , trim_rounds : 8
+                 }
+    }
+}
+
+
+pub enum MusResult {
+    SAT,
+    Mus(Vec<usize>),        // Labels of the groups in a minimal unsatisfiable subset, sorted.
+    Interrupted(Vec<usize>) // Unsatisfiable, but not necessarily minimal.
+}
+
+
+// Minimal unsatisfiable subset extraction. Every group of clauses is guarded by a selector variable,
+// so groups can be switched on and off through assumptions. Clauses outside of any group are hard
+// and always part of the formula.
+pub struct MusExtractor {
+    solver      : CoreSolver,
+    selectors   : HashMap<usize, Var>,
+    labels      : VarMap<usize>,
+    trim_rounds : usize
+}
+
+impl MusExtractor {
+    pub fn new(settings : Settings) -> MusExtractor {
+        MusExtractor { solver      : CoreSolver::new(settings.core)
+                     , selectors   : HashMap::new()
+                     , labels      : VarMap::new()
+                     , trim_rounds : settings.trim_rounds
+                     }
+    }
+
+    pub fn newVar(&mut self) -> Var {
+        self.solver.newVar(None, true)
+    }
+
+    pub fn addClause(&mut self, group : Option<usize>, clause : &[Lit]) {
+        match group {
+            None        => { self.solver.addClause(clause); }
+            Some(label) => {
+                let sel = self.selector(label);
+                let mut ps = clause.to_vec();
+                ps.push(sel.negLit());
+                self.solver.addClause(&ps);
+            }
+        }
+    }
+
+    pub fn interruptHandle(&self) -> InterruptHandle {
+        self.solver.interruptHandle()
+    }
+
+    pub fn extract(&mut self) -> MusResult {
+        let mut all : Vec<usize> = self.selectors.keys().cloned().collect();
+        all.sort();
+
+        let mut unknown =
+            match self.solveGroups(&all) {
+                Some(Some(core)) => { core }
+                Some(None)       => { return MusResult::SAT; }
+                None             => { return MusResult::Interrupted(all); }
+            };
+
+        // Core-guided trimming, while the final conflict keeps shrinking:
+        for _ in 0 .. self.trim_rounds {
+            match self.solveGroups(&unknown) {
+                Some(Some(core)) => {
+                    if core.len() == unknown.len() { break; }
+                    unknown = core;
+                }
+
+                _                => { return MusResult::Interrupted(unknown); }
+            }
+        }
+
+        // Deletion: a group is necessary iff the formula becomes satisfiable without it. Groups
+        // outside of the final conflict of an unsatisfiable call can be dropped as well:
+        let mut critical = Vec::new();
+        while let Some(g) = unknown.pop() {
+            let mut active = critical.clone();
+            active.extend(unknown.iter().cloned());
+
+            match self.solveGroups(&active) {
+                Some(None)       => { critical.push(g); }
+                Some(Some(core)) => { unknown.retain(|x| core.contains(x)); }
+                None             => {
+                    critical.push(g);
+                    critical.extend(unknown.into_iter());
+                    critical.sort();
+                    return MusResult::Interrupted(critical);
+                }
+            }
+        }
+
+        critical.sort();
+        MusResult::Mus(critical)
+    }
+
+    fn selector(&mut self, label : usize) -> Var {
+        if let Some(&sel) = self.selectors.get(&label) {
+            return sel;
+        }
+
+        let sel = self.solver.newVar(None, true);
+        self.selectors.insert(label, sel);
+        self.labels.insert(&sel, label);
+        sel
+    }
+
+    // Solve with the given groups enabled. Returns the groups in the final conflict if unsatisfiable:
+    fn solveGroups(&mut self, groups : &[usize]) -> Option<Option<Vec<usize>>> {
+        let assumptions : Vec<Lit> = groups.iter().map(|g| self.selectors[g].posLit()).collect();
+        match self.solver.solveLimited(&assumptions) {
+            PartialResult::SAT(_)           => { Some(None) }
+            PartialResult::UnSAT            => { Some(Some(Vec::new())) }
+            PartialResult::UnSATUnder(core) => {
+                let mut core : Vec<usize> = core.iter().map(|lit| self.labels[&lit.var()]).collect();
+                core.sort();
+                Some(Some(core))
+            }
+            PartialResult::Interrupted(_)   => { None }
+        }
+    }
+}
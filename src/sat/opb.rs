@@ -0,0 +1,275 @@
+use std::io;
+use sat::{PartialResult, Solver, IncrementalSolver, LinearSolver};
+use sat::encode::{self, PbEncoding};
+use sat::formula::{Var, Lit, VarMap};
+
+
+pub struct Parsed {
+    pub backward_subst : VarMap<i32>,
+    pub objective      : Option<Vec<(i64, Lit)>> // Terms of the 'min:' line, if there is one.
+}
+
+
+// Linear pseudo-Boolean constraints in the format of the PB competition. Products of literals are
+// replaced by fresh variables defined as their conjunction, so non-linear instances are read too.
+// Every constraint is encoded into clauses right away:
+pub fn parse<R : io::Read, S : Solver>(stream : &mut R, solver : &mut S, encoding : Option<PbEncoding>) -> io::Result<Parsed> {
+    parseWith(stream, solver, |solver, terms, bound| { encode::atLeastPB(solver, terms, bound, encoding) })
+}
+
+
+// Same, but the constraints are handed to the solver as they are:
+pub fn parseNative<R : io::Read, S : LinearSolver>(stream : &mut R, solver : &mut S) -> io::Result<Parsed> {
+    parseWith(stream, solver, |solver, terms, bound| { solver.addAtLeast(terms, bound) })
+}
+
+
+fn parseWith<R, S, F>(stream : &mut R, solver : &mut S, mut add : F) -> io::Result<Parsed>
+    where R : io::Read, S : Solver, F : FnMut(&mut S, &[(u64, Lit)], u64) -> bool {
+    let mut buf = String::new();
+    try!(stream.read_to_string(&mut buf));
+
+    let mut text = String::new();
+    for line in buf.lines() {
+        if !line.starts_with('*') {
+            text.push_str(&line.replace(';', " ; "));
+            text.push('\n');
+        }
+    }
+
+    let mut p = OpbParser { solver : solver, vars : Vec::new(), backward_subst : VarMap::new() };
+    let mut objective = None;
+    let mut tokens = text.split_whitespace().peekable();
+    'parse: while tokens.peek().is_some() {
+        if tokens.peek() == Some(&"min:") {
+            tokens.next();
+            let (terms, _) = try!(p.parseTerms(&mut tokens));
+            try!(expect(tokens.next(), ";"));
+            objective = Some(terms);
+            continue;
+        }
+
+        let (terms, rel) = try!(p.parseTerms(&mut tokens));
+        let rhs = try!(parseInt(tokens.next()));
+        try!(expect(tokens.next(), ";"));
+
+        let bounds =
+            match rel {
+                Some(">=") => { vec![(terms, rhs)] }
+                Some("<=") => { vec![try!(negate(&terms, rhs))] }
+                Some("=")  => { vec![try!(negate(&terms, rhs)), (terms, rhs)] }
+                _          => { return Err(parseError("relation expected")); }
+            };
+
+        // The rest of the input can not make a difference once the solver has found a contradiction:
+        for (terms, rhs) in bounds {
+            if let Some((terms, bound)) = try!(normalize(&terms, rhs)) {
+                if !add(p.solver, &terms, bound) { break 'parse; }
+            }
+        }
+    }
+
+    Ok(Parsed { backward_subst : p.backward_subst
+              , objective      : objective
+              })
+}
+
+
+// Fails if the constraint does not fit into 64 bits once normalized:
+pub fn atLeast<S : Solver>(solver : &mut S, terms : &[(i64, Lit)], rhs : i64, encoding : Option<PbEncoding>) -> io::Result<bool> {
+    match try!(normalize(terms, rhs)) {
+        Some((terms, bound)) => { Ok(encode::atLeastPB(solver, &terms, bound, encoding)) }
+        None                 => { Ok(true) }
+    }
+}
+
+
+pub fn atMost<S : Solver>(solver : &mut S, terms : &[(i64, Lit)], rhs : i64, encoding : Option<PbEncoding>) -> io::Result<bool> {
+    let (negated, rhs) = try!(negate(terms, rhs));
+    atLeast(solver, &negated, rhs, encoding)
+}
+
+
+// Turns '>=' into '<=' and back:
+fn negate(terms : &[(i64, Lit)], rhs : i64) -> io::Result<(Vec<(i64, Lit)>, i64)> {
+    let mut negated = Vec::with_capacity(terms.len());
+    for &(c, lit) in terms.iter() {
+        negated.push((try!(c.checked_neg().ok_or_else(|| parseError("coefficient out of range"))), lit));
+    }
+    Ok((negated, try!(rhs.checked_neg().ok_or_else(|| parseError("right-hand side out of range")))))
+}
+
+
+// A negative term 'c * x' equals 'c + |c| * ¬x'. Returns nothing for a constraint that always holds:
+fn normalize(terms : &[(i64, Lit)], rhs : i64) -> io::Result<Option<(Vec<(u64, Lit)>, u64)>> {
+    let mut bound = rhs;
+    let mut total : u64 = 0; // The encodings add up all coefficients.
+    let mut positive = Vec::with_capacity(terms.len());
+    for &(c, lit) in terms.iter() {
+        if c >= 0 {
+            positive.push((c as u64, lit));
+        } else {
+            positive.push((c.wrapping_neg() as u64, !lit));
+            bound = try!(bound.checked_sub(c).ok_or_else(|| parseError("bound out of range")));
+        }
+        total = try!(total.checked_add(c.wrapping_abs() as u64).ok_or_else(|| parseError("coefficients out of range")));
+    }
+
+    Ok(if bound <= 0 { None } else { Some((positive, bound as u64)) })
+}
+
+
+pub fn value(objective : &[(i64, Lit)], model : &VarMap<bool>) -> i64 {
+    objective.iter().filter(|&&(_, lit)| model.get(&lit.var()).map(|&val| val != lit.sign()).unwrap_or(false)).map(|&(c, _)| c).sum()
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Search {
+    Linear, // Require every next model to be better than the last one, until there is none.
+    Binary  // Bisect between the lower bound and the best model, with the upper half assumed away.
+}
+
+
+pub enum OptResult {
+    UnSAT,
+    Optimum(i64, VarMap<bool>),
+    Interrupted(Option<(i64, VarMap<bool>)>) // Best model found so far, if any.
+}
+
+
+// Every improving model is passed to 'improved' as soon as it is found:
+pub fn minimize<S, F>(solver : &mut S, objective : &[(i64, Lit)], search : Search, encoding : Option<PbEncoding>, mut improved : F) -> OptResult
+    where S : IncrementalSolver, F : FnMut(i64, &VarMap<bool>) -> () {
+    let mut lower : i64 = objective.iter().map(|&(c, _)| if c < 0 { c } else { 0 }).sum();
+    let mut best =
+        match solver.solveUnder(&[]) {
+            PartialResult::SAT(model)     => {
+                let v = value(objective, &model);
+                improved(v, &model);
+                (v, model)
+            }
+
+            PartialResult::Interrupted(_) => { return OptResult::Interrupted(None); }
+            _                             => { return OptResult::UnSAT; }
+        };
+
+    while best.0 > lower {
+        let target =
+            match search {
+                Search::Linear => { best.0 - 1 }
+                Search::Binary => { lower + (best.0 - lower - 1) / 2 }
+            };
+
+        info!("| Objective bounds: [{}, {}], trying {}", lower, best.0, target);
+        // A bound out of range can not be encoded, so the best model stands:
+        let bound =
+            match negate(objective, target).and_then(|(negated, rhs)| normalize(&negated, rhs)) {
+                Ok(Some((terms, bound))) => { encode::atLeastPBLit(solver, &terms, bound, encoding) }
+                _                        => { break; }
+            };
+
+        match solver.solveUnder(&[bound]) {
+            PartialResult::SAT(model)       => {
+                let v = value(objective, &model);
+                improved(v, &model);
+                best = (v, model);
+
+                // Models above the bound are no longer of interest:
+                solver.addClause(&[bound]);
+            }
+
+            PartialResult::UnSATUnder(_)    => { lower = target + 1; }
+            PartialResult::UnSAT            => { break; }
+            PartialResult::Interrupted(_)   => { return OptResult::Interrupted(Some(best)); }
+        }
+    }
+
+    OptResult::Optimum(best.0, best.1)
+}
+
+
+struct OpbParser<'s, S : 's> {
+    solver         : &'s mut S,
+    vars           : Vec<Var>,
+    backward_subst : VarMap<i32>
+}
+
+impl<'s, S : Solver> OpbParser<'s, S> {
+    // Terms up to a relation or the end of the statement, which is returned but not consumed if it
+    // is ';':
+    fn parseTerms<'t, I : Iterator<Item = &'t str>>(&mut self, tokens : &mut ::std::iter::Peekable<I>) -> io::Result<(Vec<(i64, Lit)>, Option<&'t str>)> {
+        let mut terms = Vec::new();
+        loop {
+            match tokens.peek().cloned() {
+                None                                   => { return Err(parseError("unexpected end of input")); }
+                Some(";")                              => { return Ok((terms, None)); }
+                Some(rel @ ">=") | Some(rel @ "<=") |
+                Some(rel @ "=")                        => { tokens.next(); return Ok((terms, Some(rel))); }
+                _                                      => {
+                    let coef = try!(parseInt(tokens.next()));
+                    let mut lits = Vec::new();
+                    while tokens.peek().map(|t| t.starts_with('x') || t.starts_with('~')).unwrap_or(false) {
+                        let lit = try!(self.lit(tokens.next().unwrap()));
+                        lits.push(lit);
+                    }
+
+                    if lits.is_empty() { return Err(parseError("literal expected")); }
+                    let lit = self.product(&lits);
+                    terms.push((coef, lit));
+                }
+            }
+        }
+    }
+
+    fn lit(&mut self, token : &str) -> io::Result<Lit> {
+        let (neg, name) = if token.starts_with('~') { (true, &token[1 ..]) } else { (false, token) };
+        let id : usize =
+            match name[1 ..].parse() {
+                Ok(id) if id > 0 => { id }
+                _                => { return Err(parseError(&format!("unexpected '{}'", token))); }
+            };
+
+        while self.vars.len() < id {
+            let v = self.solver.newVar(None, true);
+            self.backward_subst.insert(&v, (self.vars.len() + 1) as i32);
+            self.vars.push(v);
+        }
+        Ok(self.vars[id - 1].lit(neg))
+    }
+
+    fn product(&mut self, lits : &[Lit]) -> Lit {
+        if lits.len() == 1 { return lits[0]; }
+
+        let y = self.solver.newVar(None, true).posLit();
+        let mut clause = vec![y];
+        for &lit in lits.iter() {
+            self.solver.addClause(&[!y, lit]);
+            clause.push(!lit);
+        }
+        self.solver.addClause(&clause);
+        y
+    }
+}
+
+
+fn parseInt(token : Option<&str>) -> io::Result<i64> {
+    match token {
+        Some(t) => {
+            let t = if t.starts_with('+') { &t[1 ..] } else { t };
+            t.parse().map_err(|_| parseError(&format!("integer expected, found '{}'", t)))
+        }
+
+        None    => { Err(parseError("integer expected")) }
+    }
+}
+
+
+fn expect(token : Option<&str>, target : &str) -> io::Result<()> {
+    if token == Some(target) { Ok(()) } else { Err(parseError(&format!("expected '{}'", target))) }
+}
+
+
+fn parseError(msg : &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("PARSE ERROR! {}", msg))
+}
@@ -0,0 +1,508 @@
+use std::{cmp, io};
+use std::collections::HashMap;
+use sat::dimacs;
+use sat::formula::{Var, Lit, VarMap};
+use sat::formula::assignment::*;
+use sat::formula::clause::*;
+use sat::minisat::watches::Watches;
+use super::{CheckResult, DratStep};
+
+
+// Verify a DRAT proof by backward checking. Only lemmas the refutation depends on are checked, and
+// clauses already known to be needed are preferred during propagation. If 'lrat' is given, the
+// verified part of the proof is written there in LRAT format.
+pub fn checkDrat<R : io::Read, P : io::Read>(cnf : &mut R, proof : &mut P, lrat : Option<&mut io::Write>) -> io::Result<CheckResult> {
+    let mut checker = Checker::new();
+    try!(dimacs::parseClauses(cnf, false, |raw| {
+        let e = checker.addEntry(&raw);
+        checker.activate(e);
+    }));
+
+    let originals = checker.entries.len();
+    let steps = try!(super::readDrat(proof));
+
+    // Forward pass: replay the proof until unit propagation refutes the formula:
+    let mut applied = vec![None; steps.len()];
+    let mut end = 0;
+    checker.fresh();
+    for (i, step) in steps.iter().enumerate() {
+        if checker.conflict.is_some() { break; }
+
+        match *step {
+            DratStep::Add(ref raw)    => {
+                if raw.is_empty() {
+                    return Ok(CheckResult::FailedLemma(i));
+                }
+
+                let e = checker.addEntry(raw);
+                checker.activate(e);
+                applied[i] = Some(e);
+            }
+
+            // Like drat-trim, ignore deletions of unit and reason clauses:
+            DratStep::Delete(ref raw) => {
+                if let Some(e) = checker.find(raw) {
+                    if checker.canDelete(e) {
+                        checker.deactivate(e);
+                        applied[i] = Some(e);
+                    }
+                }
+            }
+        }
+
+        end = i + 1;
+        checker.fresh();
+    }
+
+    let final_hints =
+        match checker.conflict {
+            Some(confl) => { checker.analyze(confl) }
+            None        => { return Ok(CheckResult::NoConflict); }
+        };
+
+    // Backward pass: undo the proof, checking every lemma marked as needed:
+    for i in (0 .. end).rev() {
+        match (&steps[i], applied[i]) {
+            (&DratStep::Add(_), Some(e))    => {
+                checker.deactivate(e);
+                if checker.entries[e].core {
+                    match checker.check(e) {
+                        Some(hints) => { checker.entries[e].hints = Some(hints); }
+                        None        => { return Ok(CheckResult::FailedLemma(i)); }
+                    }
+                }
+            }
+
+            (&DratStep::Delete(_), Some(e)) => { checker.activate(e); }
+
+            _                               => {}
+        }
+    }
+
+    if let Some(out) = lrat {
+        try!(checker.writeLrat(out, &steps[.. end], &applied, originals, &final_hints));
+    }
+
+    Ok(CheckResult::Verified)
+}
+
+
+struct Entry {
+    lits    : Box<[Lit]>,        // As written in the input, so the first literal is the RAT pivot.
+    cref    : Option<ClauseRef>, // Units and empty clauses are kept outside of the allocator.
+    active  : bool,
+    core    : bool,
+    in_core : bool,              // Watched by the core watches.
+    hints   : Option<Vec<i64>>   // LRAT hints of a verified lemma.
+}
+
+
+struct Checker {
+    ca         : ClauseAllocator,
+    assigns    : Assignment,
+    core       : Watches,              // Watches of clauses needed by the refutation, propagated first.
+    other      : Watches,
+    other_head : usize,                // Trail position propagated by 'other'.
+    top        : DecisionLevel,        // Consequences of the current formula live on this level.
+    vars       : Vec<Var>,             // DIMACS id 'n' is 'vars[n - 1]'.
+    seen       : Vec<bool>,
+    assumed    : Vec<bool>,
+    entries    : Vec<Entry>,           // Clause id 'n' is 'entries[n - 1]'.
+    by_ref     : HashMap<ClauseRef, usize>,
+    by_lits    : HashMap<Vec<i32>, Vec<usize>>,
+    units      : Vec<usize>,
+    empties    : Vec<usize>,
+    unit_src   : VarMap<usize>,
+    pending    : Vec<usize>,           // Clauses to be moved to the core watches.
+    dirty      : bool,                 // Consequences must be recomputed from scratch.
+    conflict   : Option<usize>
+}
+
+impl Checker {
+    fn new() -> Checker {
+        Checker { ca         : ClauseAllocator::newEmpty()
+                , assigns    : Assignment::new()
+                , core       : Watches::new()
+                , other      : Watches::new()
+                , other_head : 0
+                , top        : GroundLevel
+                , vars       : Vec::new()
+                , seen       : Vec::new()
+                , assumed    : Vec::new()
+                , entries    : Vec::new()
+                , by_ref     : HashMap::new()
+                , by_lits    : HashMap::new()
+                , units      : Vec::new()
+                , empties    : Vec::new()
+                , unit_src   : VarMap::new()
+                , pending    : Vec::new()
+                , dirty      : true
+                , conflict   : None
+                }
+    }
+
+    fn lit(&mut self, id : i32) -> Lit {
+        while self.vars.len() < (id.abs() as usize) {
+            let v = self.assigns.newVar();
+            self.core.initVar(v);
+            self.other.initVar(v);
+            self.seen.push(false);
+            self.assumed.push(false);
+            self.vars.push(v);
+        }
+
+        self.vars[(id.abs() as usize) - 1].lit(id < 0)
+    }
+
+    fn addEntry(&mut self, raw : &[i32]) -> usize {
+        let mut lits = Vec::with_capacity(raw.len());
+        for &id in raw.iter() {
+            let lit = self.lit(id);
+            if !lits.contains(&lit) {
+                lits.push(lit);
+            }
+        }
+
+        let index = self.entries.len();
+        let cref =
+            match lits.len() {
+                0 => { self.empties.push(index); None }
+                1 => { self.units.push(index); None }
+                _ => {
+                    let (_, cr) = self.ca.alloc(lits.clone().into_boxed_slice(), false);
+                    self.by_ref.insert(cr, index);
+                    Some(cr)
+                }
+            };
+
+        self.by_lits.entry(key(raw)).or_insert_with(Vec::new).push(index);
+        self.entries.push(Entry { lits    : lits.into_boxed_slice()
+                                , cref    : cref
+                                , active  : false
+                                , core    : false
+                                , in_core : false
+                                , hints   : None
+                                });
+        index
+    }
+
+    fn find(&self, raw : &[i32]) -> Option<usize> {
+        self.by_lits.get(&key(raw)).and_then(|es| {
+            es.iter().rev().cloned().find(|&e| self.entries[e].active)
+        })
+    }
+
+    fn canDelete(&self, e : usize) -> bool {
+        match self.entries[e].cref {
+            Some(cr) => { !self.assigns.isLocked(&self.ca, cr) }
+            None     => { false }
+        }
+    }
+
+    fn activate(&mut self, e : usize) {
+        self.entries[e].active = true;
+        match self.entries[e].cref {
+            Some(cr) => {
+                if self.dirty || self.conflict.is_some() || !self.arrange(cr) {
+                    self.dirty = true;
+                }
+
+                let in_core = self.entries[e].core;
+                self.entries[e].in_core = in_core;
+                if in_core {
+                    self.core.watchClause(self.ca.view(cr), cr);
+                } else {
+                    self.other.watchClause(self.ca.view(cr), cr);
+                }
+            }
+
+            None     => { self.dirty = true; }
+        }
+    }
+
+    fn deactivate(&mut self, e : usize) {
+        self.entries[e].active = false;
+        if self.conflict.is_some() {
+            self.dirty = true;
+        }
+
+        match self.entries[e].cref {
+            Some(cr) => {
+                if self.assigns.isLocked(&self.ca, cr) {
+                    self.dirty = true;
+                }
+
+                if self.entries[e].in_core {
+                    self.core.unwatchClauseStrict(self.ca.view(cr), cr);
+                } else {
+                    self.other.unwatchClauseStrict(self.ca.view(cr), cr);
+                }
+            }
+
+            None     => {
+                match self.entries[e].lits.first() {
+                    Some(&lit) if !self.assigns.isSat(lit) || self.unit_src.get(&lit.var()) != Some(&e) => {}
+                    _                                                                                => { self.dirty = true; }
+                }
+            }
+        }
+    }
+
+    // Move literals to the watched positions so that the clause can be attached without propagation.
+    // Fails if the clause is unit or falsified under the current consequences:
+    fn arrange(&mut self, cr : ClauseRef) -> bool {
+        let c = self.ca.edit(cr);
+        let mut k = 0;
+        for i in 0 .. c.len() {
+            if !self.assigns.isUnsat(c[i]) {
+                c.swap(i, k);
+                k += 1;
+                if k == 2 { return true; }
+            }
+        }
+
+        k == 1 && self.assigns.isSat(c[0])
+    }
+
+    fn fresh(&mut self) {
+        if !self.dirty { return; }
+
+        self.assigns.rewindUntilLevel(GroundLevel, |_, _| {});
+        self.assigns.newDecisionLevel();
+        self.top = self.assigns.decisionLevel();
+        self.other_head = 0;
+        self.dirty = false;
+        self.conflict = None;
+
+        for &e in self.empties.iter() {
+            if self.entries[e].active {
+                self.conflict = Some(e);
+                return;
+            }
+        }
+
+        for &e in self.units.iter() {
+            if !self.entries[e].active { continue; }
+
+            let lit = self.entries[e].lits[0];
+            if self.assigns.isUnsat(lit) {
+                self.conflict = Some(e);
+                return;
+            } else if self.assigns.isUndef(lit.var()) {
+                self.assigns.assignLit(lit, None);
+                self.unit_src.insert(&lit.var(), e);
+            }
+        }
+
+        self.conflict = self.propagate().map(|cr| self.by_ref[&cr]);
+    }
+
+    fn propagate(&mut self) -> Option<ClauseRef> {
+        loop {
+            if let Some(cr) = self.core.propagate(&mut self.ca, &mut self.assigns) {
+                return Some(cr);
+            }
+
+            if self.other_head < self.assigns.numberOfAssigns() {
+                let p = self.assigns.assignAt(self.other_head);
+                self.other_head += 1;
+                if let Some(cr) = self.other.propagateLit(&mut self.ca, &mut self.assigns, p) {
+                    return Some(cr);
+                }
+            } else {
+                return None;
+            }
+        }
+    }
+
+    fn backtrack(&mut self) {
+        self.assigns.rewindUntilLevel(self.top, |_, _| {});
+        self.other_head = cmp::min(self.other_head, self.assigns.numberOfAssigns());
+
+        while let Some(e) = self.pending.pop() {
+            let ref mut entry = self.entries[e];
+            if let Some(cr) = entry.cref {
+                if entry.active && !entry.in_core {
+                    self.other.unwatchClauseStrict(self.ca.view(cr), cr);
+                    self.core.watchClause(self.ca.view(cr), cr);
+                    entry.in_core = true;
+                }
+            }
+        }
+    }
+
+    fn markCore(&mut self, e : usize) {
+        if !self.entries[e].core {
+            self.entries[e].core = true;
+            self.pending.push(e);
+        }
+    }
+
+    fn reason(&self, v : Var) -> Option<usize> {
+        let vd = self.assigns.vardata(v);
+        match vd.reason {
+            Some(cr)                     => { Some(self.by_ref[&cr]) }
+            None if vd.level == self.top => { self.unit_src.get(&v).cloned() }
+            None                         => { None }
+        }
+    }
+
+    // Mark the clauses involved in the conflict as needed. Returns them as LRAT hints, in the order
+    // they become unit, followed by the conflicting clause:
+    fn analyze(&mut self, confl : usize) -> Vec<i64> {
+        self.markCore(confl);
+        for lit in self.entries[confl].lits.iter() {
+            self.seen[lit.var().index()] = true;
+        }
+
+        let mut used = Vec::new();
+        let mut i = self.assigns.numberOfAssigns();
+        while i > 0 {
+            i -= 1;
+            let v = self.assigns.assignAt(i).var();
+            if !self.seen[v.index()] { continue; }
+            self.seen[v.index()] = false;
+            if self.assumed[v.index()] { continue; }
+
+            if let Some(r) = self.reason(v) {
+                if r == confl { continue; }
+
+                self.markCore(r);
+                used.push(r);
+                for lit in self.entries[r].lits.iter() {
+                    if lit.var() != v {
+                        self.seen[lit.var().index()] = true;
+                    }
+                }
+            }
+        }
+
+        used.reverse();
+        used.push(confl);
+        used.into_iter().map(|r| (r + 1) as i64).collect()
+    }
+
+    // Check that the lemma is implied by the current formula, either by unit propagation or as a
+    // resolution asymmetric tautology on its first literal:
+    fn check(&mut self, e : usize) -> Option<Vec<i64>> {
+        let lits = self.entries[e].lits.to_vec();
+        match self.rup(&lits) {
+            Some(hints) => { return Some(hints); }
+            None        => {}
+        }
+
+        let pivot = match lits.first() { Some(&p) => p, None => { return None; } };
+        let candidates : Vec<usize> = (0 .. self.entries.len()).filter(|&d| {
+            self.entries[d].active && self.entries[d].lits.contains(&!pivot)
+        }).collect();
+
+        let mut hints = Vec::new();
+        for d in candidates {
+            let mut resolvent = lits.clone();
+            for &lit in self.entries[d].lits.iter() {
+                if lit != !pivot && !resolvent.contains(&lit) {
+                    resolvent.push(lit);
+                }
+            }
+
+            match self.rup(&resolvent) {
+                Some(chain) => {
+                    self.markCore(d);
+                    hints.push(-((d + 1) as i64));
+                    hints.extend(chain);
+                }
+
+                None        => { return None; }
+            }
+        }
+
+        Some(hints)
+    }
+
+    fn rup(&mut self, lits : &[Lit]) -> Option<Vec<i64>> {
+        self.fresh();
+        if let Some(confl) = self.conflict {
+            return Some(self.analyze(confl));
+        }
+
+        if isTautology(lits) {
+            return Some(Vec::new());
+        }
+
+        self.assigns.newDecisionLevel();
+        for lit in lits.iter() {
+            self.assumed[lit.var().index()] = true;
+        }
+
+        let mut confl = None;
+        for &lit in lits.iter() {
+            if self.assigns.isSat(lit) {
+                confl = self.reason(lit.var());
+                break;
+            } else if self.assigns.isUndef(lit.var()) {
+                self.assigns.assignLit(!lit, None);
+            }
+        }
+
+        if confl.is_none() {
+            confl = self.propagate().map(|cr| self.by_ref[&cr]);
+        }
+
+        let hints = confl.map(|c| self.analyze(c));
+        for lit in lits.iter() {
+            self.assumed[lit.var().index()] = false;
+        }
+        self.backtrack();
+        hints
+    }
+
+    fn writeLrat(&self, out : &mut io::Write, steps : &[DratStep], applied : &[Option<usize>], originals : usize, final_hints : &[i64]) -> io::Result<()> {
+        let mut last_id = originals;
+        for (step, &e) in steps.iter().zip(applied.iter()) {
+            match (step, e) {
+                (&DratStep::Add(_), Some(e)) => {
+                    if let Some(ref hints) = self.entries[e].hints {
+                        try!(write!(out, "{} ", e + 1));
+                        for lit in self.entries[e].lits.iter() {
+                            let id = (lit.var().index() + 1) as i64;
+                            try!(write!(out, "{} ", if lit.sign() { -id } else { id }));
+                        }
+                        try!(writeHints(out, hints));
+                        last_id = e + 1;
+                    }
+                }
+
+                (&DratStep::Delete(_), Some(e)) if e < originals || self.entries[e].hints.is_some() => {
+                    try!(writeln!(out, "{} d {} 0", last_id, e + 1));
+                }
+
+                _                            => {}
+            }
+        }
+
+        try!(write!(out, "{} ", self.entries.len() + 1));
+        writeHints(out, final_hints)
+    }
+}
+
+
+fn writeHints(out : &mut io::Write, hints : &[i64]) -> io::Result<()> {
+    try!(write!(out, "0"));
+    for hint in hints.iter() {
+        try!(write!(out, " {}", hint));
+    }
+    writeln!(out, " 0")
+}
+
+
+fn key(raw : &[i32]) -> Vec<i32> {
+    let mut k = raw.to_vec();
+    k.sort();
+    k.dedup();
+    k
+}
+
+
+fn isTautology(lits : &[Lit]) -> bool {
+    lits.iter().any(|&lit| lits.contains(&!lit))
+}
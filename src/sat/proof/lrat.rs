@@ -0,0 +1,160 @@
+use std::io;
+use std::collections::HashMap;
+use sat::dimacs;
+use super::{CheckResult, LratStep};
+
+
+// Verify an LRAT proof. Every lemma must be derived by unit propagation over its hints, or as a
+// resolution asymmetric tautology with a group of hints for every clause containing the negated
+// first literal:
+pub fn checkLrat<R : io::Read, P : io::Read>(cnf : &mut R, proof : &mut P) -> io::Result<CheckResult> {
+    let mut clauses = HashMap::new();
+    try!(dimacs::parseClauses(cnf, false, |raw| {
+        let id = (clauses.len() + 1) as i64;
+        clauses.insert(id, raw);
+    }));
+
+    let steps = try!(super::readLrat(proof));
+    let mut values = Values { val : Vec::new(), trail : Vec::new() };
+    for (i, step) in steps.into_iter().enumerate() {
+        match step {
+            LratStep::Delete(ids)          => {
+                for id in ids.iter() {
+                    clauses.remove(id);
+                }
+            }
+
+            LratStep::Add(id, lits, hints) => {
+                if !values.implied(&clauses, &lits, &hints) {
+                    return Ok(CheckResult::FailedLemma(i));
+                }
+
+                if lits.is_empty() {
+                    return Ok(CheckResult::Verified);
+                }
+                clauses.insert(id, lits);
+            }
+        }
+    }
+
+    Ok(CheckResult::NoConflict)
+}
+
+
+enum Hint { Unit, Conflict, Invalid }
+
+
+struct Values {
+    val   : Vec<i8>, // Indexed by DIMACS variable id: 1 is true, -1 is false.
+    trail : Vec<usize>
+}
+
+impl Values {
+    fn value(&self, lit : i32) -> i8 {
+        let v = lit.abs() as usize;
+        let val = if v < self.val.len() { self.val[v] } else { 0 };
+        if lit < 0 { -val } else { val }
+    }
+
+    fn assign(&mut self, lit : i32) {
+        let v = lit.abs() as usize;
+        if v >= self.val.len() {
+            self.val.resize(v + 1, 0);
+        }
+        self.val[v] = if lit < 0 { -1 } else { 1 };
+        self.trail.push(v);
+    }
+
+    fn undo(&mut self, to : usize) {
+        while self.trail.len() > to {
+            let v = self.trail.pop().unwrap();
+            self.val[v] = 0;
+        }
+    }
+
+    fn implied(&mut self, clauses : &HashMap<i64, Vec<i32>>, lits : &[i32], hints : &[i64]) -> bool {
+        let base = self.trail.len();
+        let res = self.checkLemma(clauses, lits, hints);
+        self.undo(base);
+        res
+    }
+
+    fn checkLemma(&mut self, clauses : &HashMap<i64, Vec<i32>>, lits : &[i32], hints : &[i64]) -> bool {
+        for &lit in lits.iter() {
+            match self.value(lit) {
+                1 => { return true; } // Tautology.
+                0 => { self.assign(-lit); }
+                _ => {}
+            }
+        }
+
+        let mut i = 0;
+        while i < hints.len() && hints[i] > 0 {
+            match self.hint(clauses, hints[i]) {
+                Hint::Unit     => { i += 1; }
+                Hint::Conflict => { return true; }
+                Hint::Invalid  => { return false; }
+            }
+        }
+
+        // No conflict from unit hints, so every clause containing the negated pivot needs its own group:
+        let pivot = match lits.first() { Some(&p) => p, None => { return false; } };
+        let mut groups = HashMap::new();
+        while i < hints.len() {
+            let start = i + 1;
+            i = start;
+            while i < hints.len() && hints[i] > 0 { i += 1; }
+            groups.insert(-hints[start - 1], &hints[start .. i]);
+        }
+
+        for (id, clause) in clauses.iter() {
+            if !clause.contains(&-pivot) { continue; }
+
+            let group = match groups.get(id) { Some(group) => group, None => { return false; } };
+            let base = self.trail.len();
+            let ok = self.checkGroup(clauses, clause, pivot, group);
+            self.undo(base);
+            if !ok { return false; }
+        }
+
+        true
+    }
+
+    fn checkGroup(&mut self, clauses : &HashMap<i64, Vec<i32>>, clause : &[i32], pivot : i32, group : &[i64]) -> bool {
+        for &lit in clause.iter() {
+            if lit == -pivot { continue; }
+            match self.value(lit) {
+                1 => { return true; } // Resolvent is a tautology.
+                0 => { self.assign(-lit); }
+                _ => {}
+            }
+        }
+
+        for &id in group.iter() {
+            match self.hint(clauses, id) {
+                Hint::Unit     => {}
+                Hint::Conflict => { return true; }
+                Hint::Invalid  => { return false; }
+            }
+        }
+
+        false
+    }
+
+    fn hint(&mut self, clauses : &HashMap<i64, Vec<i32>>, id : i64) -> Hint {
+        let clause = match clauses.get(&id) { Some(c) => c, None => { return Hint::Invalid; } };
+        let mut unit = None;
+        for &lit in clause.iter() {
+            match self.value(lit) {
+                -1                                       => {}
+                0 if unit.is_none() || unit == Some(lit) => { unit = Some(lit); }
+                _                                        => { return Hint::Invalid; }
+            }
+        }
+
+        match unit {
+            Some(lit) => { self.assign(lit); Hint::Unit }
+            None      => { Hint::Conflict }
+        }
+    }
+}
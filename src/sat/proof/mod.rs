@@ -0,0 +1,183 @@
+use std::{io, str};
+pub use self::drat::checkDrat;
+pub use self::lrat::checkLrat;
+
+mod drat;
+mod lrat;
+
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CheckResult {
+    Verified,
+    NoConflict,        // The proof never refutes the formula.
+    FailedLemma(usize) // Zero-based index of the first proof step that could not be verified.
+}
+
+
+enum DratStep {
+    Add(Vec<i32>),
+    Delete(Vec<i32>)
+}
+
+
+enum LratStep {
+    Add(i64, Vec<i32>, Vec<i64>),
+    Delete(Vec<i64>)
+}
+
+
+// Binary proofs start with an 'a' or 'd' byte directly followed by a literal, text ones never do:
+fn isBinary(buf : &[u8]) -> bool {
+    match (buf.get(0), buf.get(1)) {
+        (Some(&b'a'), _)                   => { true }
+        (Some(&b'd'), Some(&c)) if c > 32  => { true }
+        _                                  => { false }
+    }
+}
+
+
+fn readDrat<R : io::Read>(stream : &mut R) -> io::Result<Vec<DratStep>> {
+    let mut buf = Vec::new();
+    try!(stream.read_to_end(&mut buf));
+
+    let mut steps = Vec::new();
+    if isBinary(&buf) {
+        let mut pos = 0;
+        while pos < buf.len() {
+            let tag = buf[pos];
+            pos += 1;
+
+            let mut lits = Vec::new();
+            loop {
+                let mut u : u64 = 0;
+                let mut shift = 0;
+                loop {
+                    let byte = match buf.get(pos) { Some(&b) => b, None => { return Err(parseError("unexpected end of binary proof")); } };
+                    pos += 1;
+                    u |= ((byte & 127) as u64) << shift;
+                    shift += 7;
+                    if byte < 128 { break; }
+                }
+
+                if u == 0 { break; }
+                let id = (u >> 1) as i32;
+                lits.push(if u & 1 == 1 { -id } else { id });
+            }
+
+            match tag {
+                b'a' => { steps.push(DratStep::Add(lits)); }
+                b'd' => { steps.push(DratStep::Delete(lits)); }
+                _    => { return Err(parseError("unknown binary proof step")); }
+            }
+        }
+    } else {
+        let mut tokens = Tokens { buf : &buf, pos : 0 };
+        while let Some(tok) = tokens.next() {
+            let delete = tok == b"d";
+            let mut lits = Vec::new();
+            let mut cur = if delete { tokens.next() } else { Some(tok) };
+            loop {
+                let lit = try!(parseInt(cur)) as i32;
+                if lit == 0 { break; }
+                lits.push(lit);
+                cur = tokens.next();
+            }
+
+            steps.push(if delete { DratStep::Delete(lits) } else { DratStep::Add(lits) });
+        }
+    }
+
+    Ok(steps)
+}
+
+
+fn readLrat<R : io::Read>(stream : &mut R) -> io::Result<Vec<LratStep>> {
+    let mut buf = Vec::new();
+    try!(stream.read_to_end(&mut buf));
+
+    let mut steps = Vec::new();
+    let mut tokens = Tokens { buf : &buf, pos : 0 };
+    while let Some(tok) = tokens.next() {
+        let id = try!(parseInt(Some(tok)));
+        match tokens.next() {
+            Some(t) if t == b"d" => {
+                let mut ids = Vec::new();
+                loop {
+                    let x = try!(parseInt(tokens.next()));
+                    if x == 0 { break; }
+                    ids.push(x);
+                }
+                steps.push(LratStep::Delete(ids));
+            }
+
+            cur                  => {
+                let mut lits = Vec::new();
+                let mut cur = cur;
+                loop {
+                    let lit = try!(parseInt(cur)) as i32;
+                    if lit == 0 { break; }
+                    lits.push(lit);
+                    cur = tokens.next();
+                }
+
+                let mut hints = Vec::new();
+                loop {
+                    let x = try!(parseInt(tokens.next()));
+                    if x == 0 { break; }
+                    hints.push(x);
+                }
+                steps.push(LratStep::Add(id, lits, hints));
+            }
+        }
+    }
+
+    Ok(steps)
+}
+
+
+fn parseInt(tok : Option<&[u8]>) -> io::Result<i64> {
+    tok.and_then(|t| str::from_utf8(t).ok())
+       .and_then(|s| s.parse().ok())
+       .ok_or_else(|| parseError("int expected"))
+}
+
+
+fn parseError(msg : &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("PARSE ERROR! {}", msg))
+}
+
+
+// Whitespace separated tokens, skipping comment lines:
+struct Tokens<'a> {
+    buf : &'a [u8],
+    pos : usize
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        loop {
+            while self.pos < self.buf.len() && (self.buf[self.pos] as char).is_whitespace() {
+                self.pos += 1;
+            }
+
+            if self.pos >= self.buf.len() {
+                return None;
+            }
+
+            if self.buf[self.pos] == b'c' {
+                while self.pos < self.buf.len() && self.buf[self.pos] != b'\n' {
+                    self.pos += 1;
+                }
+                continue;
+            }
+
+            let start = self.pos;
+            while self.pos < self.buf.len() && !(self.buf[self.pos] as char).is_whitespace() {
+                self.pos += 1;
+            }
+            return Some(&self.buf[start .. self.pos]);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+extern crate minisat_rust;
+
+use std::io::Cursor;
+use minisat_rust::sat::{PartialResult, Solver};
+use minisat_rust::sat::aiger::{self, Aig, Reset};
+use minisat_rust::sat::bmc::{self, BmcResult};
+use minisat_rust::sat::formula::Lit;
+use minisat_rust::sat::minisat::CoreSolver;
+
+
+// Two bit counter that increments while input 'e' is set, bad once both bits are set:
+const COUNTER : &'static str = "aag 11 1 2 0 8 1
+2
+4 13
+6 21
+22
+8 4 3
+10 5 2
+12 11 9
+14 4 2
+16 15 6
+18 14 7
+20 19 17
+22 6 4
+";
+
+
+// Same circuit in the binary format, assuming the gates are ordered as the format requires:
+fn to_binary(aig : &Aig) -> Vec<u8> {
+    let mut out = format!("aig {} {} {} {} {} {}\n", aig.maxvar, aig.inputs.len(), aig.latches.len(), aig.outputs.len(), aig.ands.len(), aig.bad.len()).into_bytes();
+    for l in aig.latches.iter() {
+        out.extend(format!("{}\n", l.next).into_bytes());
+    }
+    for &lit in aig.outputs.iter().chain(aig.bad.iter()) {
+        out.extend(format!("{}\n", lit).into_bytes());
+    }
+    for g in aig.ands.iter() {
+        for &delta in [g.lhs - g.rhs0, g.rhs0 - g.rhs1].iter() {
+            let mut x = delta;
+            while x >= 0x80 {
+                out.push((x & 0x7f) as u8 | 0x80);
+                x >>= 7;
+            }
+            out.push(x as u8);
+        }
+    }
+    out.extend(b"c\nsome comment\n".iter());
+    out
+}
+
+
+// Values of all variables in one step, gates in file order:
+fn simulate(aig : &Aig, inputs : &[bool], latches : &[bool]) -> Vec<bool> {
+    let mut vals = vec![false; aig.maxvar + 1];
+    for (&i, &val) in aig.inputs.iter().zip(inputs.iter()) { vals[(i >> 1) as usize] = val; }
+    for (l, &val) in aig.latches.iter().zip(latches.iter()) { vals[(l.lit >> 1) as usize] = val; }
+    for g in aig.ands.iter() {
+        vals[(g.lhs >> 1) as usize] = value(&vals, g.rhs0) && value(&vals, g.rhs1);
+    }
+    vals
+}
+
+
+fn value(vals : &[bool], lit : u32) -> bool {
+    vals[(lit >> 1) as usize] != (lit & 1 == 1)
+}
+
+
+#[test]
+fn ascii_and_binary_agree() {
+    let aig = aiger::parse(&mut Cursor::new(COUNTER.as_bytes())).unwrap();
+    assert_eq!((aig.inputs.len(), aig.latches.len(), aig.ands.len(), aig.bad.len()), (1, 2, 8, 1));
+    assert!(aig.latches.iter().all(|l| l.reset == Reset::Zero));
+
+    let bin = aiger::parse(&mut Cursor::new(to_binary(&aig))).unwrap();
+    assert_eq!(bin.inputs, aig.inputs);
+    assert_eq!(bin.bad, aig.bad);
+    assert_eq!(bin.latches.iter().map(|l| (l.lit, l.next)).collect::<Vec<_>>(), aig.latches.iter().map(|l| (l.lit, l.next)).collect::<Vec<_>>());
+    assert_eq!(bin.ands.iter().map(|g| (g.lhs, g.rhs0, g.rhs1)).collect::<Vec<_>>(), aig.ands.iter().map(|g| (g.lhs, g.rhs0, g.rhs1)).collect::<Vec<_>>());
+
+    // Deltas of more than seven bits:
+    let mut wide = String::from("aag 101 100 0 1 1\n");
+    for i in 1 .. 101 { wide.push_str(&format!("{}\n", 2 * i)); }
+    wide.push_str("202\n202 200 3\n");
+    let wide = aiger::parse(&mut Cursor::new(wide.as_bytes())).unwrap();
+    let bin = aiger::parse(&mut Cursor::new(to_binary(&wide))).unwrap();
+    assert_eq!((bin.ands[0].lhs, bin.ands[0].rhs0, bin.ands[0].rhs1), (202, 200, 3));
+    assert_eq!(bin.outputs, vec![202]);
+
+    for bad in ["aag 1 0 0 0 0 0 0 1 0\n", "aag 1 1 0 1 0\n2\n4\n", "aag 2 1 0 0 1\n2\n4 2 4\n", "aig 2 1 0 0 0\n"].iter() {
+        assert!(aiger::parse(&mut Cursor::new(bad.as_bytes())).is_err(), "{}", bad);
+    }
+}
+
+
+#[test]
+fn tseitin_matches_simulation() {
+    let aig = aiger::parse(&mut Cursor::new(COUNTER.as_bytes())).unwrap();
+    let mut solver = CoreSolver::new(Default::default());
+    let inputs : Vec<Lit> = (0 .. 1).map(|_| solver.newVar(None, true).posLit()).collect();
+    let latches : Vec<Lit> = (0 .. 2).map(|_| solver.newVar(None, true).posLit()).collect();
+    let frame = aig.encode(&mut solver, &inputs, &latches);
+
+    for mask in 0 .. 8 {
+        let (e, b0, b1) = (mask & 1 != 0, mask & 2 != 0, mask & 4 != 0);
+        let vals = simulate(&aig, &[e], &[b0, b1]);
+        let assumptions = [inputs[0].var().lit(!e), latches[0].var().lit(!b0), latches[1].var().lit(!b1)];
+        match solver.solveLimited(&assumptions) {
+            PartialResult::SAT(model) => {
+                let lits = aig.latches.iter().map(|l| l.next).chain(aig.bad.iter().cloned());
+                for lit in lits {
+                    let x = frame.lit(lit);
+                    assert_eq!(model[&x.var()] != x.sign(), value(&vals, lit));
+                }
+            }
+            _                         => { panic!("expected SAT"); }
+        }
+    }
+}
+
+
+#[test]
+fn counterexample_is_a_trace() {
+    let aig = aiger::parse(&mut Cursor::new(COUNTER.as_bytes())).unwrap();
+    match bmc::check(&aig, &mut CoreSolver::new(Default::default()), None) {
+        BmcResult::Counterexample(trace) => {
+            assert_eq!(trace.inputs.len(), 4);
+            let mut state = vec![false, false];
+            for (k, step) in trace.inputs.iter().enumerate() {
+                let vals = simulate(&aig, step, &state);
+                assert_eq!(value(&vals, aig.bad[0]), k == 3);
+                state = aig.latches.iter().map(|l| value(&vals, l.next)).collect();
+            }
+
+            let mut out = Vec::new();
+            bmc::writeWitness(&mut out, &aig, &trace).unwrap();
+            let text = String::from_utf8(out).unwrap();
+            assert!(text.starts_with("1\nb0\n00\n1\n1\n1\n"));
+            assert!(text.ends_with("\n.\n"));
+            assert_eq!(text.lines().count(), 8);
+        }
+        _                                => { panic!("expected a counterexample"); }
+    }
+
+    match bmc::check(&aig, &mut CoreSolver::new(Default::default()), Some(2)) {
+        BmcResult::Bounded(2) => {}
+        _                     => { panic!("expected no counterexample up to depth 2"); }
+    }
+}
+
+
+#[test]
+fn constraints_and_uninitialized_latches() {
+    // The counter never moves if 'e' is constrained to be unset:
+    let constrained = COUNTER.replacen("aag 11 1 2 0 8 1\n", "aag 11 1 2 0 8 1 1\n", 1).replacen("\n22\n8", "\n22\n3\n8", 1);
+    let aig = aiger::parse(&mut Cursor::new(constrained.as_bytes())).unwrap();
+    assert_eq!(aig.constraints, vec![3]);
+    match bmc::check(&aig, &mut CoreSolver::new(Default::default()), Some(6)) {
+        BmcResult::Bounded(6) => {}
+        _                     => { panic!("expected no counterexample up to depth 6"); }
+    }
+
+    // No trace satisfies a false constraint:
+    let aig = aiger::parse(&mut Cursor::new("aag 1 1 0 0 0 1 1\n2\n2\n0\n".as_bytes())).unwrap();
+    match bmc::check(&aig, &mut CoreSolver::new(Default::default()), None) {
+        BmcResult::Unreachable => {}
+        _                      => { panic!("expected no reachable state"); }
+    }
+
+    // A latch without reset value that is bad right away, written as an output:
+    let aig = aiger::parse(&mut Cursor::new("aag 1 0 1 1 0\n2 2 2\n2\n".as_bytes())).unwrap();
+    assert_eq!(aig.latches[0].reset, Reset::Undef);
+    match bmc::check(&aig, &mut CoreSolver::new(Default::default()), None) {
+        BmcResult::Counterexample(trace) => {
+            assert_eq!(trace.init, vec![Some(true)]);
+            let mut out = Vec::new();
+            bmc::writeWitness(&mut out, &aig, &trace).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), "1\nb0\n1\n\n.\n");
+        }
+        _                                => { panic!("expected a counterexample"); }
+    }
+}
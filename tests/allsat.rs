@@ -0,0 +1,69 @@
+extern crate minisat_rust;
+
+use std::io::Cursor;
+use minisat_rust::sat::dimacs;
+use minisat_rust::sat::allsat::Enumerator;
+use minisat_rust::sat::formula::VarMap;
+use minisat_rust::sat::minisat::CoreSolver;
+
+
+fn enumerator(cnf : &str, shrink : bool) -> Enumerator<CoreSolver> {
+    let mut en = Enumerator::new(CoreSolver::new(Default::default()), shrink);
+    let parsed = dimacs::parse(&mut Cursor::new(cnf.as_bytes()), &mut en, true).unwrap();
+    if let Some(vars) = parsed.projection {
+        en.setProjection(vars);
+    }
+    en
+}
+
+
+// Number of total assignments over 'n' variables covered by a (partial) model:
+fn extensions(model : &VarMap<bool>, n : usize) -> usize {
+    1 << (n - model.iter().count())
+}
+
+
+#[test]
+fn all_models_are_enumerated() {
+    // a ∨ b, ¬a ∨ c: 4 models over three variables.
+    let models : Vec<_> = enumerator("p cnf 3 2\n1 2 0\n-1 3 0\n", false).collect();
+    assert_eq!(models.len(), 4);
+    assert!(models.iter().all(|m| m.iter().count() == 3));
+
+    assert_eq!(enumerator("p cnf 1 2\n1 0\n-1 0\n", false).count(), 0);
+}
+
+
+#[test]
+fn projection_merges_models() {
+    // Same formula projected to 'a': both polarities are possible.
+    let models : Vec<_> = enumerator("c ind 1 0\np cnf 3 2\n1 2 0\n-1 3 0\n", false).collect();
+    assert_eq!(models.len(), 2);
+    assert!(models.iter().all(|m| m.iter().count() == 1));
+}
+
+
+#[test]
+fn shrunk_models_are_disjoint() {
+    let cnf = "p cnf 5 4\n1 2 3 0\n-1 4 0\n-2 -3 5 0\n4 5 0\n";
+    let total = enumerator(cnf, false).count();
+    let covered : usize = enumerator(cnf, true).map(|m| extensions(&m, 5)).sum();
+    assert_eq!(total, covered);
+}
+
+
+#[test]
+fn encoded_xors_stay_out_of_models() {
+    // Shrinking encodes the long XOR into clauses over auxiliary variables, which have no DIMACS id:
+    let cnf = "p cnf 7 2\nx 1 2 3 4 5 6 7 0\n1 2 0\n";
+    let mut en = Enumerator::new(CoreSolver::new(Default::default()), true);
+    let backward_subst = dimacs::parse(&mut Cursor::new(cnf.as_bytes()), &mut en, true).unwrap().backward_subst;
+
+    let mut covered = 0;
+    for model in en {
+        assert!(model.iter().all(|(v, _)| backward_subst.get(&v).is_some()));
+        dimacs::writeModel(&mut Vec::new(), &backward_subst, &model).unwrap();
+        covered += extensions(&model, 7);
+    }
+    assert_eq!(covered, 48);
+}
@@ -0,0 +1,60 @@
+extern crate minisat_rust;
+
+mod common;
+
+use std::io::Cursor;
+use minisat_rust::sat::{dimacs, Solver, IncrementalSolver};
+use minisat_rust::sat::backbone::{self, BackboneResult};
+use minisat_rust::sat::formula::VarMap;
+use minisat_rust::sat::minisat::CoreSolver;
+use minisat_rust::sat::minisat::simp::SimpSolver;
+use common::pigeonhole;
+
+
+fn backbone<S : IncrementalSolver>(mut solver : S, cnf : &str) -> Option<Vec<i32>> {
+    let parsed = dimacs::parse(&mut Cursor::new(cnf.as_bytes()), &mut solver, true).unwrap();
+    let subst : VarMap<i32> = parsed.backward_subst;
+
+    match backbone::compute(&mut solver, parsed.projection.as_ref().map(|vars| &vars[..])) {
+        BackboneResult::Backbone(lits) => {
+            let mut ids : Vec<i32> = lits.iter().map(|lit| if lit.sign() { -subst[&lit.var()] } else { subst[&lit.var()] }).collect();
+            ids.sort_by_key(|id| id.abs());
+            Some(ids)
+        }
+
+        BackboneResult::UnSAT          => { None }
+        _                              => { panic!("unexpected interrupt") }
+    }
+}
+
+
+#[test]
+fn backbone_literals() {
+    // 1 is a unit, ¬2 follows from 1 ∧ (¬1 ∨ ¬2), 3 ∧ 4 hold in both branches on 5, 6 is free.
+    let cnf = "p cnf 6 7\n1 0\n-1 -2 0\n5 3 0\n-5 3 0\n-3 4 0\n4 6 5 0\n-6 5 3 0\n";
+    assert_eq!(backbone(CoreSolver::new(Default::default()), cnf), Some(vec![1, -2, 3, 4]));
+    assert_eq!(backbone(SimpSolver::new(Default::default()), cnf), Some(vec![1, -2, 3, 4]));
+
+    let projected = format!("c ind 2 4 5 0\n{}", cnf);
+    assert_eq!(backbone(CoreSolver::new(Default::default()), &projected), Some(vec![-2, 4]));
+}
+
+
+#[test]
+fn unsatisfiable_formula() {
+    assert_eq!(backbone(CoreSolver::new(Default::default()), "p cnf 2 3\n1 2 0\n-1 0\n-2 0\n"), None);
+}
+
+
+#[test]
+fn interrupt_before_a_model_is_indeterminate() {
+    // Pigeons 9 into 8 holes, stopped at the first budget check:
+    let mut solver = CoreSolver::new(Default::default());
+    pigeonhole(&mut solver, 8);
+
+    solver.setTerminateCallback(Some(Box::new(|| true)));
+    match backbone::compute(&mut solver, None) {
+        BackboneResult::Indeterminate => {}
+        _                             => { panic!("expected no answer") }
+    }
+}
@@ -0,0 +1,73 @@
+extern crate minisat_rust;
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use minisat_rust::sat::{PartialResult, TotalResult, Solver};
+use minisat_rust::sat::formula::Lit;
+use minisat_rust::sat::minisat::CoreSolver;
+use minisat_rust::sat::minisat::simp::SimpSolver;
+use common::pigeonhole;
+
+
+#[test]
+fn learnt_clauses_are_implied() {
+    let learnts = Rc::new(RefCell::new(Vec::new()));
+    let mut solver = CoreSolver::new(Default::default());
+    let clauses = pigeonhole(&mut solver, 5);
+    {
+        let learnts = learnts.clone();
+        solver.setLearnCallback(4, Some(Box::new(move |lits : &[Lit], lbd| { learnts.borrow_mut().push((lits.to_vec(), lbd)); })));
+    }
+
+    match solver.solve() {
+        TotalResult::UnSAT => {}
+        _                  => { panic!("expected UNSAT"); }
+    }
+
+    let learnts = learnts.borrow();
+    assert!(!learnts.is_empty());
+    for &(ref lits, lbd) in learnts.iter() {
+        assert!(lits.len() <= 4 && lbd >= 1 && lbd <= lits.len());
+
+        // The formula together with the negation of the clause has no model:
+        let mut check = CoreSolver::new(Default::default());
+        let n = solver.nVars();
+        for _ in 0 .. n {
+            check.newVar(None, true);
+        }
+        for c in clauses.iter() {
+            check.addClause(c);
+        }
+        if let PartialResult::SAT(_) = check.solveLimited(&lits.iter().map(|&lit| !lit).collect::<Vec<Lit>>()) {
+            panic!("learnt clause {:?} is not implied", lits);
+        }
+    }
+}
+
+
+#[test]
+fn terminate_stops_search() {
+    for &(p, limit) in [(3, None), (8, Some(100))].iter() {
+        let polls = Rc::new(RefCell::new(0));
+        let mut solver = SimpSolver::new(Default::default());
+        pigeonhole(&mut solver, p);
+        {
+            let polls = polls.clone();
+            solver.setTerminateCallback(Some(Box::new(move || {
+                *polls.borrow_mut() += 1;
+                limit.map(|limit| *polls.borrow() > limit).unwrap_or(false)
+            })));
+        }
+
+        match (solver.solve(), limit) {
+            (TotalResult::UnSAT, None)           => {}
+            (TotalResult::Interrupted, Some(_))  => {}
+            _                                    => { panic!("unexpected result for {} holes", p); }
+        }
+        if let Some(limit) = limit {
+            assert!(*polls.borrow() > limit);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+// Helpers shared by the integration tests, each test uses only some of them:
+#![allow(dead_code)]
+
+use minisat_rust::sat::Solver;
+use minisat_rust::sat::formula::{Var, Lit};
+
+
+// Linear congruential generator, so that random tests are the same on every run:
+pub struct Random(pub u64);
+
+impl Random {
+    pub fn next(&mut self, m : u64) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 33) % m
+    }
+
+    // A literal over 'n' variables, numbered from 1 as in DIMACS:
+    pub fn lit(&mut self, n : usize) -> i32 {
+        let id = self.next(n as u64) as i32 + 1;
+        if self.next(2) == 0 { id } else { -id }
+    }
+
+    // 'm' clauses of three literals over 'n' variables, as variable indices and signs:
+    pub fn clauses(&mut self, n : usize, m : usize) -> Vec<Vec<(usize, bool)>> {
+        (0 .. m).map(|_| (0 .. 3).map(|_| (self.next(n as u64) as usize, self.next(2) == 0)).collect()).collect()
+    }
+}
+
+
+// Pigeonhole principle: 'p + 1' pigeons do not fit into 'p' holes. Returns the clauses added:
+pub fn pigeonhole<S : Solver>(solver : &mut S, p : usize) -> Vec<Vec<Lit>> {
+    let vars : Vec<Vec<Var>> = (0 .. p + 1).map(|_| (0 .. p).map(|_| solver.newVar(None, true)).collect()).collect();
+    let mut clauses : Vec<Vec<Lit>> = vars.iter().map(|row| row.iter().map(|v| v.posLit()).collect()).collect();
+    for j in 0 .. p {
+        for i in 0 .. p + 1 {
+            for k in i + 1 .. p + 1 {
+                clauses.push(vec![vars[i][j].negLit(), vars[k][j].negLit()]);
+            }
+        }
+    }
+
+    for c in clauses.iter() {
+        solver.addClause(c);
+    }
+    clauses
+}
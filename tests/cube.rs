@@ -0,0 +1,174 @@
+extern crate tempfile;
+extern crate minisat_rust;
+
+mod common;
+
+use std::fs;
+use std::io::Write;
+use minisat_rust::sat::{PartialResult, TotalResult, Solver, IncrementalSolver};
+use minisat_rust::sat::dimacs;
+use minisat_rust::sat::formula::{Var, Lit};
+use minisat_rust::sat::minisat::CoreSolver;
+use minisat_rust::sat::minisat::cube::{self, CubeSettings, CubeStats};
+use common::Random;
+
+
+fn load(n : usize, clauses : &[Vec<(usize, bool)>]) -> (CoreSolver, Vec<Var>) {
+    let mut solver = CoreSolver::new(Default::default());
+    let vars : Vec<Var> = (0 .. n).map(|_| solver.newVar(None, true)).collect();
+    for c in clauses.iter() {
+        solver.addClause(&c.iter().map(|&(v, sign)| vars[v].lit(sign)).collect::<Vec<Lit>>());
+    }
+    (solver, vars)
+}
+
+
+#[test]
+fn cubes_cover_every_model() {
+    let mut rnd = Random(23);
+    let n = 12;
+    let settings = CubeSettings { depth : 4, candidates : n };
+    for round in 0 .. 40 {
+        let clauses = rnd.clauses(n, 30 + round);
+        let (mut solver, vars) = load(n, &clauses);
+        let mut stats = CubeStats::default();
+        let cubes = cube::cube(&mut solver, &settings, &mut stats);
+        assert!(cubes.iter().all(|c| c.len() <= settings.depth), "round {}", round);
+
+        for mask in 0 .. 1usize << n {
+            let value = |v : usize, sign : bool| (mask & (1 << v) != 0) != sign;
+            if !clauses.iter().all(|c| c.iter().any(|&(v, sign)| value(v, sign))) { continue; }
+
+            let index = |var : Var| vars.iter().position(|&v| v == var).unwrap();
+            let covered = cubes.iter().filter(|c| c.iter().all(|lit| value(index(lit.var()), lit.sign()))).count();
+            assert!(covered >= 1, "model {:b} is not covered in round {}", mask, round);
+        }
+    }
+}
+
+
+#[test]
+fn conquer_agrees_with_core_solver() {
+    let mut rnd = Random(29);
+    let n = 80;
+    for round in 0 .. 12 {
+        let clauses = rnd.clauses(n, 320 + 4 * round);
+        let (mut core, _) = load(n, &clauses);
+        let (mut solver, _) = load(n, &clauses);
+        let cubes = cube::cube(&mut solver, &CubeSettings { depth : 5, candidates : 30 }, &mut CubeStats::default());
+
+        let problem = clauses.clone();
+        let result = cube::conquer(move || load(n, &problem).0, cubes, 3);
+        match (result, core.solve()) {
+            (TotalResult::SAT(model), TotalResult::SAT(_)) => {
+                let (_, vars) = load(n, &clauses);
+                for c in clauses.iter() {
+                    assert!(c.iter().any(|&(v, sign)| model[&vars[v]] != sign), "round {}", round);
+                }
+            }
+
+            (TotalResult::UnSAT, TotalResult::UnSAT)       => {}
+            _                                              => { panic!("results differ in round {}", round); }
+        }
+    }
+}
+
+
+#[test]
+fn pigeonhole_is_refuted_cube_by_cube() {
+    let p = 6;
+    let var = |i : usize, j : usize| i * p + j;
+    let mut clauses : Vec<Vec<(usize, bool)>> = (0 .. p + 1).map(|i| (0 .. p).map(|j| (var(i, j), false)).collect()).collect();
+    for j in 0 .. p {
+        for i in 0 .. p + 1 {
+            for k in i + 1 .. p + 1 {
+                clauses.push(vec![(var(i, j), true), (var(k, j), true)]);
+            }
+        }
+    }
+
+    let n = (p + 1) * p;
+    let (mut solver, _) = load(n, &clauses);
+    let mut stats = CubeStats::default();
+    let cubes = cube::cube(&mut solver, &CubeSettings { depth : 3, candidates : n }, &mut stats);
+    assert!(cubes.len() > 1 && stats.probes > 0);
+
+    match cube::conquer(move || load(n, &clauses).0, cubes, 2) {
+        TotalResult::UnSAT => {}
+        _                  => { panic!("expected UNSAT"); }
+    }
+}
+
+
+#[test]
+fn contradictory_failed_literals_leave_no_cubes() {
+    // Both values of 'x' fail by propagation, which the cuber finds without branching:
+    let clauses = vec![vec![(0, false), (1, false)], vec![(0, false), (1, true)],
+                       vec![(0, true), (2, false)], vec![(0, true), (2, true)]];
+    let (mut solver, _) = load(3, &clauses);
+    let mut stats = CubeStats::default();
+    assert!(cube::cube(&mut solver, &Default::default(), &mut stats).is_empty());
+    assert!(stats.failed > 0 || stats.refuted > 0);
+    match solver.solve() {
+        TotalResult::UnSAT => {}
+        _                  => { panic!("expected UNSAT"); }
+    }
+
+    match cube::conquer(move || load(3, &clauses).0, Vec::new(), 2) {
+        TotalResult::UnSAT => {}
+        _                  => { panic!("expected UNSAT"); }
+    }
+}
+
+
+#[test]
+fn icnf_lists_clauses_then_cubes() {
+    let mut out = Vec::new();
+    dimacs::writeIcnf(&mut out, &[vec![1, -2], vec![2, 3]], &[vec![1, 2], vec![-1], vec![]]).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "p inccnf\n1 -2 0\n2 3 0\na 1 2 0\na -1 0\na 0\n");
+}
+
+
+#[test]
+fn files_are_cubed_and_conquered() {
+    let mut rnd = Random(31);
+    let n = 40;
+    for round in 0 .. 10 {
+        let m = 150 + 15 * round;
+        let mut text = format!("p cnf {} {}\n", n, m);
+        for c in rnd.clauses(n, m) {
+            text.push_str(&c.iter().map(|&(v, sign)| format!("{}{} ", if sign { "-" } else { "" }, v + 1)).collect::<String>());
+            text.push_str("0\n");
+        }
+        let mut input = tempfile::NamedTempFile::new().unwrap();
+        input.write_all(text.as_bytes()).unwrap();
+        input.flush().unwrap();
+
+        // Some cube is satisfiable with the written clauses iff the problem is:
+        let cubes = tempfile::NamedTempFile::new().unwrap();
+        let options = |out : Option<&tempfile::NamedTempFile>| {
+            minisat_rust::CubeOptions { in_path  : input.path().to_path_buf()
+                                      , out_path : out.map(|file| file.path().to_path_buf())
+                                      , threads  : 2
+                                      }
+        };
+        let settings = CubeSettings { depth : 3, candidates : n };
+        minisat_rust::cubeAndConquer(options(Some(&cubes)), Default::default(), settings.clone()).unwrap();
+
+        let mut sat = false;
+        let icnf = fs::read_to_string(cubes.path()).unwrap();
+        dimacs::parseIncremental(&mut icnf.as_bytes(), &mut CoreSolver::new(Default::default()), |solver, _, assumptions| {
+            if let PartialResult::SAT(_) = solver.solveUnder(&assumptions) { sat = true; }
+            Ok(())
+        }).unwrap();
+
+        let mut solver = CoreSolver::new(Default::default());
+        dimacs::parse(&mut text.as_bytes(), &mut solver, true).unwrap();
+        match solver.solve() {
+            TotalResult::SAT(_) => { assert!(sat, "round {}", round); }
+            _                   => { assert!(!sat, "round {}", round); }
+        }
+
+        minisat_rust::cubeAndConquer(options(None), Default::default(), settings).unwrap();
+    }
+}
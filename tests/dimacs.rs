@@ -0,0 +1,209 @@
+extern crate tempfile;
+extern crate minisat_rust;
+
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::process;
+use minisat_rust::sat::{TotalResult, Solver, LinearSolver};
+use minisat_rust::sat::dimacs;
+use minisat_rust::sat::formula::VarMap;
+use minisat_rust::sat::minisat::CoreSolver;
+use minisat_rust::sat::minisat::simp::SimpSolver;
+use common::Random;
+
+
+// A written formula: the map comments, the number of variables and the clauses:
+struct Written {
+    map     : Vec<(i32, i32)>,
+    n       : usize,
+    clauses : Vec<Vec<i32>>
+}
+
+fn read(text : &str) -> Written {
+    let mut written = Written { map : Vec::new(), n : 0, clauses : Vec::new() };
+    for line in text.lines() {
+        let words : Vec<&str> = line.split_whitespace().collect();
+        let ids = |words : &[&str]| -> Vec<i32> {
+            let ids : Vec<i32> = words.iter().map(|w| w.parse().unwrap()).collect();
+            assert_eq!(ids.last(), Some(&0));
+            ids[.. ids.len() - 1].to_vec()
+        };
+
+        match words[0] {
+            "c" => { assert_eq!(words[1], "map"); written.map.push((words[2].parse().unwrap(), words[3].parse().unwrap())); }
+            "p" => { assert_eq!(words[1], "cnf"); assert_eq!(words[3].parse::<usize>().unwrap(), text.lines().count() - written.map.len() - 1); written.n = words[2].parse().unwrap(); }
+            _   => { written.clauses.push(ids(&words)); }
+        }
+    }
+    written
+}
+
+fn holds(mask : usize, id : i32) -> bool {
+    (mask & (1 << (id.abs() - 1)) != 0) == (id > 0)
+}
+
+fn count(n : usize, clauses : &[Vec<i32>], xors : &[Vec<i32>]) -> usize {
+    (0 .. 1 << n).filter(|&mask| {
+        clauses.iter().all(|c| c.iter().any(|&id| holds(mask, id)))
+            && xors.iter().all(|x| x.iter().filter(|&&id| holds(mask, id)).count() % 2 == 1)
+    }).count()
+}
+
+fn text(clauses : &[Vec<i32>], xors : &[Vec<i32>], n : usize) -> String {
+    let mut text = format!("p cnf {} {}\n", n, clauses.len() + xors.len());
+    for c in clauses.iter() {
+        text.push_str(&format!("{} 0\n", c.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(" ")));
+    }
+    for x in xors.iter() {
+        text.push_str(&format!("x {} 0\n", x.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(" ")));
+    }
+    text
+}
+
+fn satisfiable<S : Solver>(mut solver : S, text : &str) -> bool {
+    dimacs::parse(&mut text.as_bytes(), &mut solver, true).unwrap();
+    match solver.solve() {
+        TotalResult::SAT(_) => { true }
+        TotalResult::UnSAT  => { false }
+        _                   => { panic!("unexpected interrupt"); }
+    }
+}
+
+
+#[test]
+fn ground_simplification_keeps_the_models() {
+    // Without elimination only variables in satisfied clauses drop out, and they are free:
+    let mut rnd = Random(47);
+    let n = 10;
+    for round in 0 .. 60 {
+        let clauses : Vec<Vec<i32>> = (0 .. 8 + rnd.next(30)).map(|_| (0 .. 1 + rnd.next(3)).map(|_| rnd.lit(n)).collect()).collect();
+        let xors : Vec<Vec<i32>> = (0 .. rnd.next(3)).map(|_| (0 .. 2 + rnd.next(3)).map(|_| rnd.lit(n)).collect()).collect();
+        let input = text(&clauses, &xors, n);
+
+        let mut solver = CoreSolver::new(Default::default());
+        let backward_subst = dimacs::parse(&mut input.as_bytes(), &mut solver, true).unwrap().backward_subst;
+        solver.preprocess();
+        let mut out = Vec::new();
+        dimacs::write(&mut out, &solver, &backward_subst).unwrap();
+        let output = String::from_utf8(out).unwrap();
+        let written = read(&output);
+
+        let expected = count(n, &clauses, &xors);
+        if expected == 0 {
+            assert_eq!(count(written.n, &written.clauses, &[]), 0, "round {}", round);
+        } else {
+            assert_eq!(count(written.n, &written.clauses, &[]) << (n - written.map.len()), expected, "round {}\n{}", round, output);
+        }
+        assert_eq!(satisfiable(CoreSolver::new(Default::default()), &output), expected > 0);
+    }
+}
+
+
+#[test]
+fn eliminated_variables_are_left_out() {
+    let mut rnd = Random(53);
+    let n = 40;
+    let mut shrunk = 0;
+    for round in 0 .. 20 {
+        let clauses : Vec<Vec<i32>> = (0 .. 120 + 4 * round).map(|_| (0 .. 3).map(|_| rnd.lit(n)).collect()).collect();
+        let input = text(&clauses, &[], n);
+
+        let mut solver = SimpSolver::new(Default::default());
+        let backward_subst = dimacs::parse(&mut input.as_bytes(), &mut solver, true).unwrap().backward_subst;
+        let ok = solver.preprocess();
+        let mut out = Vec::new();
+        dimacs::write(&mut out, &solver, &backward_subst).unwrap();
+        let output = String::from_utf8(out).unwrap();
+        let written = read(&output);
+
+        // Densely numbered, in the order of the original ids, and none of them eliminated:
+        assert_eq!(written.map.iter().map(|&(id, _)| id).collect::<Vec<i32>>(), (1 .. written.n as i32 + 1).collect::<Vec<i32>>());
+        assert!(written.map.windows(2).all(|w| w[0].1 < w[1].1));
+        for &(_, orig) in written.map.iter() {
+            let (v, _) = backward_subst.iter().find(|&(_, &id)| id == orig).unwrap();
+            assert!(!solver.isEliminated(v), "round {}", round);
+        }
+        if ok && written.n < n { shrunk += 1; }
+
+        assert_eq!(satisfiable(CoreSolver::new(Default::default()), &output), satisfiable(CoreSolver::new(Default::default()), &input), "round {}", round);
+    }
+    assert!(shrunk > 0);
+}
+
+
+#[test]
+fn contradictions_and_linear_constraints() {
+    let mut solver = CoreSolver::new(Default::default());
+    let backward_subst = dimacs::parse(&mut "p cnf 2 3\n1 2 0\n-1 0\n-2 0\n".as_bytes(), &mut solver, true).unwrap().backward_subst;
+    let mut out = Vec::new();
+    dimacs::write(&mut out, &solver, &backward_subst).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "p cnf 0 1\n0\n");
+
+    // Pseudo-boolean constraints have no DIMACS form:
+    let mut solver = CoreSolver::new(Default::default());
+    let x = solver.newVar(None, true);
+    let y = solver.newVar(None, true);
+    solver.addAtLeast(&[(2, x.posLit()), (1, y.posLit())], 2);
+    solver.addClause(&[x.negLit(), y.negLit()]);
+    assert!(dimacs::write(&mut Vec::new(), &solver, &VarMap::new()).is_err());
+
+    // Variables without an original id come last:
+    let mut solver = CoreSolver::new(Default::default());
+    let backward_subst = dimacs::parse(&mut "p cnf 2 1\n-2 1 0\n".as_bytes(), &mut solver, true).unwrap().backward_subst;
+    let aux = solver.newVar(None, true);
+    let (one, _) = backward_subst.iter().find(|&(_, &id)| id == 1).unwrap();
+    solver.addClause(&[aux.posLit(), one.posLit()]);
+    let mut out = Vec::new();
+    dimacs::write(&mut out, &solver, &backward_subst).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "c map 1 1\nc map 2 2\np cnf 3 2\n1 -2 0\n1 3 0\n");
+}
+
+
+#[test]
+fn no_solve_writes_the_simplified_problem() {
+    let mut rnd = Random(59);
+    let n = 60;
+    let clauses : Vec<Vec<i32>> = (0 .. 240).map(|_| (0 .. 3).map(|_| rnd.lit(n)).collect()).collect();
+    let mut input = tempfile::NamedTempFile::new().unwrap();
+    input.write_all(text(&clauses, &[], n).as_bytes()).unwrap();
+    input.flush().unwrap();
+    let output = tempfile::NamedTempFile::new().unwrap();
+
+    let status = process::Command::new(env!("CARGO_BIN_EXE_minisat-rust"))
+        .arg("--verb=0").arg("--no-solve").arg(format!("--dimacs={}", output.path().display())).arg(input.path())
+        .stdout(process::Stdio::null())
+        .status().unwrap();
+    assert!(status.success());
+
+    let written = fs::read_to_string(output.path()).unwrap();
+    assert!(read(&written).n < n);
+    assert_eq!(satisfiable(CoreSolver::new(Default::default()), &written), satisfiable(CoreSolver::new(Default::default()), &text(&clauses, &[], n)));
+}
+
+
+#[test]
+fn xors_are_written_as_clauses() {
+    // The long constraint is chained through a fresh variable numbered after the original ones:
+    let mut solver = CoreSolver::new(Default::default());
+    let backward_subst = dimacs::parse(&mut "p cnf 7 2\nx 1 2 3 4 5 6 7 0\n-1 -2 0\n".as_bytes(), &mut solver, true).unwrap().backward_subst;
+    let mut out = Vec::new();
+    dimacs::write(&mut out, &solver, &backward_subst).unwrap();
+    let written = read(&String::from_utf8(out).unwrap());
+    assert_eq!(written.map.len(), 7);
+    assert_eq!(written.n, 8);
+    assert_eq!(count(written.n, &written.clauses, &[]), count(7, &[vec![-1, -2]], &[vec![1, 2, 3, 4, 5, 6, 7]]));
+
+    // Recovered XOR constraints still have their clauses, so they are not written twice:
+    let text = "p cnf 3 4\n1 2 3 0\n1 -2 -3 0\n-1 2 -3 0\n-1 -2 3 0\n";
+    let mut solver = CoreSolver::new(Default::default());
+    let backward_subst = dimacs::parse(&mut text.as_bytes(), &mut solver, true).unwrap().backward_subst;
+    assert_eq!(solver.findXors().len(), 1);
+    assert!(solver.preprocess());
+    let mut out = Vec::new();
+    dimacs::write(&mut out, &solver, &backward_subst).unwrap();
+    let written = read(&String::from_utf8(out).unwrap());
+    assert_eq!(written.clauses.len(), 4);
+    assert_eq!(written.n, 3);
+}
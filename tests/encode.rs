@@ -0,0 +1,85 @@
+extern crate minisat_rust;
+
+use minisat_rust::sat::{PartialResult, Solver};
+use minisat_rust::sat::allsat::Enumerator;
+use minisat_rust::sat::encode::{self, AmoEncoding, CardEncoding, Totalizer};
+use minisat_rust::sat::formula::{Var, Lit};
+use minisat_rust::sat::minisat::CoreSolver;
+
+
+fn binomial(n : usize, k : usize) -> usize {
+    (0 .. k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+
+// Number of assignments to 'n' inputs that satisfy the encoding, projected on the inputs:
+fn count<F : Fn(&mut Enumerator<CoreSolver>, &[Lit]) -> bool>(n : usize, encode : F) -> usize {
+    let mut en = Enumerator::new(CoreSolver::new(Default::default()), false);
+    let vars : Vec<Var> = (0 .. n).map(|_| en.newVar(None, true)).collect();
+    let lits : Vec<Lit> = vars.iter().map(|v| v.posLit()).collect();
+    if !encode(&mut en, &lits) { return 0; }
+
+    en.setProjection(vars);
+    en.count()
+}
+
+
+#[test]
+fn at_most_one() {
+    for &encoding in [AmoEncoding::Pairwise, AmoEncoding::Ladder, AmoEncoding::Commander].iter() {
+        for n in 1 .. 12 {
+            assert_eq!(count(n, |s, lits| encode::atMostOne(s, lits, encoding)), n + 1, "{:?} n = {}", encoding, n);
+        }
+    }
+}
+
+
+#[test]
+fn at_most_and_at_least_k() {
+    for &encoding in [CardEncoding::SequentialCounter, CardEncoding::Totalizer, CardEncoding::CardinalityNetwork].iter() {
+        for n in 1 .. 9 {
+            for k in 0 .. n + 2 {
+                let at_most : usize = (0 .. k + 1).filter(|&i| i <= n).map(|i| binomial(n, i)).sum();
+                assert_eq!(count(n, |s, lits| encode::atMostK(s, lits, k, encoding)), at_most, "{:?} n = {} k = {}", encoding, n, k);
+
+                let at_least : usize = (k .. n + 1).map(|i| binomial(n, i)).sum();
+                assert_eq!(count(n, |s, lits| encode::atLeastK(s, lits, k, encoding)), at_least, "{:?} n = {} k = {}", encoding, n, k);
+            }
+        }
+    }
+}
+
+
+#[test]
+fn incremental_totalizer() {
+    let mut solver = CoreSolver::new(Default::default());
+    let lits : Vec<Lit> = (0 .. 8).map(|_| solver.newVar(None, true).posLit()).collect();
+    for lit in lits[.. 5].iter() {
+        solver.addClause(&[*lit]);
+    }
+
+    // Five inputs are true, so the bound can be tightened down to five and no further:
+    let mut tot = Totalizer::new(&mut solver, &lits[.. 6], 1);
+    for k in (0 .. 6).rev() {
+        let bound = tot.atMost(&mut solver, k).unwrap();
+        match solver.solveLimited(&[bound]) {
+            PartialResult::SAT(_)        => { assert!(k >= 5); }
+            PartialResult::UnSATUnder(_) => { assert!(k < 5); }
+            _                            => { panic!("unexpected result") }
+        }
+    }
+
+    // More inputs under the same bound:
+    tot.addInputs(&mut solver, &lits[6 ..]);
+    solver.addClause(&[lits[7]]);
+    let bound = tot.atMost(&mut solver, 5).unwrap();
+    match solver.solveLimited(&[bound]) {
+        PartialResult::UnSATUnder(_) => {}
+        _                            => { panic!("expected UNSAT") }
+    }
+    let bound = tot.atMost(&mut solver, 6).unwrap();
+    match solver.solveLimited(&[bound]) {
+        PartialResult::SAT(_) => {}
+        _                     => { panic!("expected SAT") }
+    }
+}
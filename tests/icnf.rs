@@ -0,0 +1,136 @@
+extern crate minisat_rust;
+
+mod common;
+
+use std::io;
+use minisat_rust::sat::{PartialResult, IncrementalSolver};
+use minisat_rust::sat::dimacs;
+use minisat_rust::sat::minisat::CoreSolver;
+use minisat_rust::sat::minisat::simp::SimpSolver;
+use common::Random;
+
+
+#[derive(Debug, PartialEq)]
+enum Answer {
+    SAT,
+    UnSAT,
+    UnSATUnder(Vec<i32>)
+}
+
+
+// Answers to the queries of 'text', with the failed assumptions checked to be among the assumed ones:
+fn answers<S : IncrementalSolver>(mut solver : S, text : &str) -> Vec<Answer> {
+    let mut answers = Vec::new();
+    dimacs::parseIncremental(&mut text.as_bytes(), &mut solver, |solver, backward_subst, assumptions| {
+        let answer =
+            match solver.solveUnder(&assumptions) {
+                PartialResult::SAT(model)       => {
+                    assert!(assumptions.iter().all(|lit| model[&lit.var()] != lit.sign()));
+                    Answer::SAT
+                }
+
+                PartialResult::UnSAT            => { Answer::UnSAT }
+                PartialResult::UnSATUnder(lits) => {
+                    assert!(lits.iter().all(|lit| assumptions.contains(lit)));
+                    let mut ids : Vec<i32> = lits.iter().map(|lit| {
+                        let id = backward_subst[&lit.var()];
+                        if lit.sign() { -id } else { id }
+                    }).collect();
+                    ids.sort();
+                    Answer::UnSATUnder(ids)
+                }
+
+                PartialResult::Interrupted(_)   => { panic!("unexpected interrupt"); }
+            };
+        answers.push(answer);
+        Ok(())
+    }).unwrap();
+    answers
+}
+
+
+#[test]
+fn queries_match_brute_force() {
+    let mut rnd = Random(31);
+    let n = 8;
+    for round in 0 .. 40 {
+        let mut text = String::from("c random\np inccnf\n");
+        let mut clauses : Vec<Vec<i32>> = Vec::new();
+        let mut expected = Vec::new();
+        for _ in 0 .. 6 {
+            for _ in 0 .. 2 + rnd.next(5) {
+                let c : Vec<i32> = (0 .. 1 + rnd.next(3)).map(|_| rnd.lit(n)).collect();
+                text.push_str(&format!("{} 0\n", c.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(" ")));
+                clauses.push(c);
+            }
+
+            let assumptions : Vec<i32> = (0 .. rnd.next(4)).map(|_| rnd.lit(n)).collect();
+            text.push_str(&format!("a {} 0\n", assumptions.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(" ")));
+
+            let holds = |mask : usize, id : i32| (mask & (1 << (id.abs() - 1)) != 0) == (id > 0);
+            let models : Vec<usize> = (0 .. 1 << n).filter(|&mask| clauses.iter().all(|c| c.iter().any(|&id| holds(mask, id)))).collect();
+            expected.push(
+                if models.is_empty() {
+                    None
+                } else {
+                    Some(models.iter().any(|&mask| assumptions.iter().all(|&id| holds(mask, id))))
+                });
+        }
+
+        for answers in vec![answers(CoreSolver::new(Default::default()), &text), answers(SimpSolver::new(Default::default()), &text)] {
+            assert_eq!(answers.len(), expected.len());
+            for (answer, expected) in answers.iter().zip(expected.iter()) {
+                match (answer, *expected) {
+                    (&Answer::SAT, Some(true))            => {}
+                    (&Answer::UnSATUnder(_), Some(false)) => {}
+                    (&Answer::UnSAT, None)                => {}
+                    _                                     => { panic!("{:?} instead of {:?} in round {}", answer, expected, round); }
+                }
+            }
+        }
+    }
+}
+
+
+#[test]
+fn failed_assumptions_are_reported() {
+    let text = "p inccnf\n1 2 0\n-1 2 0\na -2 3 0\n1 -2 3 0\n-3 0\na 4 -1 0\n-1 0\na 0\n";
+    assert_eq!(answers(CoreSolver::new(Default::default()), text),
+               vec![Answer::UnSATUnder(vec![-2]), Answer::UnSATUnder(vec![-1]), Answer::UnSAT]);
+}
+
+
+#[test]
+fn header_decides_the_format() {
+    assert!(dimacs::isIncremental(io::Cursor::new("c comment\n\np inccnf\n1 0\n")).unwrap().0);
+    assert!(!dimacs::isIncremental(io::Cursor::new("c comment\np cnf 1 1\n1 0\n")).unwrap().0);
+
+    // Comments that do not fit into one buffer:
+    let text = format!("{}p inccnf\n1 0\na -1 0\n", "c comment\n".repeat(10000));
+    let (incremental, mut input) = dimacs::isIncremental(io::BufReader::with_capacity(64, io::Cursor::new(text.clone()))).unwrap();
+    assert!(incremental);
+    let mut rest = String::new();
+    io::Read::read_to_string(&mut input, &mut rest).unwrap();
+    assert_eq!(rest, text);
+
+    // Plain DIMACS has no queries, and assumptions are refused outside of 'parseIncremental':
+    let mut solver = CoreSolver::new(Default::default());
+    assert!(dimacs::parseIncremental(&mut "p cnf 2 1\n1 -2 0\n".as_bytes(), &mut solver, |_, _, _| { panic!("no queries expected"); }).is_ok());
+
+    let mut solver = CoreSolver::new(Default::default());
+    assert!(dimacs::parse(&mut "p inccnf\n1 -2 0\na 1 0\n".as_bytes(), &mut solver, false).is_err());
+    assert!(dimacs::parseClauses(&mut "p inccnf\n1 -2 0\na 1 0\n".as_bytes(), false, |_| {}).is_err());
+}
+
+
+#[test]
+fn query_errors_stop_parsing() {
+    let mut solver = CoreSolver::new(Default::default());
+    let mut calls = 0;
+    let result = dimacs::parseIncremental(&mut "p inccnf\n1 0\na 1 0\n2 0\na 2 0\n".as_bytes(), &mut solver, |_, _, _| {
+        calls += 1;
+        Err(io::Error::new(io::ErrorKind::Other, "stop"))
+    });
+    assert!(result.is_err());
+    assert_eq!(calls, 1);
+}
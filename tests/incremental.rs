@@ -0,0 +1,207 @@
+extern crate minisat_rust;
+
+mod common;
+
+use std::thread;
+use minisat_rust::sat::{PartialResult, Solver};
+use minisat_rust::sat::formula::{Var, Lit};
+use minisat_rust::sat::minisat::CoreSolver;
+use minisat_rust::sat::minisat::simp::SimpSolver;
+use common::pigeonhole;
+
+
+fn vars<S : Solver>(solver : &mut S, n : usize) -> Vec<Var> {
+    (0 .. n).map(|_| solver.newVar(None, true)).collect()
+}
+
+
+fn check_failed_assumptions<S : Solver, F : FnMut(&mut S, &[Lit]) -> PartialResult>(solver : &mut S, mut solve : F) {
+    let v = vars(solver, 4);
+
+    // (¬a ∨ ¬b), c and d are irrelevant:
+    assert!(solver.addClause(&[v[0].negLit(), v[1].negLit()]));
+    assert!(solver.addClause(&[v[2].posLit(), v[3].posLit()]));
+
+    match solve(solver, &[v[2].negLit(), v[0].posLit(), v[1].posLit()]) {
+        PartialResult::UnSATUnder(mut failed) => {
+            failed.sort();
+            assert_eq!(failed, vec![v[0].posLit(), v[1].posLit()]);
+        }
+        _ => { panic!("expected failed assumptions") }
+    }
+
+    // The solver must stay usable after a final conflict:
+    match solve(solver, &[v[0].posLit()]) {
+        PartialResult::SAT(model) => { assert_eq!(model[&v[1]], false); }
+        _                         => { panic!("expected SAT") }
+    }
+}
+
+
+#[test]
+fn core_failed_assumptions() {
+    let mut solver = CoreSolver::new(Default::default());
+    check_failed_assumptions(&mut solver, |s, assumps| s.solveLimited(assumps));
+}
+
+
+#[test]
+fn simp_failed_assumptions() {
+    let mut solver = SimpSolver::new(Default::default());
+    check_failed_assumptions(&mut solver, |s, assumps| s.solveLimited(assumps, true, false));
+}
+
+
+#[test]
+fn conflict_budget() {
+    let mut solver = CoreSolver::new(Default::default());
+    pigeonhole(&mut solver, 7);
+
+    solver.setConfBudget(10);
+    match solver.solveLimited(&[]) {
+        PartialResult::Interrupted(_) => {}
+        _                             => { panic!("expected interruption") }
+    }
+
+    solver.budgetOff();
+    match solver.solveLimited(&[]) {
+        PartialResult::UnSAT => {}
+        _                    => { panic!("expected UNSAT") }
+    }
+}
+
+
+#[test]
+fn interrupt_from_other_thread() {
+    let mut solver = SimpSolver::new(Default::default());
+    pigeonhole(&mut solver, 7);
+
+    let handle = solver.interruptHandle();
+    thread::spawn(move || { handle.interrupt(); }).join().unwrap();
+    match solver.solveLimited(&[], true, false) {
+        PartialResult::Interrupted(_) => {}
+        _                             => { panic!("expected interruption") }
+    }
+
+    solver.clearInterrupt();
+    match solver.solveLimited(&[], true, false) {
+        PartialResult::UnSAT => {}
+        _                    => { panic!("expected UNSAT") }
+    }
+}
+
+
+#[test]
+fn release_var() {
+    let mut solver = CoreSolver::new(Default::default());
+    let v = vars(&mut solver, 2);
+    let act = solver.newVar(None, true);
+
+    // Clause guarded by activation literal:
+    assert!(solver.addClause(&[act.negLit(), v[0].negLit(), v[1].negLit()]));
+    match solver.solveLimited(&[act.posLit(), v[0].posLit(), v[1].posLit()]) {
+        PartialResult::UnSATUnder(_) => {}
+        _                            => { panic!("expected failed assumptions") }
+    }
+
+    solver.releaseVar(act.negLit());
+    assert!(solver.simplify());
+    assert_eq!(solver.nClauses(), 0);
+
+    let reused = solver.newVar(None, true);
+    assert_eq!(reused, act);
+    assert_eq!(solver.nVars(), 3);
+
+    match solver.solveLimited(&[reused.posLit(), v[0].posLit(), v[1].posLit()]) {
+        PartialResult::SAT(_) => {}
+        _                     => { panic!("expected SAT") }
+    }
+}
+
+
+#[test]
+fn frozen_vars_survive_elimination() {
+    for &freeze in [false, true].iter() {
+        let mut solver = SimpSolver::new(Default::default());
+        let v = vars(&mut solver, 3);
+        solver.setFrozen(v[1], freeze);
+
+        assert!(solver.addClause(&[v[0].posLit(), v[1].posLit()]));
+        assert!(solver.addClause(&[v[1].negLit(), v[2].posLit()]));
+        assert!(solver.eliminate(false));
+
+        assert!(solver.isEliminated(v[0]));
+        assert_eq!(solver.isEliminated(v[1]), !freeze);
+    }
+}
+
+
+fn eliminated_solver(turn_off : bool) -> (SimpSolver, Vec<Var>) {
+    let mut solver = SimpSolver::new(Default::default());
+    let v = vars(&mut solver, 3);
+    assert!(solver.addClause(&[v[0].posLit(), v[1].posLit()]));
+    assert!(solver.addClause(&[v[1].negLit(), v[2].posLit()]));
+    assert!(solver.eliminate(turn_off));
+    assert!(solver.isEliminated(v[0]));
+    (solver, v)
+}
+
+
+#[test]
+fn reintroduce_eliminated_vars() {
+    for &turn_off in [false, true].iter() {
+        // Assumption over an eliminated variable:
+        {
+            let (mut solver, v) = eliminated_solver(turn_off);
+            match solver.solveLimited(&[v[0].negLit(), v[2].negLit()], true, false) {
+                PartialResult::UnSATUnder(_) => {}
+                _                            => { panic!("expected failed assumptions") }
+            }
+        }
+
+        // Clause over an eliminated variable:
+        {
+            let (mut solver, v) = eliminated_solver(turn_off);
+            assert!(solver.addClause(&[v[0].negLit(), v[2].negLit()]));
+            assert!(!solver.isEliminated(v[0]));
+
+            match solver.solveLimited(&[v[2].posLit()], true, false) {
+                PartialResult::SAT(model) => {
+                    assert_eq!((model[&v[0]], model[&v[1]], model[&v[2]]), (false, true, true));
+                }
+                _                         => { panic!("expected SAT") }
+            }
+
+            assert!(solver.addClause(&[v[0].negLit()]));
+            assert!(!solver.addClause(&[v[2].negLit()]));
+        }
+    }
+}
+
+
+#[test]
+fn resume_simplification() {
+    for &keep_simp in [false, true].iter() {
+        let mut solver = {
+            let mut settings = minisat_rust::sat::minisat::simp::Settings::default();
+            settings.keep_simp = keep_simp;
+            SimpSolver::new(settings)
+        };
+
+        let v = vars(&mut solver, 5);
+        assert!(solver.addClause(&[v[0].posLit(), v[1].posLit()]));
+        assert!(solver.addClause(&[v[1].negLit(), v[2].posLit()]));
+        assert!(solver.eliminate(true));
+
+        assert!(solver.addClause(&[v[2].negLit(), v[3].posLit()]));
+        assert!(solver.addClause(&[v[3].negLit(), v[4].posLit()]));
+        match solver.solveLimited(&[v[4].negLit()], true, false) {
+            PartialResult::SAT(model) => {
+                assert!(!model[&v[1]] && !model[&v[2]] && !model[&v[3]] && model[&v[0]]);
+            }
+            _                         => { panic!("expected SAT") }
+        }
+
+        assert_eq!(solver.isEliminated(v[3]), keep_simp);
+    }
+}
@@ -0,0 +1,187 @@
+extern crate bzip2;
+extern crate flate2;
+extern crate tempfile;
+extern crate xz2;
+extern crate minisat_rust;
+
+mod common;
+
+use std::io::{self, Read, Write};
+use std::{process, thread};
+use std::time::Duration;
+use minisat_rust::sat::{TotalResult, Solver};
+use minisat_rust::sat::dimacs;
+use minisat_rust::sat::minisat::CoreSolver;
+use common::Random;
+
+
+// Random 3-SAT with comments between the clauses, 'm' clauses over 'n' variables:
+fn cnf(rnd : &mut Random, n : usize, m : usize) -> (String, Vec<Vec<i32>>) {
+    let mut text = format!("c random\np cnf {} {}\n", n, m);
+    let mut clauses = Vec::new();
+    for i in 0 .. m {
+        let c : Vec<i32> = (0 .. 3).map(|_| {
+            let id = rnd.next(n as u64) as i32 + 1;
+            if rnd.next(2) == 0 { id } else { -id }
+        }).collect();
+        if i % 50 == 0 { text.push_str("c comment\n"); }
+        text.push_str(&format!("{} {} {} 0\n", c[0], c[1], c[2]));
+        clauses.push(c);
+    }
+    (text, clauses)
+}
+
+
+// Returns at most a few bytes per call:
+struct Trickle<'a> {
+    data : &'a [u8],
+    step : usize
+}
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf : &mut [u8]) -> io::Result<usize> {
+        let len = self.data.len().min(buf.len()).min(self.step);
+        buf[.. len].copy_from_slice(&self.data[.. len]);
+        self.data = &self.data[len ..];
+        Ok(len)
+    }
+}
+
+
+fn compressed(text : &str, format : &str) -> Vec<u8> {
+    match format {
+        "gz"   => {
+            let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            enc.write_all(text.as_bytes()).unwrap();
+            enc.finish().unwrap()
+        }
+
+        "bz2"  => {
+            let mut enc = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+            enc.write_all(text.as_bytes()).unwrap();
+            enc.finish().unwrap()
+        }
+
+        "xz"   => {
+            let mut enc = xz2::write::XzEncoder::new(Vec::new(), 6);
+            enc.write_all(text.as_bytes()).unwrap();
+            enc.finish().unwrap()
+        }
+
+        "lzma" => {
+            let stream = xz2::stream::Stream::new_lzma_encoder(&xz2::stream::LzmaOptions::new_preset(6).unwrap()).unwrap();
+            let mut enc = xz2::write::XzEncoder::new_stream(Vec::new(), stream);
+            enc.write_all(text.as_bytes()).unwrap();
+            enc.finish().unwrap()
+        }
+
+        _      => { text.as_bytes().to_vec() }
+    }
+}
+
+
+#[test]
+fn clauses_survive_buffer_boundaries() {
+    let mut rnd = Random(37);
+    let (text, clauses) = cnf(&mut rnd, 500, 20000);
+    assert!(text.len() > 1 << 17);
+
+    for &step in [1, 7, 4096, 1 << 20].iter() {
+        let mut read = Vec::new();
+        dimacs::parseClauses(&mut Trickle { data : text.as_bytes(), step : step }, true, |c| { read.push(c); }).unwrap();
+        assert!(read == clauses, "read {} bytes at a time", step);
+    }
+
+    // Header counts are still checked:
+    let wrong = text.replacen("p cnf 500 20000", "p cnf 500 19999", 1);
+    assert!(dimacs::parseClauses(&mut wrong.as_bytes(), true, |_| {}).is_err());
+    assert!(dimacs::parseClauses(&mut wrong.as_bytes(), false, |_| {}).is_ok());
+}
+
+
+#[test]
+fn compressed_inputs_are_recognized() {
+    let mut rnd = Random(41);
+    let (text, clauses) = cnf(&mut rnd, 60, 240);
+    let expected = {
+        let mut solver = CoreSolver::new(Default::default());
+        dimacs::parse(&mut text.as_bytes(), &mut solver, true).unwrap();
+        match solver.solve() {
+            TotalResult::SAT(_) => { true }
+            _                   => { false }
+        }
+    };
+
+    for format in ["plain", "gz", "bz2", "xz", "lzma"].iter() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&compressed(&text, format)).unwrap();
+        file.flush().unwrap();
+
+        let mut read = Vec::new();
+        dimacs::parseClauses(&mut dimacs::open(file.path()).unwrap(), true, |c| { read.push(c); }).unwrap();
+        assert!(read == clauses, "{}", format);
+
+        let mut solver = CoreSolver::new(Default::default());
+        let backward_subst = dimacs::parse(&mut dimacs::open(file.path()).unwrap(), &mut solver, true).unwrap().backward_subst;
+        match solver.solve() {
+            TotalResult::SAT(model) => {
+                assert!(expected, "{}", format);
+                assert!(dimacs::validateModel(&mut dimacs::open(file.path()).unwrap(), &backward_subst, &model).unwrap());
+            }
+            _                       => { assert!(!expected, "{}", format); }
+        }
+    }
+}
+
+
+#[test]
+fn compressed_icnf_is_detected() {
+    let text = "c queries\np inccnf\n1 2 0\na -1 0\n";
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&compressed(text, "gz")).unwrap();
+    file.flush().unwrap();
+
+    let (incremental, mut input) = dimacs::isIncremental(dimacs::open(file.path()).unwrap()).unwrap();
+    assert!(incremental);
+
+    // The lines read for detection come first:
+    let mut rest = String::new();
+    input.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, text);
+}
+
+
+#[test]
+fn standard_input_is_read_with_a_dash() {
+    let mut rnd = Random(43);
+    let (text, _) = cnf(&mut rnd, 40, 120);
+    for &(format, input) in [("gz", "-"), ("xz", "-")].iter() {
+        let mut child = process::Command::new(env!("CARGO_BIN_EXE_minisat-rust"))
+            .arg("--verb=0").arg(input)
+            .stdin(process::Stdio::piped()).stdout(process::Stdio::piped())
+            .spawn().unwrap();
+        child.stdin.take().unwrap().write_all(&compressed(&text, format)).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "{}", format);
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "SATISFIABLE");
+    }
+}
+
+
+#[test]
+fn icnf_header_may_arrive_late_on_a_pipe() {
+    let mut child = process::Command::new(env!("CARGO_BIN_EXE_minisat-rust"))
+        .arg("--verb=0").arg("-")
+        .stdin(process::Stdio::piped()).stdout(process::Stdio::piped())
+        .spawn().unwrap();
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(b"c hello\n").unwrap();
+        stdin.flush().unwrap();
+        thread::sleep(Duration::from_millis(300));
+        stdin.write_all(b"p inccnf\n1 2 0\na -1 0\n").unwrap();
+    }
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "query 1: SATISFIABLE");
+}
@@ -0,0 +1,116 @@
+/* Test driver for the IPASIR interface, linked against the shared library. Exits with a nonzero
+   status and a message on the first failed check. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+
+const char * ipasir_signature(void);
+void * ipasir_init(void);
+void ipasir_release(void * solver);
+void ipasir_add(void * solver, int32_t lit_or_zero);
+void ipasir_assume(void * solver, int32_t lit);
+int ipasir_solve(void * solver);
+int32_t ipasir_val(void * solver, int32_t lit);
+int ipasir_failed(void * solver, int32_t lit);
+void ipasir_set_terminate(void * solver, void * data, int (*terminate)(void * data));
+void ipasir_set_learn(void * solver, void * data, int max_length, void (*learn)(void * data, int32_t * clause));
+
+#define CHECK(cond) do { if (!(cond)) { fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); exit(1); } } while (0)
+
+
+static void add(void * solver, const int32_t * lits) {
+    while (*lits) ipasir_add(solver, *lits++);
+    ipasir_add(solver, 0);
+}
+
+
+static int stop(void * data) {
+    ++*(int *)data;
+    return 1;
+}
+
+
+struct learnt { int count; int max_length; int max_var; };
+
+static void learnt(void * data, int32_t * clause) {
+    struct learnt * l = data;
+    int length = 0;
+    for (; clause[length]; length++) CHECK(abs(clause[length]) <= l->max_var);
+    CHECK(length > 0 && length <= l->max_length);
+    l->count++;
+}
+
+
+/* Pigeons 'p + 1' into 'p' holes, variable 'i * p + j + 1' puts pigeon 'i' into hole 'j': */
+static void pigeonhole(void * solver, int p) {
+    for (int i = 0; i <= p; i++) {
+        for (int j = 0; j < p; j++) ipasir_add(solver, i * p + j + 1);
+        ipasir_add(solver, 0);
+    }
+    for (int j = 0; j < p; j++) {
+        for (int i = 0; i <= p; i++) {
+            for (int k = i + 1; k <= p; k++) {
+                int32_t c[] = { -(i * p + j + 1), -(k * p + j + 1), 0 };
+                add(solver, c);
+            }
+        }
+    }
+}
+
+
+int main(void) {
+    CHECK(ipasir_signature() != NULL && ipasir_signature()[0] != '\0');
+
+    void * solver = ipasir_init();
+    int32_t c1[] = { 1, 2, 0 }, c2[] = { -1, 2, 0 }, c3[] = { -2, 3, 0 };
+    add(solver, c1);
+    add(solver, c2);
+    add(solver, c3);
+
+    CHECK(ipasir_solve(solver) == 10);
+    CHECK(ipasir_val(solver, 2) == 2 && ipasir_val(solver, -2) == 2 && ipasir_val(solver, 3) == 3);
+    CHECK(ipasir_val(solver, 1) == 1 || ipasir_val(solver, 1) == -1);
+    CHECK(ipasir_val(solver, 0) == 0 && ipasir_val(solver, INT32_MIN) == 0 && ipasir_val(solver, 7) == 0);
+
+    /* Assumptions hold for one call, and only those used in the refutation fail: */
+    ipasir_assume(solver, 1);
+    ipasir_assume(solver, -3);
+    CHECK(ipasir_solve(solver) == 20);
+    CHECK(ipasir_failed(solver, -3) && !ipasir_failed(solver, 1));
+    CHECK(!ipasir_failed(solver, 0) && !ipasir_failed(solver, INT32_MIN));
+
+    ipasir_assume(solver, 1);
+    CHECK(ipasir_solve(solver) == 10);
+    CHECK(ipasir_val(solver, 1) == 1);
+
+    /* Variables that first appear in assumptions: */
+    ipasir_assume(solver, -5);
+    CHECK(ipasir_solve(solver) == 10);
+    CHECK(ipasir_val(solver, 5) == -5 && ipasir_val(solver, -5) == -5);
+
+    int calls = 0;
+    ipasir_set_terminate(solver, &calls, stop);
+    CHECK(ipasir_solve(solver) == 0 && calls > 0);
+    ipasir_set_terminate(solver, NULL, NULL);
+    CHECK(ipasir_solve(solver) == 10);
+
+    add(solver, (int32_t[]){ -3, 0 });
+    CHECK(ipasir_solve(solver) == 20);
+    CHECK(ipasir_solve(solver) == 20);
+    ipasir_release(solver);
+
+    /* Once unsatisfiable without assumptions, none of them fail: */
+    solver = ipasir_init();
+    pigeonhole(solver, 5);
+    struct learnt l = { 0, 3, 30 };
+    ipasir_set_learn(solver, &l, l.max_length, learnt);
+    CHECK(ipasir_solve(solver) == 20);
+    CHECK(l.count > 0);
+    ipasir_assume(solver, 1);
+    CHECK(ipasir_solve(solver) == 20);
+    CHECK(!ipasir_failed(solver, 1));
+    ipasir_release(solver);
+
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,29 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+
+// Build the C driver against the shared library next to the test executable and run it. Skipped
+// if there is no C compiler:
+#[test]
+fn c_driver() {
+    let exe = env::current_exe().unwrap();
+    let lib_dir = exe.parent().and_then(|deps| deps.parent()).unwrap();
+    let driver = lib_dir.join("ipasir-driver");
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("ipasir.c");
+
+    let cc = env::var("CC").unwrap_or(String::from("cc"));
+    let status = Command::new(&cc)
+        .arg("-std=c99").arg("-o").arg(&driver).arg(&source)
+        .arg("-L").arg(lib_dir).arg("-lminisat_rust")
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .status();
+    match status {
+        Ok(status) => { assert!(status.success(), "failed to build the driver"); }
+        Err(err)   => { println!("skipped, no C compiler '{}': {}", cc, err); return; }
+    }
+
+    let output = Command::new(&driver).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
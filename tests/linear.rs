@@ -0,0 +1,162 @@
+extern crate minisat_rust;
+
+mod common;
+
+use minisat_rust::sat::{PartialResult, Solver, LinearSolver};
+use minisat_rust::sat::allsat::Enumerator;
+use minisat_rust::sat::encode;
+use minisat_rust::sat::formula::{Var, Lit};
+use minisat_rust::sat::minisat::CoreSolver;
+use common::Random;
+
+
+// Constraint over 'n' variables with a few terms, some of them negative or on the same variable:
+fn constraint(rnd : &mut Random, n : usize) -> (Vec<(u64, usize, bool)>, u64) {
+    let len = 2 + rnd.next(5) as usize;
+    let terms : Vec<(u64, usize, bool)> = (0 .. len).map(|_| (1 + rnd.next(6), rnd.next(n as u64) as usize, rnd.next(3) == 0)).collect();
+    let total : u64 = terms.iter().map(|&(a, _, _)| a).sum();
+    let bound = 1 + rnd.next(total);
+    (terms, bound)
+}
+
+
+fn to_lits(vars : &[Var], terms : &[(u64, usize, bool)]) -> Vec<(u64, Lit)> {
+    terms.iter().map(|&(a, i, neg)| (a, vars[i].lit(neg))).collect()
+}
+
+
+fn satisfies(mask : usize, terms : &[(u64, usize, bool)], bound : u64) -> bool {
+    terms.iter().filter(|&&(_, i, neg)| (mask & (1 << i) != 0) != neg).map(|&(a, _, _)| a).sum::<u64>() >= bound
+}
+
+
+#[test]
+fn models_match_brute_force() {
+    let mut rnd = Random(7);
+    let n = 8;
+    for _ in 0 .. 200 {
+        let constrs : Vec<(Vec<(u64, usize, bool)>, u64)> = (0 .. 1 + rnd.next(6)).map(|_| constraint(&mut rnd, n)).collect();
+        let expected = (0 .. 1 << n).filter(|&mask| constrs.iter().all(|&(ref terms, bound)| satisfies(mask, terms, bound))).count();
+
+        let mut solver = CoreSolver::new(Default::default());
+        let vars : Vec<Var> = (0 .. n).map(|_| solver.newVar(None, true)).collect();
+        let mut ok = true;
+        for &(ref terms, bound) in constrs.iter() {
+            ok &= solver.addAtLeast(&to_lits(&vars, terms), bound);
+        }
+
+        let count =
+            if ok {
+                let mut en = Enumerator::new(solver, false);
+                en.setProjection(vars);
+                en.count()
+            } else {
+                0
+            };
+        assert_eq!(count, expected, "{:?}", constrs);
+    }
+}
+
+
+#[test]
+fn native_agrees_with_encoding() {
+    let mut rnd = Random(11);
+    let n = 40;
+    for round in 0 .. 40 {
+        let constrs : Vec<(Vec<(u64, usize, bool)>, u64)> = (0 .. 60 + round).map(|_| constraint(&mut rnd, n)).collect();
+
+        let mut native = CoreSolver::new(Default::default());
+        let mut encoded = CoreSolver::new(Default::default());
+        let native_vars : Vec<Var> = (0 .. n).map(|_| native.newVar(None, true)).collect();
+        let encoded_vars : Vec<Var> = (0 .. n).map(|_| encoded.newVar(None, true)).collect();
+        for &(ref terms, bound) in constrs.iter() {
+            native.addAtLeast(&to_lits(&native_vars, terms), bound);
+            encode::atLeastPB(&mut encoded, &to_lits(&encoded_vars, terms), bound, None);
+        }
+
+        // Some assumptions, so that the solvers have to backtrack over them:
+        let assumptions : Vec<usize> = (0 .. 3).map(|_| rnd.next(n as u64) as usize).collect();
+        let native_result = native.solveLimited(&assumptions.iter().map(|&i| native_vars[i].posLit()).collect::<Vec<Lit>>());
+        let encoded_result = encoded.solveLimited(&assumptions.iter().map(|&i| encoded_vars[i].posLit()).collect::<Vec<Lit>>());
+        match (native_result, encoded_result) {
+            (PartialResult::SAT(model), PartialResult::SAT(_)) => {
+                let mask = native_vars.iter().enumerate().filter(|&(_, v)| model[v]).fold(0u64, |acc, (i, _)| acc | (1 << i));
+                for &(ref terms, bound) in constrs.iter() {
+                    let sum : u64 = terms.iter().filter(|&&(_, i, neg)| (mask & (1 << i) != 0) != neg).map(|&(a, _, _)| a).sum();
+                    assert!(sum >= bound);
+                }
+                assert!(assumptions.iter().all(|&i| model[&native_vars[i]]));
+            }
+
+            (PartialResult::UnSAT, PartialResult::UnSAT)                 => {}
+            (PartialResult::UnSATUnder(_), PartialResult::UnSATUnder(_)) => {}
+            (PartialResult::UnSAT, PartialResult::UnSATUnder(_))         => {}
+            (PartialResult::UnSATUnder(_), PartialResult::UnSAT)         => {}
+            _                                                            => { panic!("results differ in round {}", round); }
+        }
+    }
+}
+
+
+#[test]
+fn cardinality_with_clauses() {
+    // Pigeons into holes: every pigeon in some hole, at most one pigeon per hole:
+    for &(pigeons, holes, sat) in [(5, 5, true), (6, 5, false)].iter() {
+        let mut solver = CoreSolver::new(Default::default());
+        let x : Vec<Vec<Lit>> = (0 .. pigeons).map(|_| (0 .. holes).map(|_| solver.newVar(None, true).posLit()).collect()).collect();
+        for p in 0 .. pigeons {
+            solver.addClause(&x[p]);
+        }
+        for h in 0 .. holes {
+            let terms : Vec<(u64, Lit)> = (0 .. pigeons).map(|p| (1, !x[p][h])).collect();
+            solver.addAtLeast(&terms, (pigeons - 1) as u64);
+        }
+
+        match solver.solveLimited(&[]) {
+            PartialResult::SAT(model) => {
+                assert!(sat);
+                let value = |lit : Lit| -> bool { model[&lit.var()] != lit.sign() };
+                for h in 0 .. holes {
+                    assert!((0 .. pigeons).filter(|&p| value(x[p][h])).count() <= 1);
+                }
+            }
+            PartialResult::UnSAT      => { assert!(!sat); }
+            _                         => { panic!("unexpected result"); }
+        }
+    }
+
+    // Unsatisfiable right away, and merged terms on the same variable:
+    let mut solver = CoreSolver::new(Default::default());
+    let a = solver.newVar(None, true);
+    let b = solver.newVar(None, true);
+    assert!(solver.addAtLeast(&[(2, a.posLit()), (3, a.negLit()), (1, b.posLit())], 4));
+    match solver.solveLimited(&[]) {
+        PartialResult::SAT(model) => { assert!(!model[&a] && model[&b]); }
+        _                         => { panic!("expected SAT"); }
+    }
+    assert!(!solver.addAtLeast(&[(1, a.posLit()), (1, b.negLit())], 1));
+}
+
+
+#[test]
+fn released_variables_keep_their_terms() {
+    let mut solver = CoreSolver::new(Default::default());
+    let x = solver.newVar(None, true);
+    let y = solver.newVar(None, true);
+    solver.addAtLeast(&[(1, x.posLit()), (1, y.posLit())], 1);
+    solver.releaseVar(x.negLit());
+    assert!(solver.simplify());
+
+    // The constraint still forces 'y', whatever a fresh variable is set to:
+    let w = solver.newVar(None, true);
+    assert!(w != x);
+    solver.addClause(&[w.posLit()]);
+    match solver.solveLimited(&[]) {
+        PartialResult::SAT(model) => { assert!(model[&y]); }
+        _                         => { panic!("expected SAT"); }
+    }
+    match solver.solveLimited(&[y.negLit()]) {
+        PartialResult::SAT(_) => { panic!("expected UNSAT"); }
+        _                     => {}
+    }
+}
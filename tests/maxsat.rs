@@ -0,0 +1,54 @@
+extern crate minisat_rust;
+
+use std::io::Cursor;
+use minisat_rust::sat::dimacs;
+use minisat_rust::sat::formula::Var;
+use minisat_rust::sat::maxsat::{MaxSatSolver, MaxSatResult};
+
+
+fn solve(wcnf : &str) -> Option<u64> {
+    let mut clauses = Vec::new();
+    dimacs::parseWeighted(&mut Cursor::new(wcnf.as_bytes()), |weight, raw| { clauses.push((weight, raw)); }).unwrap();
+
+    let mut solver = MaxSatSolver::new(Default::default());
+    let vars : Vec<Var> = (0 .. 4).map(|_| solver.newVar()).collect();
+    for &(weight, ref raw) in clauses.iter() {
+        let lits : Vec<_> = raw.iter().map(|&id| vars[(id.abs() as usize) - 1].lit(id < 0)).collect();
+        match weight {
+            Some(w) => { solver.addSoft(&lits, w); }
+            None    => { solver.addHard(&lits); }
+        }
+    }
+
+    let mut last = None;
+    match solver.solve(|cost, _| { assert!(last.map(|c| cost < c).unwrap_or(true)); last = Some(cost); }) {
+        MaxSatResult::Optimum(cost, model) => {
+            assert_eq!(Some(cost), last);
+            assert_eq!(solver.cost(&model), cost);
+            Some(cost)
+        }
+
+        MaxSatResult::UnSAT                => { None }
+        MaxSatResult::Interrupted(_)       => { panic!("unexpected interrupt") }
+    }
+}
+
+
+#[test]
+fn weighted_partial_formats() {
+    // Exactly one of 1, 2, 3 and 4 ⇒ 1; each soft unit wants its variable true.
+    let old = "c old format\np wcnf 4 9 100\n100 1 2 3 0\n100 -1 -2 0\n100 -1 -3 0\n100 -2 -3 0\n100 -4 1 0\n\
+               4 1 0\n3 2 0\n5 3 0\n3 4 0\n";
+    assert_eq!(solve(old), Some(8));
+
+    let new = "c 2022 format\nh 1 2 3 0\nh -1 -2 0\nh -1 -3 0\nh -2 -3 0\nh -4 1 0\n4 1 0\n3 2 0\n5 3 0\n3 4 0\n";
+    assert_eq!(solve(new), Some(8));
+}
+
+
+#[test]
+fn unweighted_and_unsatisfiable() {
+    // All soft: at most two of the four clauses can hold.
+    assert_eq!(solve("p wcnf 2 4\n1 1 0\n1 -1 0\n1 2 0\n1 -2 0\n"), Some(2));
+    assert_eq!(solve("h 1 0\nh -1 0\n3 2 0\n"), None);
+}
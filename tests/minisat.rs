@@ -58,7 +58,13 @@ fn run_me(cnf_path : &path::Path) -> io::Result<String> {
             solve       : true,
             in_path     : cnf_path.to_path_buf(),
             out_path    : Some(result.path().to_path_buf()),
-            dimacs_path : None
+            dimacs_path : None,
+            proof_path  : None,
+            proof_bin   : false,
+            enumerate   : None,
+            shrink      : false,
+            backbone    : false,
+            threads     : 1
         };
 
     try!(solve(opts, SolverOptions::Simp(Default::default())));
@@ -0,0 +1,73 @@
+extern crate minisat_rust;
+
+use minisat_rust::sat::formula::{Var, Lit};
+use minisat_rust::sat::mus::{MusExtractor, MusResult};
+
+
+fn vars(extractor : &mut MusExtractor, n : usize) -> Vec<Var> {
+    (0 .. n).map(|_| extractor.newVar()).collect()
+}
+
+
+fn lits(v : &[Var], ids : &[i32]) -> Vec<Lit> {
+    ids.iter().map(|&id| v[(id.abs() as usize) - 1].lit(id < 0)).collect()
+}
+
+
+fn extract(clauses : &[(Option<usize>, &[i32])]) -> MusResult {
+    let mut extractor = MusExtractor::new(Default::default());
+    let v = vars(&mut extractor, 4);
+    for &(group, ids) in clauses.iter() {
+        extractor.addClause(group, &lits(&v, ids));
+    }
+    extractor.extract()
+}
+
+
+#[test]
+fn clause_mus() {
+    // 1: a, 2: ¬a ∨ b, 3: ¬b, 4: c ∨ d, 5: ¬a ∨ ¬c, 6: ¬b
+    let res = extract(&[ (Some(1), &[1])
+                       , (Some(2), &[-1, 2])
+                       , (Some(3), &[-2])
+                       , (Some(4), &[3, 4])
+                       , (Some(5), &[-1, -3])
+                       , (Some(6), &[-2])
+                       ]);
+    match res {
+        MusResult::Mus(groups) => {
+            assert!(groups == vec![1, 2, 3] || groups == vec![1, 2, 6]);
+        }
+        _                      => { panic!("expected MUS") }
+    }
+}
+
+
+#[test]
+fn group_mus_with_hard_clauses() {
+    // Hard: a ∨ b. Group 7 forbids both, group 3 is irrelevant.
+    let res = extract(&[ (None, &[1, 2])
+                       , (Some(7), &[-1])
+                       , (Some(3), &[3])
+                       , (Some(7), &[-2])
+                       , (Some(3), &[-3, 4])
+                       ]);
+    match res {
+        MusResult::Mus(groups) => { assert_eq!(groups, vec![7]); }
+        _                      => { panic!("expected MUS") }
+    }
+
+    match extract(&[(None, &[1]), (None, &[-1]), (Some(1), &[2])]) {
+        MusResult::Mus(groups) => { assert!(groups.is_empty()); }
+        _                      => { panic!("expected empty MUS") }
+    }
+}
+
+
+#[test]
+fn satisfiable_formula() {
+    match extract(&[(Some(1), &[1, 2]), (Some(2), &[-1])]) {
+        MusResult::SAT => {}
+        _              => { panic!("expected SAT") }
+    }
+}
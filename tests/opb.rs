@@ -0,0 +1,151 @@
+extern crate minisat_rust;
+
+use minisat_rust::sat::allsat::Enumerator;
+use minisat_rust::sat::encode::PbEncoding;
+use minisat_rust::sat::minisat::CoreSolver;
+use minisat_rust::sat::opb::{self, Search, OptResult};
+
+
+const ENCODINGS : [Option<PbEncoding>; 4] = [None, Some(PbEncoding::Bdd), Some(PbEncoding::Adder), Some(PbEncoding::SortingNetwork)];
+
+
+// Number of assignments to 'x1 .. xn' that satisfy the instance:
+fn count(text : &str, n : usize, encoding : Option<PbEncoding>) -> usize {
+    let mut en = Enumerator::new(CoreSolver::new(Default::default()), false);
+    let parsed = opb::parse(&mut text.as_bytes(), &mut en, encoding).unwrap();
+    assert_eq!(parsed.backward_subst.iter().count(), n);
+
+    let vars = parsed.backward_subst.iter().map(|(v, _)| v).collect();
+    en.setProjection(vars);
+    en.count()
+}
+
+
+#[test]
+fn encodings_match_brute_force() {
+    let mut seed : u64 = 12345;
+    let mut next = |m : u64| -> i64 {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((seed >> 33) % m) as i64
+    };
+
+    let n = 5;
+    for _ in 0 .. 60 {
+        let coefs : Vec<i64> = (0 .. n).map(|_| next(13) - 6).collect();
+        let rel = [">=", "<=", "="][next(3) as usize];
+        let rhs = next(15) - 5;
+
+        let mut text = String::from("* random constraint\n");
+        for (i, c) in coefs.iter().enumerate() {
+            text.push_str(&format!("{:+} x{} ", c, i + 1));
+        }
+        text.push_str(&format!("{} {};\n", rel, rhs));
+
+        let expected = (0 .. 1 << n).filter(|&mask : &usize| {
+            let sum : i64 = (0 .. n).filter(|&i| mask & (1 << i) != 0).map(|i| coefs[i]).sum();
+            match rel { ">=" => { sum >= rhs } "<=" => { sum <= rhs } _ => { sum == rhs } }
+        }).count();
+
+        for &encoding in ENCODINGS.iter() {
+            assert_eq!(count(&text, n, encoding), expected, "{:?} {}", encoding, text);
+        }
+    }
+}
+
+
+#[test]
+fn products_and_negations() {
+    // 'x1 x2' is the conjunction, '~x3' the negation:
+    let text = "2 x1 x2 +1 ~x3 >= 2 ;\n-1 x1 -1 x2 >= -1;\n";
+    for &encoding in ENCODINGS.iter() {
+        assert_eq!(count(text, 3, encoding), 0);
+    }
+
+    let text = "2 x1 x2 +1 ~x3 >= 2 ;\n";
+    for &encoding in ENCODINGS.iter() {
+        assert_eq!(count(text, 3, encoding), 2);
+    }
+}
+
+
+#[test]
+fn minimize_objective() {
+    // Cover the edges of a 5-cycle with weighted vertices; the optimum takes x1, x3 and x4:
+    let text = "* #variable= 5 #constraint= 5\n\
+                min: +3 x1 +4 x2 +2 x3 +3 x4 +5 x5 ;\n\
+                +1 x1 +1 x2 >= 1 ;\n\
+                +1 x2 +1 x3 >= 1 ;\n\
+                +1 x3 +1 x4 >= 1 ;\n\
+                +1 x4 +1 x5 >= 1 ;\n\
+                +1 x5 +1 x1 >= 1 ;\n";
+
+    for &search in [Search::Linear, Search::Binary].iter() {
+        for &encoding in ENCODINGS.iter() {
+            let mut solver = CoreSolver::new(Default::default());
+            let parsed = opb::parse(&mut text.as_bytes(), &mut solver, encoding).unwrap();
+            let objective = parsed.objective.unwrap();
+
+            let mut costs = Vec::new();
+            match opb::minimize(&mut solver, &objective, search, encoding, |cost, _| { costs.push(cost); }) {
+                OptResult::Optimum(cost, model) => {
+                    assert_eq!(cost, 8);
+                    assert_eq!(opb::value(&objective, &model), 8);
+                }
+                _                               => { panic!("expected an optimum") }
+            }
+
+            // Every reported model improves on the previous one:
+            assert!(costs.windows(2).all(|w| w[1] < w[0]));
+            assert_eq!(costs.last(), Some(&8));
+        }
+    }
+}
+
+
+#[test]
+fn negative_objective_and_unsatisfiable() {
+    let text = "min: -2 x1 -3 x2 +1 x3 ;\n+1 x1 +1 x2 <= 1 ;\n";
+    for &search in [Search::Linear, Search::Binary].iter() {
+        let mut solver = CoreSolver::new(Default::default());
+        let parsed = opb::parse(&mut text.as_bytes(), &mut solver, None).unwrap();
+        match opb::minimize(&mut solver, &parsed.objective.unwrap(), search, None, |_, _| {}) {
+            OptResult::Optimum(cost, _) => { assert_eq!(cost, -3); }
+            _                           => { panic!("expected an optimum") }
+        }
+    }
+
+    let text = "min: +1 x1 ;\n+1 x1 +1 x2 >= 2 ;\n-1 x2 = 0 ;\n";
+    let mut solver = CoreSolver::new(Default::default());
+    let parsed = opb::parse(&mut text.as_bytes(), &mut solver, None).unwrap();
+    match opb::minimize(&mut solver, &parsed.objective.unwrap(), Search::Binary, None, |_, _| {}) {
+        OptResult::UnSAT => {}
+        _                => { panic!("expected UNSAT") }
+    }
+
+    assert!(opb::parse(&mut "+1 x1 >= ;".as_bytes(), &mut CoreSolver::new(Default::default()), None).is_err());
+}
+
+
+#[test]
+fn coefficients_out_of_range() {
+    let texts = [ "-9223372036854775808 x1 >= 9223372036854775807 ;"
+                , "-9223372036854775808 x1 <= 0 ;"
+                , "+1 x1 = -9223372036854775808 ;"
+                , "+9223372036854775807 x1 +9223372036854775807 x2 +2 x3 >= 1 ;"
+                ];
+    for text in texts.iter() {
+        assert!(opb::parse(&mut text.as_bytes(), &mut CoreSolver::new(Default::default()), None).is_err());
+    }
+
+    let text = "-9223372036854775808 x1 +9223372036854775807 x2 >= -1 ;";
+    assert_eq!(count(text, 2, None), 3);
+}
+
+
+#[test]
+fn parsing_stops_at_contradiction() {
+    let text = "+1 x1 >= 2 ;\n+1 x2 >= 1 ;\n";
+    let mut solver = CoreSolver::new(Default::default());
+    let parsed = opb::parse(&mut text.as_bytes(), &mut solver, None).unwrap();
+    assert_eq!(parsed.backward_subst.iter().count(), 1);
+}
@@ -0,0 +1,241 @@
+extern crate tempfile;
+extern crate minisat_rust;
+
+mod common;
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+use minisat_rust::sat::{PartialResult, TotalResult, Solver};
+use minisat_rust::sat::allsat::Enumerator;
+use minisat_rust::sat::formula::{Var, Lit};
+use minisat_rust::sat::minisat::{self, CoreSolver};
+use minisat_rust::sat::minisat::portfolio::{self, Portfolio};
+use common::{Random, pigeonhole};
+
+
+fn load<S : Solver>(solver : &mut S, n : usize, clauses : &[Vec<(usize, bool)>]) -> Vec<Var> {
+    let vars : Vec<Var> = (0 .. n).map(|_| solver.newVar(None, true)).collect();
+    for c in clauses.iter() {
+        solver.addClause(&c.iter().map(|&(v, sign)| vars[v].lit(sign)).collect::<Vec<Lit>>());
+    }
+    vars
+}
+
+
+fn portfolio(threads : usize) -> Portfolio {
+    Portfolio::new(portfolio::diversify(&Default::default(), threads))
+}
+
+
+#[test]
+fn agrees_with_core_solver() {
+    let mut rnd = Random(11);
+    let n = 120;
+    for round in 0 .. 30 {
+        let clauses = rnd.clauses(n, 480 + 5 * round);
+
+        let mut core = CoreSolver::new(Default::default());
+        load(&mut core, n, &clauses);
+        let mut solver = portfolio(4);
+        let vars = load(&mut solver, n, &clauses);
+        match (solver.solve(), core.solve()) {
+            (TotalResult::SAT(model), TotalResult::SAT(_)) => {
+                for c in clauses.iter() {
+                    assert!(c.iter().any(|&(v, sign)| model[&vars[v]] != sign), "round {}", round);
+                }
+            }
+
+            (TotalResult::UnSAT, TotalResult::UnSAT)       => {}
+            _                                              => { panic!("results differ in round {}", round); }
+        }
+    }
+}
+
+
+#[test]
+fn enumerates_every_model() {
+    let mut rnd = Random(13);
+    let n = 8;
+    for round in 0 .. 20 {
+        let clauses = rnd.clauses(n, 10 + round);
+        let expected = (0 .. 1 << n).filter(|&mask : &usize| {
+            clauses.iter().all(|c| c.iter().any(|&(v, sign)| (mask & (1 << v) != 0) != sign))
+        }).count();
+
+        let mut en = Enumerator::new(portfolio(3), false);
+        load(&mut en, n, &clauses);
+        assert_eq!(en.count(), expected, "round {}", round);
+    }
+}
+
+
+#[test]
+fn shared_clauses_are_implied() {
+    let mut rnd = Random(17);
+    let n = 150;
+    let clauses = rnd.clauses(n, 640);
+
+    let shared = Rc::new(RefCell::new(Vec::new()));
+    let mut solver = portfolio(2);
+    load(&mut solver, n, &clauses);
+    {
+        let shared = shared.clone();
+        solver.setLearnCallback(3, Some(Box::new(move |lits : &[Lit], _| { shared.borrow_mut().push(lits.to_vec()); })));
+    }
+    solver.solve();
+
+    let shared = shared.borrow();
+    assert!(!shared.is_empty());
+    for lits in shared.iter().take(50) {
+        assert!(lits.len() <= 3);
+        let mut check = CoreSolver::new(Default::default());
+        load(&mut check, n, &clauses);
+        if let PartialResult::SAT(_) = check.solveLimited(&lits.iter().map(|&lit| !lit).collect::<Vec<Lit>>()) {
+            panic!("shared clause {:?} is not implied", lits);
+        }
+    }
+}
+
+
+#[test]
+fn terminate_interrupts_every_thread() {
+    // Pigeons 11 into 10 holes are out of reach:
+    let mut solver = portfolio(3);
+    pigeonhole(&mut solver, 10);
+
+    let mut polls = 0;
+    solver.setTerminateCallback(Some(Box::new(move || { polls += 1; polls > 5 })));
+    match solver.solve() {
+        TotalResult::Interrupted => {}
+        _                        => { panic!("expected the search to stop"); }
+    }
+}
+
+
+#[test]
+fn imported_clauses_are_added_at_restarts() {
+    let mut rnd = Random(19);
+    let n = 60;
+    let clauses = rnd.clauses(n, 220);
+
+    let mut check = CoreSolver::new(Default::default());
+    load(&mut check, n, &clauses);
+    let model =
+        match check.solve() {
+            TotalResult::SAT(model) => { model }
+            _                       => { panic!("expected SAT"); }
+        };
+
+    // Units of a model, one per restart. The ones handed out before the answer must hold in it:
+    let mut settings : minisat::Settings = Default::default();
+    settings.restart.restart_first = 1.0;
+    let mut solver = CoreSolver::new(settings.clone());
+    let vars = load(&mut solver, n, &clauses);
+    let handed = Rc::new(RefCell::new(Vec::new()));
+    {
+        let handed = handed.clone();
+        let mut units : Vec<Lit> = vars.iter().map(|&v| v.lit(!model[&v])).collect();
+        solver.setImportCallback(Some(Box::new(move || {
+            units.pop().map(|lit| { handed.borrow_mut().push(lit); vec![vec![lit]] }).unwrap_or(Vec::new())
+        })));
+    }
+    match solver.solve() {
+        TotalResult::SAT(found) => {
+            assert!(!handed.borrow().is_empty());
+            assert!(handed.borrow().iter().all(|lit| found[&lit.var()] != lit.sign()));
+        }
+        _                       => { panic!("expected SAT"); }
+    }
+
+    // A contradiction from outside ends the search at the first restart:
+    let mut solver = CoreSolver::new(settings);
+    let p = 6;
+    let holes : Vec<Vec<Var>> = (0 .. p + 1).map(|_| (0 .. p).map(|_| solver.newVar(None, true)).collect()).collect();
+    for row in holes.iter() {
+        solver.addClause(&row.iter().map(|v| v.posLit()).collect::<Vec<Lit>>());
+    }
+    for j in 0 .. p {
+        for i in 0 .. p + 1 {
+            for k in i + 1 .. p + 1 {
+                solver.addClause(&[holes[i][j].negLit(), holes[k][j].negLit()]);
+            }
+        }
+    }
+
+    let calls = Rc::new(RefCell::new(0));
+    {
+        let calls = calls.clone();
+        let x = holes[0][0];
+        solver.setImportCallback(Some(Box::new(move || {
+            *calls.borrow_mut() += 1;
+            vec![vec![x.posLit()], vec![x.negLit()]]
+        })));
+    }
+    match solver.solve() {
+        TotalResult::UnSAT => { assert_eq!(*calls.borrow(), 1); }
+        _                  => { panic!("expected UNSAT"); }
+    }
+}
+
+
+#[test]
+fn proofs_are_refused() {
+    let mut input = tempfile::NamedTempFile::new().unwrap();
+    input.write_all(b"p cnf 2 2\n1 2 0\n-1 0\n").unwrap();
+    input.flush().unwrap();
+    let proof = tempfile::NamedTempFile::new().unwrap();
+
+    let options =
+        minisat_rust::MainOptions {
+            strict      : true,
+            pre         : true,
+            solve       : true,
+            in_path     : input.path().to_path_buf(),
+            out_path    : None,
+            dimacs_path : None,
+            proof_path  : Some(proof.path().to_path_buf()),
+            proof_bin   : false,
+            enumerate   : None,
+            shrink      : false,
+            backbone    : false,
+            threads     : 2
+        };
+    assert!(minisat_rust::solve(options, minisat_rust::SolverOptions::Core(Default::default())).is_err());
+}
+
+
+#[test]
+fn options_without_threads_are_refused() {
+    let mut input = tempfile::NamedTempFile::new().unwrap();
+    input.write_all(b"p cnf 2 2\n1 2 0\n-1 0\n").unwrap();
+    input.flush().unwrap();
+    let dimacs = tempfile::NamedTempFile::new().unwrap();
+
+    let options = |pre, dimacs_path : Option<&tempfile::NamedTempFile>, backbone| {
+        minisat_rust::MainOptions {
+            strict      : true,
+            pre         : pre,
+            solve       : true,
+            in_path     : input.path().to_path_buf(),
+            out_path    : None,
+            dimacs_path : dimacs_path.map(|file| file.path().to_path_buf()),
+            proof_path  : None,
+            proof_bin   : false,
+            enumerate   : None,
+            shrink      : false,
+            backbone    : backbone,
+            threads     : 2
+        }
+    };
+    let core = || minisat_rust::SolverOptions::Core(Default::default());
+    let simp = || minisat_rust::SolverOptions::Simp(Default::default());
+
+    assert!(minisat_rust::solve(options(true, None, true), core()).is_err());
+    assert!(minisat_rust::solve(options(true, Some(&dimacs), false), core()).is_err());
+    assert!(minisat_rust::solve(options(true, None, false), simp()).is_err());
+
+    // Without preprocessing the core settings of a simplifying solver do:
+    assert!(minisat_rust::solve(options(false, None, false), simp()).is_ok());
+    assert!(minisat_rust::solve(options(true, None, false), core()).is_ok());
+}
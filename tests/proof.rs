@@ -0,0 +1,248 @@
+extern crate tempfile;
+extern crate minisat_rust;
+
+mod common;
+
+use std::fs;
+use std::io::{self, Cursor, Read};
+use minisat_rust::sat::{dimacs, encode, PartialResult, Solver, LinearSolver};
+use minisat_rust::sat::formula::Lit;
+use minisat_rust::sat::minisat::{CoreSolver, Proof, ProofFormat};
+use minisat_rust::sat::minisat::simp::SimpSolver;
+use minisat_rust::sat::proof::{self, CheckResult};
+use common::pigeonhole;
+
+
+fn write_proof<F : FnOnce(Proof)>(format : ProofFormat, run : F) -> Vec<u8> {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    run(Proof::new(Box::new(fs::File::create(file.path()).unwrap()), format));
+
+    let mut buf = Vec::new();
+    fs::File::open(file.path()).unwrap().read_to_end(&mut buf).unwrap();
+    buf
+}
+
+
+#[test]
+fn text_proof_ends_with_empty_clause() {
+    let proof = write_proof(ProofFormat::Text, |proof| {
+        let mut solver = CoreSolver::new(Default::default());
+        solver.setProof(proof);
+        pigeonhole(&mut solver, 5);
+        match solver.solveLimited(&[]) {
+            PartialResult::UnSAT => {}
+            _                    => { panic!("expected UNSAT") }
+        }
+    });
+
+    let text = String::from_utf8(proof).unwrap();
+    assert!(text.lines().all(|line| line == "0" || line.ends_with(" 0")));
+    assert_eq!(text.lines().last(), Some("0"));
+}
+
+
+#[test]
+fn binary_proof_ends_with_empty_clause() {
+    let proof = write_proof(ProofFormat::Binary, |proof| {
+        let mut solver = SimpSolver::new(Default::default());
+        solver.setProof(proof);
+        pigeonhole(&mut solver, 5);
+        match solver.solveLimited(&[], true, false) {
+            PartialResult::UnSAT => {}
+            _                    => { panic!("expected UNSAT") }
+        }
+    });
+
+    assert!(proof.len() > 2);
+    assert_eq!(proof[0], b'a');
+    assert_eq!(&proof[proof.len() - 2 ..], &[b'a', 0]);
+}
+
+
+fn pigeonhole_cnf(n : usize) -> String {
+    let var = |i : usize, h : usize| i * n + h + 1;
+    let mut clauses = Vec::new();
+    for i in 0 .. n + 1 {
+        clauses.push((0 .. n).map(|h| format!("{} ", var(i, h))).collect::<String>());
+    }
+
+    for h in 0 .. n {
+        for i in 0 .. n + 1 {
+            for j in i + 1 .. n + 1 {
+                clauses.push(format!("-{} -{} ", var(i, h), var(j, h)));
+            }
+        }
+    }
+
+    let mut cnf = format!("p cnf {} {}\n", n * (n + 1), clauses.len());
+    for c in clauses.iter() {
+        cnf.push_str(c);
+        cnf.push_str("0\n");
+    }
+    cnf
+}
+
+
+#[test]
+fn drat_proof_is_verified_and_trimmed() {
+    for &format in [ProofFormat::Text, ProofFormat::Binary].iter() {
+        let cnf = pigeonhole_cnf(5);
+        let drat = write_proof(format, |proof| {
+            let mut solver = SimpSolver::new(Default::default());
+            solver.setProof(proof);
+            dimacs::parse(&mut Cursor::new(cnf.as_bytes()), &mut solver, true).unwrap();
+            match solver.solveLimited(&[], true, false) {
+                PartialResult::UnSAT => {}
+                _                    => { panic!("expected UNSAT") }
+            }
+        });
+
+        let mut lrat = Vec::new();
+        let res = proof::checkDrat(&mut Cursor::new(cnf.as_bytes()), &mut Cursor::new(&drat), Some(&mut lrat)).unwrap();
+        assert_eq!(res, CheckResult::Verified);
+
+        let res = proof::checkLrat(&mut Cursor::new(cnf.as_bytes()), &mut Cursor::new(&lrat)).unwrap();
+        assert_eq!(res, CheckResult::Verified);
+    }
+}
+
+
+#[test]
+fn invalid_proofs_are_rejected() {
+    let cnf = "p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n";
+
+    let res = proof::checkDrat(&mut Cursor::new(cnf), &mut Cursor::new("0\n"), None).unwrap();
+    assert_eq!(res, CheckResult::FailedLemma(0));
+
+    let res = proof::checkDrat(&mut Cursor::new(cnf), &mut Cursor::new(""), None).unwrap();
+    assert_eq!(res, CheckResult::NoConflict);
+
+    let res = proof::checkDrat(&mut Cursor::new(cnf), &mut Cursor::new("1 0\n0\n"), None).unwrap();
+    assert_eq!(res, CheckResult::Verified);
+
+    let res = proof::checkLrat(&mut Cursor::new(cnf), &mut Cursor::new("5 1 0 1 3 0\n6 0 5 2 0\n")).unwrap();
+    assert_eq!(res, CheckResult::FailedLemma(1));
+
+    let res = proof::checkLrat(&mut Cursor::new(cnf), &mut Cursor::new("5 1 0 1 3 0\n6 0 5 4 2 0\n")).unwrap();
+    assert_eq!(res, CheckResult::Verified);
+}
+
+
+#[test]
+fn xors_are_encoded_under_a_proof() {
+    // Two XOR constraints that contradict each other, checked against their clauses:
+    let cnf = "p cnf 3 8\n\
+               -1 -2 -3 0\n-1 2 3 0\n1 -2 3 0\n1 2 -3 0\n\
+               1 -2 -3 0\n1 2 3 0\n-1 -2 3 0\n-1 2 -3 0\n";
+    for &simp in [false, true].iter() {
+        let drat = write_proof(ProofFormat::Text, |proof| {
+            let result =
+                if simp {
+                    let mut solver = SimpSolver::new(Default::default());
+                    solver.setProof(proof);
+                    dimacs::parse(&mut Cursor::new("p cnf 3 2\nx 1 2 3 0\nx -1 2 3 0\n"), &mut solver, true).unwrap();
+                    solver.solveLimited(&[], true, false)
+                } else {
+                    let mut solver = CoreSolver::new(Default::default());
+                    solver.setProof(proof);
+                    dimacs::parse(&mut Cursor::new("p cnf 3 2\nx 1 2 3 0\nx -1 2 3 0\n"), &mut solver, true).unwrap();
+                    solver.solveLimited(&[])
+                };
+            match result {
+                PartialResult::UnSAT => {}
+                _                    => { panic!("expected UNSAT") }
+            }
+        });
+
+        let res = proof::checkDrat(&mut Cursor::new(cnf), &mut Cursor::new(&drat), None).unwrap();
+        assert_eq!(res, CheckResult::Verified, "simp {}", simp);
+    }
+}
+
+
+#[test]
+fn linear_constraints_are_encoded_under_a_proof() {
+    // Every one of 5 pigeons in some hole, and at least 4 pigeons out of each of the 4 holes:
+    let p = 4;
+    let constrain = |solver : &mut CoreSolver, native : bool| {
+        let vars : Vec<Vec<Lit>> = (0 .. p + 1).map(|_| (0 .. p).map(|_| solver.newVar(None, true).posLit()).collect()).collect();
+        for row in vars.iter() {
+            solver.addClause(row);
+        }
+        for j in 0 .. p {
+            let terms : Vec<(u64, Lit)> = vars.iter().map(|row| (1, !row[j])).collect();
+            if native {
+                solver.addAtLeast(&terms, p as u64);
+            } else {
+                encode::atLeastPB(solver, &terms, p as u64, None);
+            }
+        }
+    };
+
+    // The proof is checked against the clauses of the same encoding, with the same variables:
+    let (n, cnf) = {
+        let mut solver = CoreSolver::new(Default::default());
+        constrain(&mut solver, false);
+        let clauses = solver.formula().unwrap().clauses;
+        let mut cnf = format!("p cnf {} {}\n", solver.nVars(), clauses.len());
+        for c in clauses.iter() {
+            for lit in c.iter() {
+                let id = lit.var().index() as i32 + 1;
+                cnf.push_str(&format!("{} ", if lit.sign() { -id } else { id }));
+            }
+            cnf.push_str("0\n");
+        }
+        (solver.nVars(), cnf)
+    };
+
+    let drat = write_proof(ProofFormat::Text, |proof| {
+        let mut solver = CoreSolver::new(Default::default());
+        solver.setProof(proof);
+        constrain(&mut solver, true);
+        assert_eq!(solver.nVars(), n);
+        match solver.solveLimited(&[]) {
+            PartialResult::UnSAT => {}
+            _                    => { panic!("expected UNSAT") }
+        }
+    });
+
+    let res = proof::checkDrat(&mut Cursor::new(cnf.as_bytes()), &mut Cursor::new(&drat), None).unwrap();
+    assert_eq!(res, CheckResult::Verified);
+}
+
+
+// Takes a few bytes, then fails every write:
+struct Full(usize);
+
+impl io::Write for Full {
+    fn write(&mut self, buf : &[u8]) -> io::Result<usize> {
+        if self.0 < buf.len() {
+            return Err(io::Error::new(io::ErrorKind::Other, "no space left"));
+        }
+        self.0 -= buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+
+#[test]
+fn write_errors_are_reported_when_finishing() {
+    let mut solver = CoreSolver::new(Default::default());
+    solver.setProof(Proof::new(Box::new(Full(16)), ProofFormat::Text));
+    pigeonhole(&mut solver, 5);
+    match solver.solveLimited(&[]) {
+        PartialResult::UnSAT => {}
+        _                    => { panic!("expected UNSAT") }
+    }
+    assert!(solver.finishProof().is_err());
+
+    let mut solver = SimpSolver::new(Default::default());
+    solver.setProof(Proof::new(Box::new(Full(1 << 20)), ProofFormat::Text));
+    pigeonhole(&mut solver, 4);
+    solver.solveLimited(&[], true, false);
+    assert!(solver.finishProof().is_ok());
+}
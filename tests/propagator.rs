@@ -0,0 +1,264 @@
+extern crate minisat_rust;
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use minisat_rust::sat::{PartialResult, Solver};
+use minisat_rust::sat::formula::{Var, Lit, VarMap};
+use minisat_rust::sat::minisat::{CoreSolver, UserPropagator};
+use common::Random;
+
+
+#[derive(Default)]
+struct Counts {
+    checks      : usize,
+    rejected    : usize,
+    reasons     : usize,
+    backtracks  : usize
+}
+
+
+// At most 'k' of 'vars' are true. Eagerly, as a propagator with lazy reasons, or only by rejecting
+// complete models:
+struct AtMost {
+    vars    : Vec<Var>,
+    k       : usize,
+    eager   : bool,
+    trail   : Vec<Lit>,   // Assignments of 'vars' as reported by the solver.
+    levels  : Vec<usize>, // Length of 'trail' at the start of every decision level above ground.
+    clauses : Vec<Vec<Lit>>,
+    counts  : Rc<RefCell<Counts>>
+}
+
+impl AtMost {
+    fn trueLits(&self) -> Vec<Lit> {
+        self.trail.iter().cloned().filter(|lit| !lit.sign()).collect()
+    }
+
+    fn isAssigned(&self, v : Var) -> bool {
+        self.trail.iter().any(|lit| lit.var() == v)
+    }
+}
+
+impl UserPropagator for AtMost {
+    fn notifyAssignment(&mut self, lits : &[Lit]) {
+        for &lit in lits {
+            assert!(self.vars.contains(&lit.var()) && !self.isAssigned(lit.var()));
+            self.trail.push(lit);
+        }
+    }
+
+    fn notifyNewDecisionLevel(&mut self) {
+        self.levels.push(self.trail.len());
+    }
+
+    fn notifyBacktrack(&mut self, level : usize) {
+        assert!(level < self.levels.len());
+        self.trail.truncate(self.levels[level]);
+        self.levels.truncate(level);
+        self.counts.borrow_mut().backtracks += 1;
+    }
+
+    fn decide(&mut self) -> Option<Lit> {
+        self.vars.iter().find(|&&v| !self.isAssigned(v)).map(|v| v.posLit())
+    }
+
+    fn propagate(&mut self) -> Vec<Lit> {
+        if !self.eager { return Vec::new(); }
+
+        let ones = self.trueLits();
+        if ones.len() > self.k {
+            // Already falsified, which the solver has to explain right away:
+            vec![!ones[0]]
+        } else if ones.len() == self.k {
+            self.vars.iter().filter(|&&v| !self.isAssigned(v)).map(|v| v.negLit()).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn reason(&mut self, lit : Lit) -> Vec<Lit> {
+        self.counts.borrow_mut().reasons += 1;
+        let mut reason = vec![lit];
+        reason.extend(self.trueLits().into_iter().filter(|&x| x != !lit).take(self.k).map(|x| !x));
+        assert_eq!(reason.len(), self.k + 1);
+        reason
+    }
+
+    fn externalClause(&mut self) -> Option<Vec<Lit>> {
+        self.clauses.pop()
+    }
+
+    fn checkModel(&mut self, model : &VarMap<bool>) -> bool {
+        self.counts.borrow_mut().checks += 1;
+        for &lit in self.trail.iter() {
+            assert_eq!(model[&lit.var()], !lit.sign());
+        }
+        assert_eq!(self.trail.len(), self.vars.len());
+
+        let ones = self.trueLits();
+        if ones.len() <= self.k { return true; }
+
+        self.counts.borrow_mut().rejected += 1;
+        self.clauses.push(ones.iter().take(self.k + 1).map(|&x| !x).collect());
+        false
+    }
+}
+
+
+#[test]
+fn models_match_brute_force() {
+    let mut rnd = Random(7);
+    let n = 10;
+    let mut totals = [Counts::default(), Counts::default()];
+    for round in 0 .. 60 {
+        let clauses : Vec<Vec<(usize, bool)>> = (0 .. 5 + rnd.next(25)).map(|_| {
+            (0 .. 3).map(|_| (rnd.next(n as u64) as usize, rnd.next(2) == 0)).collect()
+        }).collect();
+        let observed : Vec<usize> = (0 .. n).filter(|_| rnd.next(3) != 0).collect();
+        let k = rnd.next(observed.len() as u64 + 1) as usize;
+
+        let value = |mask : usize, (v, sign) : (usize, bool)| (mask & (1 << v) != 0) != sign;
+        let expected = (0 .. 1 << n).filter(|&mask| {
+            clauses.iter().all(|c| c.iter().any(|&x| value(mask, x))) &&
+            observed.iter().filter(|&&v| mask & (1 << v) != 0).count() <= k
+        }).count();
+
+        for (mode, &eager) in [true, false].iter().enumerate() {
+            let counts = Rc::new(RefCell::new(Counts::default()));
+            let mut solver = CoreSolver::new(Default::default());
+            let vars : Vec<Var> = (0 .. n).map(|_| solver.newVar(None, true)).collect();
+            for c in clauses.iter() {
+                solver.addClause(&c.iter().map(|&(v, sign)| vars[v].lit(sign)).collect::<Vec<Lit>>());
+            }
+
+            let propagator = AtMost { vars    : observed.iter().map(|&v| vars[v]).collect()
+                                    , k       : k
+                                    , eager   : eager
+                                    , trail   : Vec::new()
+                                    , levels  : Vec::new()
+                                    , clauses : Vec::new()
+                                    , counts  : counts.clone()
+                                    };
+            solver.connectPropagator(Box::new(propagator));
+            for &v in observed.iter() {
+                solver.observeVar(vars[v]);
+            }
+
+            // Every model, each blocked once found:
+            let mut found = 0;
+            loop {
+                match solver.solveLimited(&[]) {
+                    PartialResult::SAT(model) => {
+                        let mask = (0 .. n).filter(|&v| model[&vars[v]]).fold(0, |mask, v| mask | (1 << v));
+                        assert!(clauses.iter().all(|c| c.iter().any(|&x| value(mask, x))), "round {}", round);
+                        assert!(observed.iter().filter(|&&v| model[&vars[v]]).count() <= k, "round {}", round);
+                        found += 1;
+                        if !solver.addClause(&vars.iter().map(|&v| v.lit(model[&v])).collect::<Vec<Lit>>()) { break; }
+                    }
+
+                    PartialResult::UnSAT      => { break; }
+                    _                         => { panic!("unexpected result in round {}", round); }
+                }
+            }
+            assert_eq!(found, expected, "round {}, eager {}", round, eager);
+
+            assert!(solver.disconnectPropagator().is_some());
+            let total = &mut totals[mode];
+            let counts = counts.borrow();
+            total.checks += counts.checks;
+            total.rejected += counts.rejected;
+            total.reasons += counts.reasons;
+            total.backtracks += counts.backtracks;
+        }
+    }
+
+    // Both ways of enforcing the constraint were exercised:
+    assert!(totals[0].reasons > 0 && totals[0].rejected == 0 && totals[0].backtracks > 0);
+    assert!(totals[1].rejected > 0 && totals[1].reasons == 0 && totals[1].checks > totals[1].rejected);
+}
+
+
+#[test]
+fn assumptions_and_disconnect() {
+    let counts = Rc::new(RefCell::new(Counts::default()));
+    let mut solver = CoreSolver::new(Default::default());
+    let vars : Vec<Var> = (0 .. 4).map(|_| solver.newVar(None, true)).collect();
+    solver.addClause(&[vars[0].posLit(), vars[1].posLit()]);
+
+    let propagator = AtMost { vars    : vars.clone()
+                            , k       : 1
+                            , eager   : true
+                            , trail   : Vec::new()
+                            , levels  : Vec::new()
+                            , clauses : Vec::new()
+                            , counts  : counts.clone()
+                            };
+    solver.connectPropagator(Box::new(propagator));
+    for &v in vars.iter() {
+        solver.observeVar(v);
+    }
+
+    // Two true variables contradict the propagator under assumptions only:
+    match solver.solveLimited(&[vars[2].posLit(), vars[3].posLit()]) {
+        PartialResult::UnSATUnder(_) => {}
+        _                            => { panic!("expected UNSAT under assumptions"); }
+    }
+
+    match solver.solveLimited(&[vars[0].posLit()]) {
+        PartialResult::SAT(model) => { assert!(model[&vars[0]] && !model[&vars[1]] && !model[&vars[2]] && !model[&vars[3]]); }
+        _                         => { panic!("expected SAT"); }
+    }
+
+    // What was learnt from its reasons outlives the propagator:
+    assert!(solver.disconnectPropagator().is_some());
+    assert!(solver.disconnectPropagator().is_none());
+    if let PartialResult::SAT(_) = solver.solveLimited(&[vars[2].posLit(), vars[3].posLit()]) {
+        panic!("expected UNSAT under assumptions");
+    }
+    match solver.solveLimited(&[vars[0].posLit(), vars[3].posLit()]) {
+        PartialResult::SAT(model) => { assert!(model[&vars[0]] && model[&vars[3]]); }
+        _                         => { panic!("expected SAT"); }
+    }
+}
+
+
+// Rejects every model, but never says why:
+struct Forgetful;
+
+impl UserPropagator for Forgetful {
+    fn notifyAssignment(&mut self, _ : &[Lit]) {}
+    fn notifyNewDecisionLevel(&mut self) {}
+    fn notifyBacktrack(&mut self, _ : usize) {}
+
+    fn reason(&mut self, _ : Lit) -> Vec<Lit> {
+        panic!("nothing was implied");
+    }
+
+    fn checkModel(&mut self, _ : &VarMap<bool>) -> bool {
+        false
+    }
+}
+
+
+#[test]
+fn rejection_without_a_clause_interrupts() {
+    let mut solver = CoreSolver::new(Default::default());
+    let x = solver.newVar(None, true);
+    let y = solver.newVar(None, true);
+    solver.addClause(&[x.posLit(), y.posLit()]);
+    solver.connectPropagator(Box::new(Forgetful));
+    solver.observeVar(x);
+
+    match solver.solveLimited(&[]) {
+        PartialResult::Interrupted(_) => {}
+        _                             => { panic!("expected an interrupt"); }
+    }
+
+    assert!(solver.disconnectPropagator().is_some());
+    match solver.solveLimited(&[]) {
+        PartialResult::SAT(model) => { assert!(model[&x] || model[&y]); }
+        _                         => { panic!("expected SAT"); }
+    }
+}
@@ -0,0 +1,188 @@
+extern crate minisat_rust;
+
+mod common;
+
+use std::io::Cursor;
+use minisat_rust::sat::{PartialResult, TotalResult, Solver};
+use minisat_rust::sat::allsat::Enumerator;
+use minisat_rust::sat::dimacs;
+use minisat_rust::sat::encode;
+use minisat_rust::sat::formula::{Var, Lit};
+use minisat_rust::sat::minisat::{self, CoreSolver};
+use minisat_rust::sat::minisat::simp::SimpSolver;
+use common::Random;
+
+
+fn lits(rnd : &mut Random, n : usize, len : usize) -> Vec<i32> {
+    (0 .. len).map(|_| rnd.lit(n)).collect()
+}
+
+
+fn value(mask : usize, id : i32) -> bool {
+    (mask & (1 << (id.abs() - 1)) != 0) == (id > 0)
+}
+
+
+fn to_dimacs(n : usize, xors : &[Vec<i32>], clauses : &[Vec<i32>]) -> String {
+    let mut text = format!("p cnf {} {}\n", n, xors.len() + clauses.len());
+    for (prefix, c) in xors.iter().map(|c| ("x", c)).chain(clauses.iter().map(|c| ("", c))) {
+        text.push_str(prefix);
+        for id in c.iter() {
+            text.push_str(&format!("{} ", id));
+        }
+        text.push_str("0\n");
+    }
+    text
+}
+
+
+fn to_lits(vars : &[Var], ids : &[i32]) -> Vec<Lit> {
+    ids.iter().map(|&id| vars[(id.abs() - 1) as usize].lit(id < 0)).collect()
+}
+
+
+#[test]
+fn dimacs_models_match_brute_force() {
+    let mut rnd = Random(3);
+    let n = 8;
+    for _ in 0 .. 200 {
+        let xors : Vec<Vec<i32>> = (0 .. 1 + rnd.next(5)).map(|_| { let len = 1 + rnd.next(6) as usize; lits(&mut rnd, n, len) }).collect();
+        let clauses : Vec<Vec<i32>> = (0 .. rnd.next(4)).map(|_| lits(&mut rnd, n, 3)).collect();
+        let expected = (0 .. 1 << n).filter(|&mask| {
+            xors.iter().all(|c| c.iter().filter(|&&id| value(mask, id)).count() % 2 == 1) &&
+            clauses.iter().all(|c| c.iter().any(|&id| value(mask, id)))
+        }).count();
+
+        let text = to_dimacs(n, &xors, &clauses);
+
+        // Every model, with native constraints and with clauses for shrinking:
+        for &shrink in [false, true].iter() {
+            let mut en = Enumerator::new(CoreSolver::new(Default::default()), shrink);
+            let parsed = dimacs::parse(&mut Cursor::new(text.as_bytes()), &mut en, true).unwrap();
+
+            // Shrinking introduces variables of its own:
+            en.setProjection(parsed.backward_subst.iter().map(|(v, _)| v).collect());
+            let count : usize = en.map(|model| {
+                1 << (n - model.iter().filter(|&(v, _)| parsed.backward_subst.get(&v).is_some()).count())
+            }).sum();
+            assert_eq!(count, expected, "{}", text);
+        }
+
+        // One model, checked against the file:
+        let mut solver = SimpSolver::new(Default::default());
+        let parsed = dimacs::parse(&mut Cursor::new(text.as_bytes()), &mut solver, true).unwrap();
+        let result = if solver.preprocess() { solver.solve() } else { TotalResult::UnSAT };
+        match result {
+            TotalResult::SAT(model) => {
+                assert!(expected > 0);
+                assert!(dimacs::validateModel(&mut Cursor::new(text.as_bytes()), &parsed.backward_subst, &model).unwrap(), "{}", text);
+            }
+            TotalResult::UnSAT      => { assert_eq!(expected, 0, "{}", text); }
+            _                       => { panic!("unexpected result"); }
+        }
+    }
+}
+
+
+#[test]
+fn native_agrees_with_encoding() {
+    let mut rnd = Random(5);
+    let n = 60;
+    for round in 0 .. 40 {
+        let xors : Vec<Vec<i32>> = (0 .. 20 + round % 20).map(|_| { let len = 2 + rnd.next(8) as usize; lits(&mut rnd, n, len) }).collect();
+        let clauses : Vec<Vec<i32>> = (0 .. 150).map(|_| lits(&mut rnd, n, 3)).collect();
+
+        let mut native = CoreSolver::new(Default::default());
+        let mut encoded = CoreSolver::new(Default::default());
+        let native_vars : Vec<Var> = (0 .. n).map(|_| native.newVar(None, true)).collect();
+        let encoded_vars : Vec<Var> = (0 .. n).map(|_| encoded.newVar(None, true)).collect();
+        for c in xors.iter() {
+            native.addXor(&to_lits(&native_vars, c));
+            encode::xor(&mut encoded, &to_lits(&encoded_vars, c));
+        }
+        for c in clauses.iter() {
+            native.addClause(&to_lits(&native_vars, c));
+            encoded.addClause(&to_lits(&encoded_vars, c));
+        }
+
+        // Some assumptions, so that the solvers have to backtrack over them:
+        let assumptions = lits(&mut rnd, n, 3);
+        let native_result = native.solveLimited(&to_lits(&native_vars, &assumptions));
+        let encoded_result = encoded.solveLimited(&to_lits(&encoded_vars, &assumptions));
+        match (native_result, encoded_result) {
+            (PartialResult::SAT(model), PartialResult::SAT(_)) => {
+                let value = |id : i32| -> bool { model[&native_vars[(id.abs() - 1) as usize]] == (id > 0) };
+                for c in xors.iter() {
+                    assert!(c.iter().filter(|&&id| value(id)).count() % 2 == 1);
+                }
+                for c in clauses.iter() {
+                    assert!(c.iter().any(|&id| value(id)));
+                }
+                assert!(assumptions.iter().all(|&id| value(id)));
+            }
+
+            (PartialResult::UnSAT, PartialResult::UnSAT)                 => {}
+            (PartialResult::UnSATUnder(_), PartialResult::UnSATUnder(_)) => {}
+            (PartialResult::UnSAT, PartialResult::UnSATUnder(_))         => {}
+            (PartialResult::UnSATUnder(_), PartialResult::UnSAT)         => {}
+            _                                                            => { panic!("results differ in round {}", round); }
+        }
+    }
+}
+
+
+#[test]
+fn recovered_from_clauses() {
+    // 'a ^ b ^ c', 'c ^ d ^ e' and 'a ^ b ^ d ^ e' can not all be odd, which takes elimination to see:
+    let mut clauses = Vec::new();
+    for vars in [vec![1, 2, 3], vec![3, 4, 5], vec![1, 2, 4, 5]].iter() {
+        for mask in 0 .. 1 << vars.len() {
+            if (mask as u32).count_ones() % 2 == 0 {
+                clauses.push(vars.iter().enumerate().map(|(i, &id)| if mask & (1 << i) != 0 { -id } else { id }).collect());
+            }
+        }
+    }
+    let text = to_dimacs(5, &[], &clauses);
+
+    for &recover in [true, false].iter() {
+        let mut settings : minisat::Settings = Default::default();
+        settings.core.recover_xors = recover;
+        let mut solver = CoreSolver::new(settings);
+        dimacs::parse(&mut Cursor::new(text.as_bytes()), &mut solver, true).unwrap();
+        assert_eq!(solver.findXors().len(), 3);
+        assert_eq!(solver.preprocess(), !recover);
+    }
+
+    // Recovered constraints keep their parity:
+    let text = to_dimacs(5, &[], &clauses[.. 8]);
+    let mut solver = SimpSolver::new(Default::default());
+    let parsed = dimacs::parse(&mut Cursor::new(text.as_bytes()), &mut solver, true).unwrap();
+    assert!(solver.preprocess());
+    match solver.solve() {
+        TotalResult::SAT(model) => { assert!(dimacs::validateModel(&mut Cursor::new(text.as_bytes()), &parsed.backward_subst, &model).unwrap()); }
+        _                       => { panic!("expected SAT"); }
+    }
+}
+
+
+#[test]
+fn released_variables_keep_their_rows() {
+    let mut solver = CoreSolver::new(Default::default());
+    let x = solver.newVar(None, true);
+    let y = solver.newVar(None, true);
+    let z = solver.newVar(None, true);
+    solver.addXor(&[x.posLit(), y.posLit(), z.posLit()]);
+    solver.releaseVar(x.posLit());
+    assert!(solver.simplify());
+
+    // A fresh variable must not take the place of 'x' in the row:
+    let w = solver.newVar(None, true);
+    assert!(w != x);
+    solver.addClause(&[w.negLit()]);
+    for &lit in [y.posLit(), y.negLit()].iter() {
+        match solver.solveLimited(&[lit]) {
+            PartialResult::SAT(model) => { assert_eq!(model[&y], model[&z]); }
+            _                         => { panic!("expected SAT"); }
+        }
+    }
+}